@@ -55,9 +55,7 @@ use std::ptr;
 #[doc(hidden)]
 pub mod export {
     pub use libc::{c_char, c_int};
-    pub use openssl_sys::{
-        init, ERR_get_next_error_library, ERR_load_strings, ERR_PACK, ERR_STRING_DATA,
-    };
+    pub use openssl_sys::{init, ERR_get_next_error_library, ERR_PACK, ERR_STRING_DATA};
     pub use std::borrow::Cow;
     pub use std::option::Option;
     pub use std::ptr::null;
@@ -71,13 +69,24 @@ pub trait Library {
 }
 
 /// A function declaration, parameterized by its error library.
-pub struct Function<T>(c_int, PhantomData<T>);
+// The name is only used on OpenSSL 3.0 and newer, which have no function codes.
+#[cfg_attr(not(ossl300), allow(dead_code))]
+pub struct Function<T>(c_int, &'static str, PhantomData<T>);
 
 impl<T> Function<T> {
     /// Creates a function from its raw identifier.
     #[inline]
     pub const fn from_raw(raw: c_int) -> Function<T> {
-        Function(raw, PhantomData)
+        Function(raw, "", PhantomData)
+    }
+
+    /// This is not considered part of this crate's public API. It is subject to change at any time.
+    ///
+    /// `name` must be null-terminated.
+    #[doc(hidden)]
+    #[inline]
+    pub const fn __from_raw_named(raw: c_int, name: &'static str) -> Function<T> {
+        Function(raw, name, PhantomData)
     }
 
     /// Returns the function's raw identifier.
@@ -119,13 +128,7 @@ pub unsafe fn __put_error<T>(
 ) where
     T: Library,
 {
-    openssl_sys::ERR_put_error(
-        T::id(),
-        func.as_raw(),
-        reason.as_raw(),
-        file.as_ptr() as *const c_char,
-        line as c_int,
-    );
+    put_error_code(func, reason, file, line);
     let data = match message {
         Some(Cow::Borrowed(s)) => Some((s.as_ptr() as *const c_char as *mut c_char, 0)),
         Some(Cow::Owned(s)) => {
@@ -148,6 +151,65 @@ pub unsafe fn __put_error<T>(
     }
 }
 
+#[cfg(ossl300)]
+unsafe fn put_error_code<T>(func: Function<T>, reason: Reason<T>, file: &'static str, line: u32)
+where
+    T: Library,
+{
+    // OpenSSL 3.0 no longer has function codes, only the function's name.
+    let func = if func.1.is_empty() {
+        ptr::null()
+    } else {
+        func.1.as_ptr() as *const c_char
+    };
+    openssl_sys::ERR_new();
+    openssl_sys::ERR_set_debug(file.as_ptr() as *const c_char, line as c_int, func);
+    openssl_sys::ERR_set_error(T::id(), reason.as_raw(), ptr::null());
+}
+
+#[cfg(not(ossl300))]
+unsafe fn put_error_code<T>(func: Function<T>, reason: Reason<T>, file: &'static str, line: u32)
+where
+    T: Library,
+{
+    openssl_sys::ERR_put_error(
+        T::id(),
+        func.as_raw(),
+        reason.as_raw(),
+        file.as_ptr() as *const c_char,
+        line as c_int,
+    );
+}
+
+/// This is not considered part of this crate's public API. It is subject to change at any time.
+///
+/// # Safety
+///
+/// `strings` must point to a table terminated by an entry with a null string.
+#[doc(hidden)]
+pub unsafe fn __load_strings(lib: c_int, strings: *mut openssl_sys::ERR_STRING_DATA) {
+    // Function codes pack to 0 on OpenSSL 3.0, which would end the table early, so drop their
+    // entries.
+    #[cfg(ossl300)]
+    {
+        let mut out = strings.add(1);
+        let mut cur = strings.add(1);
+        loop {
+            let done = (*cur).string.is_null();
+            if (*cur).error != 0 || done {
+                (*out).error = (*cur).error;
+                (*out).string = (*cur).string;
+                out = out.add(1);
+            }
+            if done {
+                break;
+            }
+            cur = cur.add(1);
+        }
+    }
+    openssl_sys::ERR_load_strings(lib, strings);
+}
+
 /// Pushes an error onto the OpenSSL error stack.
 ///
 /// A function and reason are required, and must be associated with the same error library. An additional formatted
@@ -254,7 +316,7 @@ macro_rules! openssl_errors {
                         $crate::export::init();
                         LIB_NUM = $crate::export::ERR_get_next_error_library();
                         STRINGS[0].error = $crate::export::ERR_PACK(LIB_NUM, 0, 0);
-                        $crate::export::ERR_load_strings(LIB_NUM, STRINGS.as_mut_ptr());
+                        $crate::__load_strings(LIB_NUM, STRINGS.as_mut_ptr());
                     });
 
                     LIB_NUM
@@ -263,13 +325,14 @@ macro_rules! openssl_errors {
         }
 
         impl $lib_name {
-            $crate::openssl_errors!(@func_consts $lib_name; 1; $($(#[$func_attr])* $func_name;)*);
+            $crate::openssl_errors!(@func_consts $lib_name; 1; $($(#[$func_attr])* $func_name($func_str);)*);
             $crate::openssl_errors!(@reason_consts $lib_name; 1; $($(#[$reason_attr])* $reason_name;)*);
         }
     )*};
-    (@func_consts $lib_name:ident; $n:expr; $(#[$attr:meta])* $name:ident; $($tt:tt)*) => {
+    (@func_consts $lib_name:ident; $n:expr; $(#[$attr:meta])* $name:ident($str:expr); $($tt:tt)*) => {
         $(#[$attr])*
-        pub const $name: $crate::Function<$lib_name> = $crate::Function::from_raw($n);
+        pub const $name: $crate::Function<$lib_name> =
+            $crate::Function::__from_raw_named($n, concat!($str, "\0"));
         $crate::openssl_errors!(@func_consts $lib_name; $n + 1; $($tt)*);
     };
     (@func_consts $lib_name:ident; $n:expr;) => {};
@@ -4,20 +4,41 @@
 #define VERSION2(n, v) RUST_VERSION_##n##_##v
 #define VERSION(n, v) VERSION2(n, v)
 
+#define NEW_VERSION2(a, b, c) RUST_VERSION_NEW_OPENSSL_##a##_##b##_##c
+#define NEW_VERSION(a, b, c) NEW_VERSION2(a, b, c)
+
 #ifdef LIBRESSL_VERSION_NUMBER
 VERSION(LIBRESSL, LIBRESSL_VERSION_NUMBER)
+#elif defined OPENSSL_VERSION_MAJOR
+NEW_VERSION(OPENSSL_VERSION_MAJOR, OPENSSL_VERSION_MINOR, OPENSSL_VERSION_PATCH)
 #else
 VERSION(OPENSSL, OPENSSL_VERSION_NUMBER)
 #endif
 
+#ifdef OPENSSL_NO_ARIA
+RUST_CONF_OPENSSL_NO_ARIA
+#endif
+
 #ifdef OPENSSL_NO_BF
 RUST_CONF_OPENSSL_NO_BF
 #endif
 
+#ifdef OPENSSL_NO_BLAKE2
+RUST_CONF_OPENSSL_NO_BLAKE2
+#endif
+
 #ifdef OPENSSL_NO_BUF_FREELISTS
 RUST_CONF_OPENSSL_NO_BUF_FREELISTS
 #endif
 
+#ifdef OPENSSL_NO_CAMELLIA
+RUST_CONF_OPENSSL_NO_CAMELLIA
+#endif
+
+#ifdef OPENSSL_NO_CAST
+RUST_CONF_OPENSSL_NO_CAST
+#endif
+
 #ifdef OPENSSL_NO_CMS
 RUST_CONF_OPENSSL_NO_CMS
 #endif
@@ -62,6 +83,10 @@ RUST_CONF_OPENSSL_NO_RFC3779
 RUST_CONF_OPENSSL_NO_RMD160
 #endif
 
+#ifdef OPENSSL_NO_SEED
+RUST_CONF_OPENSSL_NO_SEED
+#endif
+
 #ifdef OPENSSL_NO_SHA
 RUST_CONF_OPENSSL_NO_SHA
 #endif
@@ -85,3 +110,7 @@ RUST_CONF_OPENSSL_NO_STDIO
 #ifdef OPENSSL_NO_SM3
 RUST_CONF_OPENSSL_NO_SM3
 #endif
+
+#ifdef OPENSSL_NO_SM4
+RUST_CONF_OPENSSL_NO_SM4
+#endif
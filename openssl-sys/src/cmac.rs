@@ -0,0 +1,19 @@
+use libc::*;
+
+use *;
+
+pub enum CMAC_CTX {}
+
+extern "C" {
+    pub fn CMAC_CTX_new() -> *mut CMAC_CTX;
+    pub fn CMAC_CTX_free(ctx: *mut CMAC_CTX);
+    pub fn CMAC_Init(
+        ctx: *mut CMAC_CTX,
+        key: *const c_void,
+        keylen: size_t,
+        cipher: *const EVP_CIPHER,
+        impl_: *mut ENGINE,
+    ) -> c_int;
+    pub fn CMAC_Update(ctx: *mut CMAC_CTX, data: *const c_void, dlen: size_t) -> c_int;
+    pub fn CMAC_Final(ctx: *mut CMAC_CTX, out: *mut c_uchar, poutlen: *mut size_t) -> c_int;
+}
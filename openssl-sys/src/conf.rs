@@ -1,7 +1,41 @@
+use libc::*;
+
 use *;
 
+#[repr(C)]
+pub struct CONF_VALUE {
+    pub section: *mut c_char,
+    pub name: *mut c_char,
+    pub value: *mut c_char,
+}
+
+stack!(stack_st_CONF_VALUE);
+
+pub const CONF_R_NO_SECTION: c_int = 107;
+
+pub const CONF_MFLAGS_IGNORE_ERRORS: c_ulong = 0x1;
+pub const CONF_MFLAGS_IGNORE_RETURN_CODES: c_ulong = 0x2;
+pub const CONF_MFLAGS_SILENT: c_ulong = 0x4;
+pub const CONF_MFLAGS_NO_DSO: c_ulong = 0x8;
+pub const CONF_MFLAGS_IGNORE_MISSING_FILE: c_ulong = 0x10;
+#[cfg(ossl110)]
+pub const CONF_MFLAGS_DEFAULT_SECTION: c_ulong = 0x20;
+
 extern "C" {
     pub fn NCONF_new(meth: *mut CONF_METHOD) -> *mut CONF;
     pub fn NCONF_default() -> *mut CONF_METHOD;
     pub fn NCONF_free(conf: *mut CONF);
+    pub fn NCONF_load(conf: *mut CONF, file: *const c_char, eline: *mut c_long) -> c_int;
+    pub fn NCONF_get_string(
+        conf: *const CONF,
+        group: *const c_char,
+        name: *const c_char,
+    ) -> *mut c_char;
+    pub fn NCONF_get_section(conf: *const CONF, section: *const c_char)
+        -> *mut stack_st_CONF_VALUE;
+    pub fn CONF_modules_load_file(
+        filename: *const c_char,
+        appname: *const c_char,
+        flags: c_ulong,
+    ) -> c_int;
 }
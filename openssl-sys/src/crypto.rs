@@ -121,10 +121,49 @@ cfg_if! {
 }
 
 extern "C" {
-    #[cfg(ossl101)]
+    #[cfg(all(ossl101, not(ossl300)))]
     pub fn FIPS_mode() -> c_int;
-    #[cfg(ossl101)]
+    #[cfg(all(ossl101, not(ossl300)))]
     pub fn FIPS_mode_set(onoff: c_int) -> c_int;
 
     pub fn CRYPTO_memcmp(a: *const c_void, b: *const c_void, len: size_t) -> c_int;
+
+    pub fn OPENSSL_cleanse(ptr: *mut c_void, len: size_t);
+}
+
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_NO_LOAD_CRYPTO_STRINGS: u64 = 0x00000001;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_LOAD_CRYPTO_STRINGS: u64 = 0x00000002;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_LOAD_CONFIG: u64 = 0x00000040;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_NO_LOAD_CONFIG: u64 = 0x00000080;
+
+extern "C" {
+    #[cfg(ossl110)]
+    pub fn OPENSSL_init_crypto(opts: u64, settings: *const OPENSSL_INIT_SETTINGS) -> c_int;
+    #[cfg(ossl110)]
+    pub fn OPENSSL_INIT_new() -> *mut OPENSSL_INIT_SETTINGS;
+    #[cfg(ossl110)]
+    pub fn OPENSSL_INIT_free(settings: *mut OPENSSL_INIT_SETTINGS);
+    #[cfg(ossl111b)]
+    pub fn OPENSSL_INIT_set_config_filename(
+        settings: *mut OPENSSL_INIT_SETTINGS,
+        config_filename: *const c_char,
+    ) -> c_int;
+    #[cfg(ossl110)]
+    pub fn OPENSSL_INIT_set_config_appname(
+        settings: *mut OPENSSL_INIT_SETTINGS,
+        config_appname: *const c_char,
+    ) -> c_int;
+}
+
+extern "C" {
+    #[cfg(ossl300)]
+    pub fn OSSL_LIB_CTX_new() -> *mut OSSL_LIB_CTX;
+    #[cfg(ossl300)]
+    pub fn OSSL_LIB_CTX_free(ctx: *mut OSSL_LIB_CTX);
+    #[cfg(ossl300)]
+    pub fn OSSL_LIB_CTX_load_config(ctx: *mut OSSL_LIB_CTX, config_file: *const c_char) -> c_int;
 }
@@ -2,6 +2,12 @@ use libc::*;
 
 use *;
 
+#[repr(C)]
+pub struct EC_builtin_curve {
+    pub nid: c_int,
+    pub comment: *const c_char,
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub enum point_conversion_form_t {
@@ -81,6 +87,8 @@ extern "C" {
 
     pub fn EC_GROUP_new_by_curve_name(nid: c_int) -> *mut EC_GROUP;
 
+    pub fn EC_get_builtin_curves(r: *mut EC_builtin_curve, nitems: size_t) -> size_t;
+
     pub fn EC_POINT_is_at_infinity(group: *const EC_GROUP, point: *const EC_POINT) -> c_int;
 
     pub fn EC_POINT_is_on_curve(
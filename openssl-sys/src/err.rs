@@ -3,25 +3,74 @@ use libc::*;
 pub const ERR_TXT_MALLOCED: c_int = 0x01;
 pub const ERR_TXT_STRING: c_int = 0x02;
 
+pub const ERR_LIB_SYS: c_int = 2;
+pub const ERR_LIB_EVP: c_int = 6;
 pub const ERR_LIB_PEM: c_int = 9;
+pub const ERR_LIB_CONF: c_int = 14;
+pub const ERR_LIB_SSL: c_int = 20;
+#[cfg(ossl300)]
+pub const ERR_LIB_PROV: c_int = 57;
 
-const_fn! {
-    pub const fn ERR_PACK(l: c_int, f: c_int, r: c_int) -> c_ulong {
-        ((l as c_ulong & 0x0FF) << 24) |
-        ((f as c_ulong & 0xFFF) << 12) |
-        (r as c_ulong & 0xFFF)
-    }
+cfg_if! {
+    if #[cfg(ossl300)] {
+        pub const ERR_SYSTEM_FLAG: c_ulong = c_int::max_value() as c_ulong + 1;
+        pub const ERR_SYSTEM_MASK: c_ulong = c_int::max_value() as c_ulong;
 
-    pub const fn ERR_GET_LIB(l: c_ulong) -> c_int {
-        ((l >> 24) & 0x0FF) as c_int
-    }
+        pub const ERR_LIB_OFFSET: c_ulong = 23;
+        pub const ERR_LIB_MASK: c_ulong = 0xff;
+        pub const ERR_REASON_MASK: c_ulong = 0x7fffff;
 
-    pub const fn ERR_GET_FUNC(l: c_ulong) -> c_int {
-        ((l >> 12) & 0xFFF) as c_int
-    }
+        pub const ERR_RFLAGS_OFFSET: c_int = 18;
+        pub const ERR_RFLAG_COMMON: c_int = 0x2 << ERR_RFLAGS_OFFSET;
+
+        pub const ERR_R_PASSED_INVALID_ARGUMENT: c_int = 262 | ERR_RFLAG_COMMON;
+
+        const_fn! {
+            pub const fn ERR_PACK(l: c_int, _f: c_int, r: c_int) -> c_ulong {
+                ((l as c_ulong & ERR_LIB_MASK) << ERR_LIB_OFFSET) |
+                (r as c_ulong & ERR_REASON_MASK)
+            }
+        }
+
+        pub fn ERR_GET_LIB(l: c_ulong) -> c_int {
+            if l & ERR_SYSTEM_FLAG != 0 {
+                return ERR_LIB_SYS;
+            }
+            ((l >> ERR_LIB_OFFSET) & ERR_LIB_MASK) as c_int
+        }
+
+        pub fn ERR_GET_FUNC(_l: c_ulong) -> c_int {
+            0
+        }
+
+        pub fn ERR_GET_REASON(l: c_ulong) -> c_int {
+            if l & ERR_SYSTEM_FLAG != 0 {
+                return (l & ERR_SYSTEM_MASK) as c_int;
+            }
+            (l & ERR_REASON_MASK) as c_int
+        }
+    } else {
+        pub const ERR_R_PASSED_INVALID_ARGUMENT: c_int = 7;
 
-    pub const fn ERR_GET_REASON(l: c_ulong) -> c_int {
-        (l & 0xFFF) as c_int
+        const_fn! {
+            pub const fn ERR_PACK(l: c_int, f: c_int, r: c_int) -> c_ulong {
+                ((l as c_ulong & 0x0FF) << 24) |
+                ((f as c_ulong & 0xFFF) << 12) |
+                (r as c_ulong & 0xFFF)
+            }
+
+            pub const fn ERR_GET_LIB(l: c_ulong) -> c_int {
+                ((l >> 24) & 0x0FF) as c_int
+            }
+
+            pub const fn ERR_GET_FUNC(l: c_ulong) -> c_int {
+                ((l >> 12) & 0xFFF) as c_int
+            }
+
+            pub const fn ERR_GET_REASON(l: c_ulong) -> c_int {
+                (l & 0xFFF) as c_int
+            }
+        }
     }
 }
 
@@ -32,10 +81,25 @@ pub struct ERR_STRING_DATA {
 }
 
 extern "C" {
+    #[cfg(ossl300)]
+    pub fn ERR_new();
+    #[cfg(ossl300)]
+    pub fn ERR_set_debug(file: *const c_char, line: c_int, func: *const c_char);
+    #[cfg(ossl300)]
+    pub fn ERR_set_error(lib: c_int, reason: c_int, fmt: *const c_char, ...);
+    #[cfg(not(ossl300))]
     pub fn ERR_put_error(lib: c_int, func: c_int, reason: c_int, file: *const c_char, line: c_int);
     pub fn ERR_set_error_data(data: *mut c_char, flags: c_int);
 
     pub fn ERR_get_error() -> c_ulong;
+    #[cfg(ossl300)]
+    pub fn ERR_get_error_all(
+        file: *mut *const c_char,
+        line: *mut c_int,
+        func: *mut *const c_char,
+        data: *mut *const c_char,
+        flags: *mut c_int,
+    ) -> c_ulong;
     pub fn ERR_get_error_line_data(
         file: *mut *const c_char,
         line: *mut c_int,
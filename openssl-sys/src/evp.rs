@@ -6,6 +6,8 @@ pub const EVP_MAX_MD_SIZE: c_uint = 64;
 pub const PKCS5_SALT_LEN: c_int = 8;
 pub const PKCS12_DEFAULT_ITER: c_int = 2048;
 
+pub const EVP_R_COMMAND_NOT_SUPPORTED: c_int = 147;
+
 pub const EVP_PKEY_RSA: c_int = NID_rsaEncryption;
 pub const EVP_PKEY_DSA: c_int = NID_dsa;
 pub const EVP_PKEY_DH: c_int = NID_dhKeyAgreement;
@@ -20,22 +22,121 @@ pub const EVP_PKEY_X448: c_int = NID_X448;
 pub const EVP_PKEY_ED448: c_int = NID_ED448;
 pub const EVP_PKEY_HMAC: c_int = NID_hmac;
 pub const EVP_PKEY_CMAC: c_int = NID_cmac;
+#[cfg(ossl111)]
+pub const EVP_PKEY_POLY1305: c_int = NID_poly1305;
+#[cfg(ossl111)]
+pub const EVP_PKEY_SIPHASH: c_int = NID_siphash;
+#[cfg(ossl110)]
+pub const EVP_PKEY_TLS1_PRF: c_int = NID_tls1_prf;
+#[cfg(ossl110)]
+pub const EVP_PKEY_HKDF: c_int = NID_hkdf;
 
 pub const EVP_CTRL_GCM_SET_IVLEN: c_int = 0x9;
 pub const EVP_CTRL_GCM_GET_TAG: c_int = 0x10;
 pub const EVP_CTRL_GCM_SET_TAG: c_int = 0x11;
 
+pub const EVP_CIPHER_CTX_FLAG_WRAP_ALLOW: c_int = 0x1;
+
+pub const EVP_CIPH_FLAG_AEAD_CIPHER: c_ulong = 0x200000;
+
 pub unsafe fn EVP_get_digestbynid(type_: c_int) -> *const EVP_MD {
     EVP_get_digestbyname(OBJ_nid2sn(type_))
 }
 
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn EVP_MD_get_size(md: *const EVP_MD) -> c_int;
+            pub fn EVP_MD_get_type(md: *const EVP_MD) -> c_int;
+
+            pub fn EVP_CIPHER_get_key_length(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_get_block_size(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_get_iv_length(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_get_nid(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_get_flags(cipher: *const EVP_CIPHER) -> c_ulong;
+        }
+
+        #[inline]
+        pub unsafe fn EVP_MD_size(md: *const EVP_MD) -> c_int {
+            EVP_MD_get_size(md)
+        }
+
+        #[inline]
+        pub unsafe fn EVP_MD_type(md: *const EVP_MD) -> c_int {
+            EVP_MD_get_type(md)
+        }
+
+        #[inline]
+        pub unsafe fn EVP_CIPHER_key_length(cipher: *const EVP_CIPHER) -> c_int {
+            EVP_CIPHER_get_key_length(cipher)
+        }
+
+        #[inline]
+        pub unsafe fn EVP_CIPHER_block_size(cipher: *const EVP_CIPHER) -> c_int {
+            EVP_CIPHER_get_block_size(cipher)
+        }
+
+        #[inline]
+        pub unsafe fn EVP_CIPHER_iv_length(cipher: *const EVP_CIPHER) -> c_int {
+            EVP_CIPHER_get_iv_length(cipher)
+        }
+
+        #[inline]
+        pub unsafe fn EVP_CIPHER_nid(cipher: *const EVP_CIPHER) -> c_int {
+            EVP_CIPHER_get_nid(cipher)
+        }
+
+        #[inline]
+        pub unsafe fn EVP_CIPHER_flags(cipher: *const EVP_CIPHER) -> c_ulong {
+            EVP_CIPHER_get_flags(cipher)
+        }
+    } else {
+        extern "C" {
+            pub fn EVP_MD_size(md: *const EVP_MD) -> c_int;
+            pub fn EVP_MD_type(md: *const EVP_MD) -> c_int;
+
+            pub fn EVP_CIPHER_key_length(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_block_size(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_iv_length(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_nid(cipher: *const EVP_CIPHER) -> c_int;
+            pub fn EVP_CIPHER_flags(cipher: *const EVP_CIPHER) -> c_ulong;
+        }
+    }
+}
+
 extern "C" {
-    pub fn EVP_MD_size(md: *const EVP_MD) -> c_int;
-    pub fn EVP_MD_type(md: *const EVP_MD) -> c_int;
+    pub fn EVP_CIPHER_do_all_sorted(
+        fn_: Option<
+            unsafe extern "C" fn(*const EVP_CIPHER, *const c_char, *const c_char, *mut c_void),
+        >,
+        arg: *mut c_void,
+    );
+    pub fn EVP_MD_do_all_sorted(
+        fn_: Option<unsafe extern "C" fn(*const EVP_MD, *const c_char, *const c_char, *mut c_void)>,
+        arg: *mut c_void,
+    );
+}
 
-    pub fn EVP_CIPHER_key_length(cipher: *const EVP_CIPHER) -> c_int;
-    pub fn EVP_CIPHER_block_size(cipher: *const EVP_CIPHER) -> c_int;
-    pub fn EVP_CIPHER_iv_length(cipher: *const EVP_CIPHER) -> c_int;
+#[cfg(ossl300)]
+extern "C" {
+    pub fn EVP_MD_fetch(
+        ctx: *mut OSSL_LIB_CTX,
+        algorithm: *const c_char,
+        properties: *const c_char,
+    ) -> *mut EVP_MD;
+    pub fn EVP_MD_up_ref(md: *mut EVP_MD) -> c_int;
+    pub fn EVP_MD_free(md: *mut EVP_MD);
+    pub fn EVP_MD_get0_name(md: *const EVP_MD) -> *const c_char;
+    pub fn EVP_MD_get_block_size(md: *const EVP_MD) -> c_int;
+
+    pub fn EVP_CIPHER_fetch(
+        ctx: *mut OSSL_LIB_CTX,
+        algorithm: *const c_char,
+        properties: *const c_char,
+    ) -> *mut EVP_CIPHER;
+    pub fn EVP_CIPHER_up_ref(cipher: *mut EVP_CIPHER) -> c_int;
+    pub fn EVP_CIPHER_free(cipher: *mut EVP_CIPHER);
+    pub fn EVP_CIPHER_get0_name(cipher: *const EVP_CIPHER) -> *const c_char;
 }
 
 cfg_if! {
@@ -43,6 +144,8 @@ cfg_if! {
         extern "C" {
             pub fn EVP_MD_CTX_new() -> *mut EVP_MD_CTX;
             pub fn EVP_MD_CTX_free(ctx: *mut EVP_MD_CTX);
+            pub fn EVP_MD_CTX_reset(ctx: *mut EVP_MD_CTX) -> c_int;
+            pub fn EVP_CIPHER_CTX_reset(ctx: *mut EVP_CIPHER_CTX) -> c_int;
         }
     } else {
         extern "C" {
@@ -61,6 +164,8 @@ extern "C" {
     pub fn EVP_DigestFinal(ctx: *mut EVP_MD_CTX, res: *mut u8, n: *mut u32) -> c_int;
     #[cfg(ossl111)]
     pub fn EVP_DigestFinalXOF(ctx: *mut EVP_MD_CTX, res: *mut u8, len: usize) -> c_int;
+    #[cfg(ossl330)]
+    pub fn EVP_DigestSqueeze(ctx: *mut EVP_MD_CTX, out: *mut u8, outlen: size_t) -> c_int;
 
     pub fn EVP_BytesToKey(
         typ: *const EVP_CIPHER,
@@ -174,9 +279,22 @@ extern "C" {
         outl: *mut c_int,
     ) -> c_int;
 }
-const_ptr_api! {
-    extern "C" {
-        pub fn EVP_PKEY_size(pkey: #[const_ptr_if(any(ossl111b, libressl280))] EVP_PKEY) -> c_int;
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn EVP_PKEY_get_size(pkey: *const EVP_PKEY) -> c_int;
+        }
+
+        #[inline]
+        pub unsafe fn EVP_PKEY_size(pkey: *const EVP_PKEY) -> c_int {
+            EVP_PKEY_get_size(pkey)
+        }
+    } else {
+        const_ptr_api! {
+            extern "C" {
+                pub fn EVP_PKEY_size(pkey: #[const_ptr_if(any(ossl111b, libressl280))] EVP_PKEY) -> c_int;
+            }
+        }
     }
 }
 cfg_if! {
@@ -213,9 +331,11 @@ const_ptr_api! {
 extern "C" {
     pub fn EVP_CIPHER_CTX_new() -> *mut EVP_CIPHER_CTX;
     pub fn EVP_CIPHER_CTX_free(ctx: *mut EVP_CIPHER_CTX);
+    pub fn EVP_CIPHER_CTX_copy(out: *mut EVP_CIPHER_CTX, in_: *const EVP_CIPHER_CTX) -> c_int;
     pub fn EVP_MD_CTX_copy_ex(dst: *mut EVP_MD_CTX, src: *const EVP_MD_CTX) -> c_int;
     pub fn EVP_CIPHER_CTX_set_key_length(ctx: *mut EVP_CIPHER_CTX, keylen: c_int) -> c_int;
     pub fn EVP_CIPHER_CTX_set_padding(ctx: *mut EVP_CIPHER_CTX, padding: c_int) -> c_int;
+    pub fn EVP_CIPHER_CTX_set_flags(ctx: *mut EVP_CIPHER_CTX, flags: c_int);
     pub fn EVP_CIPHER_CTX_ctrl(
         ctx: *mut EVP_CIPHER_CTX,
         type_: c_int,
@@ -231,6 +351,10 @@ extern "C" {
     pub fn EVP_sha384() -> *const EVP_MD;
     pub fn EVP_sha512() -> *const EVP_MD;
     #[cfg(ossl111)]
+    pub fn EVP_sha512_224() -> *const EVP_MD;
+    #[cfg(ossl111)]
+    pub fn EVP_sha512_256() -> *const EVP_MD;
+    #[cfg(ossl111)]
     pub fn EVP_sha3_224() -> *const EVP_MD;
     #[cfg(ossl111)]
     pub fn EVP_sha3_256() -> *const EVP_MD;
@@ -243,6 +367,10 @@ extern "C" {
     #[cfg(ossl111)]
     pub fn EVP_shake256() -> *const EVP_MD;
     pub fn EVP_ripemd160() -> *const EVP_MD;
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_BLAKE2")))]
+    pub fn EVP_blake2b512() -> *const EVP_MD;
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_BLAKE2")))]
+    pub fn EVP_blake2s256() -> *const EVP_MD;
     #[cfg(all(any(ossl111, libressl291), not(osslconf = "OPENSSL_NO_SM3")))]
     pub fn EVP_sm3() -> *const EVP_MD;
     pub fn EVP_des_ecb() -> *const EVP_CIPHER;
@@ -290,6 +418,140 @@ extern "C" {
     pub fn EVP_aes_256_ofb() -> *const EVP_CIPHER;
     #[cfg(ossl110)]
     pub fn EVP_aes_256_ocb() -> *const EVP_CIPHER;
+    #[cfg(ossl102)]
+    pub fn EVP_aes_128_wrap() -> *const EVP_CIPHER;
+    #[cfg(ossl102)]
+    pub fn EVP_aes_128_wrap_pad() -> *const EVP_CIPHER;
+    #[cfg(ossl102)]
+    pub fn EVP_aes_192_wrap() -> *const EVP_CIPHER;
+    #[cfg(ossl102)]
+    pub fn EVP_aes_192_wrap_pad() -> *const EVP_CIPHER;
+    #[cfg(ossl102)]
+    pub fn EVP_aes_256_wrap() -> *const EVP_CIPHER;
+    #[cfg(ossl102)]
+    pub fn EVP_aes_256_wrap_pad() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_128_ecb() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_128_cbc() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_128_cfb1() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_128_cfb8() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_128_cfb128() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_128_ofb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CAMELLIA")))]
+    pub fn EVP_camellia_128_ctr() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_192_ecb() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_192_cbc() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_192_cfb1() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_192_cfb8() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_192_cfb128() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_192_ofb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CAMELLIA")))]
+    pub fn EVP_camellia_192_ctr() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_256_ecb() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_256_cbc() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_256_cfb1() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_256_cfb8() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_256_cfb128() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn EVP_camellia_256_ofb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CAMELLIA")))]
+    pub fn EVP_camellia_256_ctr() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_ecb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_cbc() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_cfb1() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_cfb8() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_cfb128() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_ctr() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_ofb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_gcm() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_128_ccm() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_ecb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_cbc() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_cfb1() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_cfb8() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_cfb128() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_ctr() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_ofb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_gcm() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_192_ccm() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_ecb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_cbc() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_cfb1() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_cfb8() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_cfb128() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_ctr() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_ofb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_gcm() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn EVP_aria_256_ccm() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn EVP_sm4_ecb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn EVP_sm4_cbc() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn EVP_sm4_cfb128() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn EVP_sm4_ofb() -> *const EVP_CIPHER;
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn EVP_sm4_ctr() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn EVP_seed_ecb() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn EVP_seed_cbc() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn EVP_seed_cfb128() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn EVP_seed_ofb() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn EVP_cast5_ecb() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn EVP_cast5_cbc() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn EVP_cast5_cfb64() -> *const EVP_CIPHER;
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn EVP_cast5_ofb() -> *const EVP_CIPHER;
     #[cfg(ossl110)]
     pub fn EVP_chacha20() -> *const ::EVP_CIPHER;
     #[cfg(ossl110)]
@@ -300,12 +562,32 @@ extern "C" {
 
     pub fn EVP_get_digestbyname(name: *const c_char) -> *const EVP_MD;
     pub fn EVP_get_cipherbyname(name: *const c_char) -> *const EVP_CIPHER;
-
-    pub fn EVP_PKEY_id(pkey: *const EVP_PKEY) -> c_int;
 }
-const_ptr_api! {
-    extern "C" {
-        pub fn EVP_PKEY_bits(key: #[const_ptr_if(any(ossl110, libressl280))] EVP_PKEY) -> c_int;
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn EVP_PKEY_get_id(pkey: *const EVP_PKEY) -> c_int;
+            pub fn EVP_PKEY_get_bits(key: *const EVP_PKEY) -> c_int;
+        }
+
+        #[inline]
+        pub unsafe fn EVP_PKEY_id(pkey: *const EVP_PKEY) -> c_int {
+            EVP_PKEY_get_id(pkey)
+        }
+
+        #[inline]
+        pub unsafe fn EVP_PKEY_bits(pkey: *const EVP_PKEY) -> c_int {
+            EVP_PKEY_get_bits(pkey)
+        }
+    } else {
+        extern "C" {
+            pub fn EVP_PKEY_id(pkey: *const EVP_PKEY) -> c_int;
+        }
+        const_ptr_api! {
+            extern "C" {
+                pub fn EVP_PKEY_bits(key: #[const_ptr_if(any(ossl110, libressl280))] EVP_PKEY) -> c_int;
+            }
+        }
     }
 }
 extern "C" {
@@ -367,14 +649,29 @@ extern "C" {
     ) -> c_int;
 }
 
-pub const EVP_PKEY_OP_KEYGEN: c_int = 1 << 2;
-pub const EVP_PKEY_OP_SIGN: c_int = 1 << 3;
-pub const EVP_PKEY_OP_VERIFY: c_int = 1 << 4;
-pub const EVP_PKEY_OP_VERIFYRECOVER: c_int = 1 << 5;
-pub const EVP_PKEY_OP_SIGNCTX: c_int = 1 << 6;
-pub const EVP_PKEY_OP_VERIFYCTX: c_int = 1 << 7;
-pub const EVP_PKEY_OP_ENCRYPT: c_int = 1 << 8;
-pub const EVP_PKEY_OP_DECRYPT: c_int = 1 << 9;
+cfg_if! {
+    if #[cfg(ossl300)] {
+        pub const EVP_PKEY_OP_KEYGEN: c_int = 1 << 2;
+        pub const EVP_PKEY_OP_SIGN: c_int = 1 << 4;
+        pub const EVP_PKEY_OP_VERIFY: c_int = 1 << 5;
+        pub const EVP_PKEY_OP_VERIFYRECOVER: c_int = 1 << 6;
+        pub const EVP_PKEY_OP_SIGNCTX: c_int = 1 << 7;
+        pub const EVP_PKEY_OP_VERIFYCTX: c_int = 1 << 8;
+        pub const EVP_PKEY_OP_ENCRYPT: c_int = 1 << 9;
+        pub const EVP_PKEY_OP_DECRYPT: c_int = 1 << 10;
+        pub const EVP_PKEY_OP_DERIVE: c_int = 1 << 11;
+    } else {
+        pub const EVP_PKEY_OP_KEYGEN: c_int = 1 << 2;
+        pub const EVP_PKEY_OP_SIGN: c_int = 1 << 3;
+        pub const EVP_PKEY_OP_VERIFY: c_int = 1 << 4;
+        pub const EVP_PKEY_OP_VERIFYRECOVER: c_int = 1 << 5;
+        pub const EVP_PKEY_OP_SIGNCTX: c_int = 1 << 6;
+        pub const EVP_PKEY_OP_VERIFYCTX: c_int = 1 << 7;
+        pub const EVP_PKEY_OP_ENCRYPT: c_int = 1 << 8;
+        pub const EVP_PKEY_OP_DECRYPT: c_int = 1 << 9;
+        pub const EVP_PKEY_OP_DERIVE: c_int = 1 << 10;
+    }
+}
 
 pub const EVP_PKEY_OP_TYPE_SIG: c_int = EVP_PKEY_OP_SIGN
     | EVP_PKEY_OP_VERIFY
@@ -387,12 +684,20 @@ pub const EVP_PKEY_OP_TYPE_CRYPT: c_int = EVP_PKEY_OP_ENCRYPT | EVP_PKEY_OP_DECR
 pub const EVP_PKEY_CTRL_SET_MAC_KEY: c_int = 6;
 
 pub const EVP_PKEY_CTRL_CIPHER: c_int = 12;
+#[cfg(ossl111)]
+pub const EVP_PKEY_CTRL_SET_DIGEST_SIZE: c_int = 14;
 
 pub const EVP_PKEY_ALG_CTRL: c_int = 0x1000;
 
 extern "C" {
     pub fn EVP_PKEY_CTX_new(k: *mut EVP_PKEY, e: *mut ENGINE) -> *mut EVP_PKEY_CTX;
     pub fn EVP_PKEY_CTX_new_id(id: c_int, e: *mut ENGINE) -> *mut EVP_PKEY_CTX;
+    #[cfg(ossl300)]
+    pub fn EVP_PKEY_CTX_new_from_name(
+        libctx: *mut OSSL_LIB_CTX,
+        name: *const c_char,
+        propquery: *const c_char,
+    ) -> *mut EVP_PKEY_CTX;
     pub fn EVP_PKEY_CTX_free(ctx: *mut EVP_PKEY_CTX);
 
     pub fn EVP_PKEY_CTX_ctrl(
@@ -475,3 +780,12 @@ extern "C" {
     pub fn EVP_EncodeBlock(dst: *mut c_uchar, src: *const c_uchar, src_len: c_int) -> c_int;
     pub fn EVP_DecodeBlock(dst: *mut c_uchar, src: *const c_uchar, src_len: c_int) -> c_int;
 }
+
+extern "C" {
+    #[cfg(ossl300)]
+    pub fn EVP_set_default_properties(libctx: *mut OSSL_LIB_CTX, propq: *const c_char) -> c_int;
+    #[cfg(ossl300)]
+    pub fn EVP_default_properties_is_fips_enabled(libctx: *mut OSSL_LIB_CTX) -> c_int;
+    #[cfg(ossl300)]
+    pub fn EVP_default_properties_enable_fips(libctx: *mut OSSL_LIB_CTX, enable: c_int) -> c_int;
+}
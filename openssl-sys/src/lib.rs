@@ -19,6 +19,7 @@ pub use aes::*;
 pub use asn1::*;
 pub use bio::*;
 pub use bn::*;
+pub use cmac::*;
 pub use cms::*;
 pub use conf::*;
 pub use crypto::*;
@@ -29,13 +30,17 @@ pub use ec::*;
 pub use err::*;
 pub use evp::*;
 pub use hmac::*;
+pub use kdf::*;
+pub use mac::*;
 pub use obj_mac::*;
 pub use object::*;
 pub use ocsp::*;
 pub use ossl_typ::*;
+pub use params::*;
 pub use pem::*;
 pub use pkcs12::*;
 pub use pkcs7::*;
+pub use provider::*;
 pub use rand::*;
 pub use rsa::*;
 pub use safestack::*;
@@ -56,6 +61,7 @@ mod aes;
 mod asn1;
 mod bio;
 mod bn;
+mod cmac;
 mod cms;
 mod conf;
 mod crypto;
@@ -66,13 +72,17 @@ mod ec;
 mod err;
 mod evp;
 mod hmac;
+mod kdf;
+mod mac;
 mod obj_mac;
 mod object;
 mod ocsp;
 mod ossl_typ;
+mod params;
 mod pem;
 mod pkcs12;
 mod pkcs7;
+mod provider;
 mod rand;
 mod rsa;
 mod safestack;
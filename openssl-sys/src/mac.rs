@@ -0,0 +1,31 @@
+use libc::*;
+
+use *;
+
+#[cfg(ossl300)]
+extern "C" {
+    pub fn EVP_MAC_fetch(
+        libctx: *mut OSSL_LIB_CTX,
+        algorithm: *const c_char,
+        properties: *const c_char,
+    ) -> *mut EVP_MAC;
+    pub fn EVP_MAC_free(mac: *mut EVP_MAC);
+    pub fn EVP_MAC_CTX_new(mac: *mut EVP_MAC) -> *mut EVP_MAC_CTX;
+    pub fn EVP_MAC_CTX_free(ctx: *mut EVP_MAC_CTX);
+    pub fn EVP_MAC_CTX_dup(src: *const EVP_MAC_CTX) -> *mut EVP_MAC_CTX;
+    pub fn EVP_MAC_CTX_get_mac_size(ctx: *mut EVP_MAC_CTX) -> size_t;
+    pub fn EVP_MAC_CTX_set_params(ctx: *mut EVP_MAC_CTX, params: *const OSSL_PARAM) -> c_int;
+    pub fn EVP_MAC_init(
+        ctx: *mut EVP_MAC_CTX,
+        key: *const c_uchar,
+        keylen: size_t,
+        params: *const OSSL_PARAM,
+    ) -> c_int;
+    pub fn EVP_MAC_update(ctx: *mut EVP_MAC_CTX, data: *const c_uchar, datalen: size_t) -> c_int;
+    pub fn EVP_MAC_final(
+        ctx: *mut EVP_MAC_CTX,
+        out: *mut c_uchar,
+        outl: *mut size_t,
+        outsize: size_t,
+    ) -> c_int;
+}
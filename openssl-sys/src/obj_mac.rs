@@ -916,6 +916,14 @@ pub const NID_aes_256_cbc_hmac_sha1: c_int = 918;
 pub const NID_X25519: c_int = 1034;
 #[cfg(ossl111)]
 pub const NID_X448: c_int = 1035;
+#[cfg(ossl110)]
+pub const NID_tls1_prf: c_int = 1021;
+#[cfg(ossl110)]
+pub const NID_hkdf: c_int = 1036;
+#[cfg(ossl111)]
+pub const NID_poly1305: c_int = 1061;
+#[cfg(ossl111)]
+pub const NID_siphash: c_int = 1062;
 #[cfg(ossl111)]
 pub const NID_ED25519: c_int = 1087;
 #[cfg(ossl111)]
@@ -330,6 +330,8 @@ cfg_if! {
         }
     }
 }
+pub enum X509_PUBKEY {}
+
 cfg_if! {
     if #[cfg(ossl110)] {
         pub enum X509_ALGOR {}
@@ -439,12 +441,28 @@ pub struct X509V3_CTX {
     crl: *mut c_void,
     db_meth: *mut c_void,
     db: *mut c_void,
+    #[cfg(ossl300)]
+    issuer_pkey: *mut c_void,
     // I like the last comment line, it is copied from OpenSSL sources:
     // Maybe more here
 }
 pub enum CONF {}
+#[cfg(ossl102)]
+pub enum SSL_CONF_CTX {}
 #[cfg(ossl110)]
 pub enum OPENSSL_INIT_SETTINGS {}
+#[cfg(ossl300)]
+pub enum OSSL_LIB_CTX {}
+#[cfg(ossl300)]
+pub enum OSSL_PROVIDER {}
+#[cfg(ossl300)]
+pub enum EVP_KDF {}
+#[cfg(ossl300)]
+pub enum EVP_KDF_CTX {}
+#[cfg(ossl300)]
+pub enum EVP_MAC {}
+#[cfg(ossl300)]
+pub enum EVP_MAC_CTX {}
 
 pub enum ENGINE {}
 cfg_if! {
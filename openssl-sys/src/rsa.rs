@@ -28,10 +28,16 @@ pub unsafe fn EVP_PKEY_CTX_get_rsa_padding(ctx: *mut EVP_PKEY_CTX, ppad: *mut c_
 }
 
 pub unsafe fn EVP_PKEY_CTX_set_rsa_pss_saltlen(ctx: *mut EVP_PKEY_CTX, len: c_int) -> c_int {
+    // 3.0 digest signing contexts use the SIGNCTX and VERIFYCTX operations
+    #[cfg(ossl300)]
+    let optype = EVP_PKEY_OP_TYPE_SIG;
+    #[cfg(not(ossl300))]
+    let optype = EVP_PKEY_OP_SIGN | EVP_PKEY_OP_VERIFY;
+
     EVP_PKEY_CTX_ctrl(
         ctx,
         EVP_PKEY_RSA,
-        EVP_PKEY_OP_SIGN | EVP_PKEY_OP_VERIFY,
+        optype,
         EVP_PKEY_CTRL_RSA_PSS_SALTLEN,
         len,
         ptr::null_mut(),
@@ -90,6 +96,7 @@ pub const EVP_PKEY_CTRL_RSA_OAEP_MD: c_int = EVP_PKEY_ALG_CTRL + 9;
 pub const EVP_PKEY_CTRL_RSA_OAEP_LABEL: c_int = EVP_PKEY_ALG_CTRL + 10;
 
 pub const RSA_PKCS1_PADDING: c_int = 1;
+#[cfg(not(ossl300))]
 pub const RSA_SSLV23_PADDING: c_int = 2;
 pub const RSA_NO_PADDING: c_int = 3;
 pub const RSA_PKCS1_OAEP_PADDING: c_int = 4;
@@ -642,6 +642,36 @@ extern "C" {
     );
 }
 
+#[cfg(ossl111)]
+pub type SSL_psk_use_session_cb_func = Option<
+    unsafe extern "C" fn(
+        ssl: *mut SSL,
+        md: *const EVP_MD,
+        id: *mut *const c_uchar,
+        idlen: *mut size_t,
+        sess: *mut *mut SSL_SESSION,
+    ) -> c_int,
+>;
+#[cfg(ossl111)]
+pub type SSL_psk_find_session_cb_func = Option<
+    unsafe extern "C" fn(
+        ssl: *mut SSL,
+        identity: *const c_uchar,
+        identity_len: size_t,
+        sess: *mut *mut SSL_SESSION,
+    ) -> c_int,
+>;
+
+extern "C" {
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_psk_use_session_callback(ctx: *mut SSL_CTX, cb: SSL_psk_use_session_cb_func);
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_psk_find_session_callback(
+        ctx: *mut SSL_CTX,
+        cb: SSL_psk_find_session_cb_func,
+    );
+}
+
 extern "C" {
     #[cfg(ossl111)]
     pub fn SSL_CTX_add_custom_ext(
@@ -659,6 +689,66 @@ extern "C" {
     pub fn SSL_extension_supported(ext_type: c_uint) -> c_int;
 }
 
+#[cfg(ossl102)]
+pub const SSL_CONF_FLAG_CMDLINE: c_uint = 0x1;
+#[cfg(ossl102)]
+pub const SSL_CONF_FLAG_FILE: c_uint = 0x2;
+#[cfg(ossl102)]
+pub const SSL_CONF_FLAG_CLIENT: c_uint = 0x4;
+#[cfg(ossl102)]
+pub const SSL_CONF_FLAG_SERVER: c_uint = 0x8;
+#[cfg(ossl102)]
+pub const SSL_CONF_FLAG_SHOW_ERRORS: c_uint = 0x10;
+#[cfg(ossl102)]
+pub const SSL_CONF_FLAG_CERTIFICATE: c_uint = 0x20;
+
+#[cfg(ossl102)]
+pub const SSL_CONF_TYPE_UNKNOWN: c_int = 0x0;
+
+#[cfg(ossl102)]
+pub const SSL_R_UNKNOWN_CMD_NAME: c_int = 386;
+
+pub const SSL_CB_HANDSHAKE_START: c_int = 0x10;
+pub const SSL_CB_HANDSHAKE_DONE: c_int = 0x20;
+
+extern "C" {
+    pub fn SSL_CTX_set_info_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<unsafe extern "C" fn(ssl: *const SSL, type_: c_int, val: c_int)>,
+    );
+    pub fn SSL_CTX_get_info_callback(
+        ctx: *mut SSL_CTX,
+    ) -> Option<unsafe extern "C" fn(ssl: *const SSL, type_: c_int, val: c_int)>;
+    pub fn SSL_CTX_set_msg_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+    pub fn SSL_set_msg_callback(
+        ssl: *mut SSL,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+}
+
 #[cfg(ossl111)]
 pub type SSL_CTX_keylog_cb_func =
     Option<unsafe extern "C" fn(ssl: *const SSL, line: *const c_char)>;
@@ -675,12 +765,50 @@ extern "C" {
     pub fn SSL_set_max_early_data(ctx: *mut SSL, max_early_data: u32) -> c_int;
     #[cfg(ossl111)]
     pub fn SSL_get_max_early_data(ctx: *const SSL) -> u32;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_recv_max_early_data(ctx: *mut SSL_CTX, recv_max_early_data: u32) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_get_recv_max_early_data(ctx: *const SSL_CTX) -> u32;
+    #[cfg(ossl111)]
+    pub fn SSL_set_recv_max_early_data(ctx: *mut SSL, recv_max_early_data: u32) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_recv_max_early_data(ctx: *const SSL) -> u32;
+    #[cfg(ossl111)]
+    pub fn SSL_get_early_data_status(s: *const SSL) -> c_int;
+
+    #[cfg(ossl102)]
+    pub fn SSL_CONF_CTX_new() -> *mut SSL_CONF_CTX;
+    #[cfg(ossl102)]
+    pub fn SSL_CONF_CTX_free(cctx: *mut SSL_CONF_CTX);
+    #[cfg(ossl102)]
+    pub fn SSL_CONF_CTX_set_flags(cctx: *mut SSL_CONF_CTX, flags: c_uint) -> c_uint;
+    #[cfg(ossl102)]
+    pub fn SSL_CONF_CTX_set_ssl_ctx(cctx: *mut SSL_CONF_CTX, ctx: *mut SSL_CTX);
+    #[cfg(ossl102)]
+    pub fn SSL_CONF_cmd(cctx: *mut SSL_CONF_CTX, cmd: *const c_char, value: *const c_char)
+        -> c_int;
+    #[cfg(ossl102)]
+    pub fn SSL_CONF_cmd_value_type(cctx: *mut SSL_CONF_CTX, cmd: *const c_char) -> c_int;
+    #[cfg(ossl102)]
+    pub fn SSL_CONF_CTX_finish(cctx: *mut SSL_CONF_CTX) -> c_int;
+
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_set_security_level(ctx: *mut SSL_CTX, level: c_int);
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_get_security_level(ctx: *const SSL_CTX) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_set_security_level(s: *mut SSL, level: c_int);
+    #[cfg(ossl110)]
+    pub fn SSL_get_security_level(s: *const SSL) -> c_int;
 
     pub fn SSL_get_finished(s: *const SSL, buf: *mut c_void, count: size_t) -> size_t;
     pub fn SSL_get_peer_finished(s: *const SSL, buf: *mut c_void, count: size_t) -> size_t;
 
     pub fn SSL_CTX_get_verify_mode(ctx: *const SSL_CTX) -> c_int;
     pub fn SSL_get_verify_mode(s: *const SSL) -> c_int;
+    pub fn SSL_get_verify_callback(
+        s: *const SSL,
+    ) -> Option<extern "C" fn(c_int, *mut X509_STORE_CTX) -> c_int>;
 }
 
 const_ptr_api! {
@@ -711,8 +839,23 @@ pub const SSL_CTRL_SET_TMP_DH: c_int = 3;
 pub const SSL_CTRL_SET_TMP_ECDH: c_int = 4;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_GET_SESSION_REUSED: c_int = 8;
+pub const SSL_CTRL_GET_NUM_RENEGOTIATIONS: c_int = 10;
+pub const SSL_CTRL_CLEAR_NUM_RENEGOTIATIONS: c_int = 11;
+pub const SSL_CTRL_GET_TOTAL_RENEGOTIATIONS: c_int = 12;
 pub const SSL_CTRL_EXTRA_CHAIN_CERT: c_int = 14;
 pub const SSL_CTRL_SET_MTU: c_int = 17;
+pub const SSL_CTRL_SESS_NUMBER: c_int = 20;
+pub const SSL_CTRL_SESS_CONNECT: c_int = 21;
+pub const SSL_CTRL_SESS_CONNECT_GOOD: c_int = 22;
+pub const SSL_CTRL_SESS_CONNECT_RENEGOTIATE: c_int = 23;
+pub const SSL_CTRL_SESS_ACCEPT: c_int = 24;
+pub const SSL_CTRL_SESS_ACCEPT_GOOD: c_int = 25;
+pub const SSL_CTRL_SESS_ACCEPT_RENEGOTIATE: c_int = 26;
+pub const SSL_CTRL_SESS_HIT: c_int = 27;
+pub const SSL_CTRL_SESS_CB_HIT: c_int = 28;
+pub const SSL_CTRL_SESS_MISSES: c_int = 29;
+pub const SSL_CTRL_SESS_TIMEOUTS: c_int = 30;
+pub const SSL_CTRL_SESS_CACHE_FULL: c_int = 31;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_OPTIONS: c_int = 32;
 pub const SSL_CTRL_MODE: c_int = 33;
@@ -733,12 +876,18 @@ pub const SSL_CTRL_CLEAR_OPTIONS: c_int = 77;
 pub const SSL_CTRL_GET_EXTRA_CHAIN_CERTS: c_int = 82;
 #[cfg(ossl111)]
 pub const SSL_CTRL_SET_GROUPS_LIST: c_int = 92;
+#[cfg(ossl110)]
+pub const SSL_CTRL_GET_SHARED_GROUP: c_int = 93;
 #[cfg(any(libressl, all(ossl102, not(ossl110))))]
 pub const SSL_CTRL_SET_ECDH_AUTO: c_int = 94;
 #[cfg(ossl102)]
 pub const SSL_CTRL_SET_SIGALGS_LIST: c_int = 98;
 #[cfg(ossl102)]
 pub const SSL_CTRL_SET_VERIFY_CERT_STORE: c_int = 106;
+#[cfg(ossl102)]
+pub const SSL_CTRL_GET_PEER_SIGNATURE_NID: c_int = 108;
+#[cfg(ossl111)]
+pub const SSL_CTRL_GET_PEER_TMP_KEY: c_int = 109;
 #[cfg(ossl110)]
 pub const SSL_CTRL_SET_MIN_PROTO_VERSION: c_int = 123;
 #[cfg(ossl110)]
@@ -747,6 +896,8 @@ pub const SSL_CTRL_SET_MAX_PROTO_VERSION: c_int = 124;
 pub const SSL_CTRL_GET_MIN_PROTO_VERSION: c_int = 130;
 #[cfg(ossl110g)]
 pub const SSL_CTRL_GET_MAX_PROTO_VERSION: c_int = 131;
+#[cfg(ossl300)]
+pub const SSL_CTRL_GET_NEGOTIATED_GROUP: c_int = 134;
 
 pub unsafe fn SSL_CTX_set_tmp_dh(ctx: *mut SSL_CTX, dh: *mut DH) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_TMP_DH, 0, dh as *mut c_void)
@@ -894,6 +1045,63 @@ pub unsafe fn SSL_get_max_proto_version(s: *mut SSL) -> c_int {
     SSL_ctrl(s, SSL_CTRL_GET_MAX_PROTO_VERSION, 0, ptr::null_mut()) as c_int
 }
 
+#[cfg(ossl102)]
+pub unsafe fn SSL_get_peer_signature_nid(s: *mut SSL, pnid: *mut c_int) -> c_long {
+    SSL_ctrl(s, SSL_CTRL_GET_PEER_SIGNATURE_NID, 0, pnid as *mut c_void)
+}
+
+#[cfg(ossl111)]
+pub unsafe fn SSL_get_peer_tmp_key(s: *mut SSL, pk: *mut *mut EVP_PKEY) -> c_long {
+    SSL_ctrl(s, SSL_CTRL_GET_PEER_TMP_KEY, 0, pk as *mut c_void)
+}
+
+#[cfg(ossl110)]
+pub unsafe fn SSL_get_shared_group(s: *mut SSL, n: c_long) -> c_int {
+    SSL_ctrl(s, SSL_CTRL_GET_SHARED_GROUP, n, ptr::null_mut()) as c_int
+}
+
+#[cfg(ossl300)]
+pub unsafe fn SSL_get_negotiated_group(s: *mut SSL) -> c_int {
+    SSL_ctrl(s, SSL_CTRL_GET_NEGOTIATED_GROUP, 0, ptr::null_mut()) as c_int
+}
+
+extern "C" {
+    #[cfg(ossl111)]
+    pub fn SSL_get_peer_signature_type_nid(s: *const SSL, pnid: *mut c_int) -> c_int;
+    #[cfg(ossl102)]
+    pub fn SSL_get_shared_sigalgs(
+        s: *mut SSL,
+        idx: c_int,
+        psign: *mut c_int,
+        phash: *mut c_int,
+        psignhash: *mut c_int,
+        rsig: *mut c_uchar,
+        rhash: *mut c_uchar,
+    ) -> c_int;
+    pub fn SSL_get_shared_ciphers(s: *const SSL, buf: *mut c_char, size: c_int) -> *mut c_char;
+    #[cfg(ossl110)]
+    pub fn SSL_get_client_ciphers(s: *const SSL) -> *mut stack_st_SSL_CIPHER;
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_get_ciphers(ctx: *const SSL_CTX) -> *mut stack_st_SSL_CIPHER;
+    #[cfg(ossl110)]
+    pub fn SSL_get1_supported_ciphers(s: *mut SSL) -> *mut stack_st_SSL_CIPHER;
+}
+
+extern "C" {
+    #[cfg(ossl320)]
+    pub fn SSL_CTX_set1_cert_comp_preference(
+        ctx: *mut SSL_CTX,
+        algs: *mut c_int,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_set1_cert_comp_preference(ssl: *mut SSL, algs: *mut c_int, len: size_t) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_CTX_compress_certs(ctx: *mut SSL_CTX, alg: c_int) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_compress_certs(ssl: *mut SSL, alg: c_int) -> c_int;
+}
+
 extern "C" {
     pub fn SSL_CTX_set_cipher_list(ssl: *mut SSL_CTX, s: *const c_char) -> c_int;
     pub fn SSL_CTX_new(method: *const SSL_METHOD) -> *mut SSL_CTX;
@@ -904,6 +1112,8 @@ extern "C" {
     pub fn SSL_CTX_set_cert_store(ctx: *mut SSL_CTX, store: *mut X509_STORE);
 
     pub fn SSL_get_current_cipher(ssl: *const SSL) -> *const SSL_CIPHER;
+    #[cfg(ossl102)]
+    pub fn SSL_CIPHER_find(ssl: *mut SSL, ptr: *const c_uchar) -> *const SSL_CIPHER;
     pub fn SSL_CIPHER_get_bits(cipher: *const SSL_CIPHER, alg_bits: *mut c_int) -> c_int;
 }
 const_ptr_api! {
@@ -963,6 +1173,17 @@ extern "C" {
     #[cfg(ossl110)]
     pub fn SSL_SESSION_get_protocol_version(s: *const SSL_SESSION) -> c_int;
 
+    pub fn SSL_SESSION_new() -> *mut SSL_SESSION;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set1_master_key(
+        sess: *mut SSL_SESSION,
+        in_: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_cipher(s: *mut SSL_SESSION, cipher: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_protocol_version(s: *mut SSL_SESSION, version: c_int) -> c_int;
     #[cfg(ossl111)]
     pub fn SSL_SESSION_set_max_early_data(ctx: *mut SSL_SESSION, max_early_data: u32) -> c_int;
     #[cfg(ossl111)]
@@ -981,8 +1202,26 @@ extern "C" {
         pp: *mut *const c_uchar,
         len: c_long,
     ) -> *mut SSL_SESSION;
+}
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn SSL_get1_peer_certificate(ssl: *const SSL) -> *mut X509;
+        }
+
+        #[inline]
+        pub unsafe fn SSL_get_peer_certificate(ssl: *const SSL) -> *mut X509 {
+            SSL_get1_peer_certificate(ssl)
+        }
+    } else {
+        extern "C" {
+            pub fn SSL_get_peer_certificate(ssl: *const SSL) -> *mut X509;
+        }
+    }
+}
 
-    pub fn SSL_get_peer_certificate(ssl: *const SSL) -> *mut X509;
+extern "C" {
 
     pub fn SSL_get_peer_cert_chain(ssl: *const SSL) -> *mut stack_st_X509;
 
@@ -1078,6 +1317,32 @@ pub const SSL_READ_EARLY_DATA_SUCCESS: c_int = 1;
 #[cfg(ossl111)]
 pub const SSL_READ_EARLY_DATA_FINISH: c_int = 2;
 
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_NOT_SENT: c_int = 0;
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_REJECTED: c_int = 1;
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_ACCEPTED: c_int = 2;
+
+#[cfg(ossl111)]
+pub type SSL_allow_early_data_cb_fn =
+    Option<unsafe extern "C" fn(s: *mut SSL, arg: *mut c_void) -> c_int>;
+
+extern "C" {
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_allow_early_data_cb(
+        ctx: *mut SSL_CTX,
+        cb: SSL_allow_early_data_cb_fn,
+        arg: *mut c_void,
+    );
+    #[cfg(ossl111)]
+    pub fn SSL_set_allow_early_data_cb(
+        s: *mut SSL,
+        cb: SSL_allow_early_data_cb_fn,
+        arg: *mut c_void,
+    );
+}
+
 extern "C" {
     pub fn SSL_write(ssl: *mut SSL, buf: *const c_void, num: c_int) -> c_int;
     #[cfg(ossl111)]
@@ -1188,6 +1453,8 @@ extern "C" {
     pub fn SSL_set_shutdown(ss: *mut SSL, mode: c_int);
     pub fn SSL_get_shutdown(ssl: *const SSL) -> c_int;
     pub fn SSL_version(ssl: *const SSL) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_is_dtls(s: *const SSL) -> c_int;
     pub fn SSL_get_session(s: *const SSL) -> *mut SSL_SESSION;
     pub fn SSL_get_SSL_CTX(ssl: *const SSL) -> *mut SSL_CTX;
     pub fn SSL_set_SSL_CTX(ssl: *mut SSL, ctx: *mut SSL_CTX) -> *mut SSL_CTX;
@@ -1273,6 +1540,62 @@ pub unsafe fn SSL_CTX_sess_get_cache_size(ctx: *mut SSL_CTX) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_GET_SESS_CACHE_SIZE, 0, ptr::null_mut())
 }
 
+pub unsafe fn SSL_CTX_sess_number(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_NUMBER, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cb_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CB_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_misses(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_MISSES, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_timeouts(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_TIMEOUTS, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cache_full(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CACHE_FULL, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_num_renegotiations(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_NUM_RENEGOTIATIONS, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_total_renegotiations(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_TOTAL_RENEGOTIATIONS, 0, ptr::null_mut())
+}
+
 pub unsafe fn SSL_CTX_set_session_cache_mode(ctx: *mut SSL_CTX, m: c_long) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_SESS_CACHE_MODE, m, ptr::null_mut())
 }
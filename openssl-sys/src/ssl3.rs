@@ -3,3 +3,11 @@ use libc::*;
 pub const SSL3_VERSION: c_int = 0x300;
 
 pub const SSL3_AD_ILLEGAL_PARAMETER: c_int = 47;
+
+pub const SSL3_RT_HANDSHAKE: c_int = 22;
+#[cfg(ossl110)]
+pub const SSL3_RT_HEADER: c_int = 0x100;
+
+pub const SSL3_MT_CLIENT_HELLO: c_int = 1;
+#[cfg(ossl320)]
+pub const SSL3_MT_COMPRESSED_CERTIFICATE: c_int = 25;
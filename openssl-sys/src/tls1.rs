@@ -16,6 +16,15 @@ pub const TLS1_AD_UNRECOGNIZED_NAME: c_int = 112;
 pub const TLSEXT_NAMETYPE_host_name: c_int = 0;
 pub const TLSEXT_STATUSTYPE_ocsp: c_int = 1;
 
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_none: c_int = 0;
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_zlib: c_int = 1;
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_brotli: c_int = 2;
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_zstd: c_int = 3;
+
 extern "C" {
     pub fn SSL_get_servername(ssl: *const SSL, name_type: c_int) -> *const c_char;
 
@@ -126,7 +126,7 @@ cfg_if! {
             issuer: *mut c_void,
             pub validity: *mut X509_VAL,
             subject: *mut c_void,
-            key: *mut c_void,
+            pub key: *mut X509_PUBKEY,
             issuerUID: *mut c_void,
             subjectUID: *mut c_void,
             pub extensions: *mut stack_st_X509_EXTENSION,
@@ -267,6 +267,7 @@ const_ptr_api! {
             palg: *mut #[const_ptr_if(any(ossl110, libressl273))] X509_ALGOR,
             x: *const X509,
         );
+        pub fn i2d_X509_PUBKEY(a: #[const_ptr_if(ossl300)] X509_PUBKEY, buf: *mut *mut u8) -> c_int;
     }
 }
 extern "C" {
@@ -286,6 +287,8 @@ extern "C" {
     pub fn d2i_X509(a: *mut *mut X509, pp: *mut *const c_uchar, length: c_long) -> *mut X509;
 
     pub fn X509_get_pubkey(x: *mut X509) -> *mut EVP_PKEY;
+    #[cfg(any(ossl110, libressl273))]
+    pub fn X509_get_X509_PUBKEY(x: *const X509) -> *mut X509_PUBKEY;
 
     pub fn X509_set_version(x: *mut X509, version: c_long) -> c_int;
     #[cfg(ossl110)]
@@ -0,0 +1,782 @@
+//! Authenticated encryption with associated data.
+//!
+//! The [`Aead`] trait encrypts and decrypts messages in place, using a cipher context which is
+//! keyed once and reused for every message. Keys and nonces are fixed size arrays, so a key or
+//! nonce of the wrong length is rejected at compile time.
+//!
+//! The [`stream`] module builds authenticated encryption of large messages on top of the AEAD
+//! ciphers in [`symm`].
+//!
+//! [`Aead`]: trait.Aead.html
+//! [`stream`]: stream/index.html
+//! [`symm`]: ../symm/index.html
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::aead::{Aead, Aes256Gcm};
+//!
+//! let key = [0x42; 32];
+//! let mut aead = Aes256Gcm::new(&key).unwrap();
+//!
+//! let mut packet = b"some packet payload".to_vec();
+//! let nonce = [1; 12];
+//! let tag = aead.seal_in_place(&nonce, b"header", &mut packet).unwrap();
+//! assert_ne!(&packet[..], b"some packet payload");
+//!
+//! aead.open_in_place(&nonce, b"header", &mut packet, &tag).unwrap();
+//! assert_eq!(&packet[..], b"some packet payload");
+//! ```
+use libc::{c_int, c_void};
+use std::fmt;
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::memcmp;
+use crate::symm::Cipher;
+use crate::{cvt, cvt_p};
+
+pub mod stream;
+
+/// The length of an authentication tag in bytes.
+pub const TAG_LEN: usize = 16;
+
+/// An authentication tag produced by an [`Aead`].
+///
+/// Tags are compared in constant time.
+///
+/// [`Aead`]: trait.Aead.html
+#[derive(Clone, Copy)]
+pub struct Tag([u8; TAG_LEN]);
+
+impl Tag {
+    /// Creates a tag from a slice, returning `None` if it is not `TAG_LEN` bytes long.
+    pub fn from_slice(tag: &[u8]) -> Option<Tag> {
+        if tag.len() != TAG_LEN {
+            return None;
+        }
+        let mut buf = [0; TAG_LEN];
+        buf.copy_from_slice(tag);
+        Some(Tag(buf))
+    }
+}
+
+impl From<[u8; TAG_LEN]> for Tag {
+    fn from(tag: [u8; TAG_LEN]) -> Tag {
+        Tag(tag)
+    }
+}
+
+impl AsRef<[u8]> for Tag {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl PartialEq for Tag {
+    fn eq(&self, other: &Tag) -> bool {
+        memcmp::eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Tag {}
+
+impl fmt::Debug for Tag {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("Tag").field(&self.0).finish()
+    }
+}
+
+/// An AEAD cipher keyed for repeated use.
+///
+/// The key schedule is computed when the cipher is created rather than for each message, and no
+/// memory is allocated when sealing or opening a message. The exceptions are the nonce misuse
+/// resistant AES-SIV and AES-GCM-SIV ciphers, which copy a context keyed when the cipher is
+/// created for each message, since their contexts cannot be reused once a message has been
+/// processed.
+pub trait Aead: Sized {
+    /// The key type.
+    type Key;
+
+    /// The nonce type.
+    type Nonce;
+
+    /// Creates a new cipher context using `key`.
+    fn new(key: &Self::Key) -> Result<Self, ErrorStack>;
+
+    /// Encrypts `buf` in place, authenticating it along with `aad`, and returns the tag.
+    ///
+    /// A nonce must never be reused with the same key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` or `aad` is longer than `c_int::max_value()` bytes.
+    fn seal_in_place(
+        &mut self,
+        nonce: &Self::Nonce,
+        aad: &[u8],
+        buf: &mut [u8],
+    ) -> Result<Tag, ErrorStack>;
+
+    /// Authenticates `buf` along with `aad` against `tag` and decrypts it in place.
+    ///
+    /// If authentication fails, an error is returned and `buf` is zeroed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` or `aad` is longer than `c_int::max_value()` bytes.
+    fn open_in_place(
+        &mut self,
+        nonce: &Self::Nonce,
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), ErrorStack>;
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    Standard,
+    Ccm,
+    // the nonce is the last associated data component, as described in RFC 5297
+    #[cfg(ossl300)]
+    Siv,
+    #[cfg(ossl320)]
+    GcmSiv,
+}
+
+enum Algorithm {
+    Builtin(Cipher),
+    #[cfg(ossl300)]
+    Fetched(&'static str),
+}
+
+// The cipher contexts shared by the `Aead` implementations.
+//
+// Some ciphers select their implementation based on the direction when they are keyed, so
+// separate contexts are keyed for encryption and decryption.
+struct AeadCtx {
+    encrypt: *mut ffi::EVP_CIPHER_CTX,
+    decrypt: *mut ffi::EVP_CIPHER_CTX,
+    // The misuse resistant modes keep state, such as the associated data, which is not cleared
+    // when a new nonce is set. They are keyed once into these contexts, which are copied into
+    // the working contexts for each message. Null for the other modes.
+    encrypt_template: *mut ffi::EVP_CIPHER_CTX,
+    decrypt_template: *mut ffi::EVP_CIPHER_CTX,
+    kind: Kind,
+}
+
+unsafe impl Sync for AeadCtx {}
+unsafe impl Send for AeadCtx {}
+
+impl AeadCtx {
+    fn new(algorithm: Algorithm, kind: Kind, key: &[u8]) -> Result<AeadCtx, ErrorStack> {
+        ffi::init();
+
+        let mut ctx = AeadCtx {
+            encrypt: ptr::null_mut(),
+            decrypt: ptr::null_mut(),
+            encrypt_template: ptr::null_mut(),
+            decrypt_template: ptr::null_mut(),
+            kind,
+        };
+
+        unsafe {
+            ctx.encrypt = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
+            ctx.decrypt = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
+            if ctx.rekeys() {
+                ctx.encrypt_template = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
+                ctx.decrypt_template = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
+            }
+
+            match algorithm {
+                Algorithm::Builtin(cipher) => ctx.init(cipher.as_ptr(), key)?,
+                // the contexts hold their own references to the cipher
+                #[cfg(ossl300)]
+                Algorithm::Fetched(name) => {
+                    let cipher = Cipher::fetch(None, name, None)?;
+                    ctx.init(cipher.as_ptr(), key)?
+                }
+            }
+        }
+
+        Ok(ctx)
+    }
+
+    fn ctx(&self, enc: c_int) -> *mut ffi::EVP_CIPHER_CTX {
+        if enc == 1 {
+            self.encrypt
+        } else {
+            self.decrypt
+        }
+    }
+
+    unsafe fn init(&self, cipher: *const ffi::EVP_CIPHER, key: &[u8]) -> Result<(), ErrorStack> {
+        let contexts = if self.rekeys() {
+            [(self.encrypt_template, 1), (self.decrypt_template, 0)]
+        } else {
+            [(self.encrypt, 1), (self.decrypt, 0)]
+        };
+
+        for &(ctx, enc) in &contexts {
+            cvt(ffi::EVP_CipherInit_ex(
+                ctx,
+                cipher,
+                ptr::null_mut(),
+                ptr::null(),
+                ptr::null(),
+                enc,
+            ))?;
+
+            if self.kind == Kind::Ccm {
+                // CCM defaults to a 7 byte nonce and a 12 byte tag
+                cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                    ctx,
+                    ffi::EVP_CTRL_GCM_SET_IVLEN,
+                    12,
+                    ptr::null_mut(),
+                ))?;
+                cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                    ctx,
+                    ffi::EVP_CTRL_GCM_SET_TAG,
+                    TAG_LEN as c_int,
+                    ptr::null_mut(),
+                ))?;
+            }
+
+            cvt(ffi::EVP_CipherInit_ex(
+                ctx,
+                ptr::null(),
+                ptr::null_mut(),
+                key.as_ptr(),
+                ptr::null(),
+                enc,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn rekeys(&self) -> bool {
+        match self.kind {
+            Kind::Standard | Kind::Ccm => false,
+            #[cfg(ossl300)]
+            Kind::Siv => true,
+            #[cfg(ossl320)]
+            Kind::GcmSiv => true,
+        }
+    }
+
+    fn nonce_is_aad(&self) -> bool {
+        #[cfg(ossl300)]
+        {
+            if self.kind == Kind::Siv {
+                return true;
+            }
+        }
+        false
+    }
+
+    unsafe fn aad_update(&self, enc: c_int, aad: &[u8]) -> Result<(), ErrorStack> {
+        assert!(aad.len() <= c_int::max_value() as usize);
+        let mut len = 0;
+        cvt(ffi::EVP_CipherUpdate(
+            self.ctx(enc),
+            ptr::null_mut(),
+            &mut len,
+            aad.as_ptr(),
+            aad.len() as c_int,
+        ))
+        .map(|_| ())
+    }
+
+    // The SIV modes authenticate each element of `aad` as a separate component.
+    unsafe fn process(
+        &mut self,
+        enc: c_int,
+        nonce: &[u8],
+        aad: &[&[u8]],
+        buf: &mut [u8],
+        tag: &mut [u8; TAG_LEN],
+    ) -> Result<(), ErrorStack> {
+        assert!(buf.len() <= c_int::max_value() as usize);
+        let ctx = self.ctx(enc);
+
+        // OpenSSL 3.0 skips the empty final component of the S2V computation and fails
+        #[cfg(all(ossl300, not(ossl320)))]
+        {
+            if self.kind == Kind::Siv && buf.is_empty() {
+                return Err(ErrorStack::internal(
+                    ffi::ERR_LIB_EVP,
+                    ffi::ERR_R_PASSED_INVALID_ARGUMENT,
+                    Some("AES-SIV requires OpenSSL 3.2.0 to process empty messages".to_string()),
+                ));
+            }
+        }
+
+        if self.rekeys() {
+            let template = if enc == 1 {
+                self.encrypt_template
+            } else {
+                self.decrypt_template
+            };
+            cvt(ffi::EVP_CIPHER_CTX_copy(ctx, template))?;
+        }
+        if !self.nonce_is_aad() {
+            cvt(ffi::EVP_CipherInit_ex(
+                ctx,
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null(),
+                nonce.as_ptr(),
+                enc,
+            ))?;
+        }
+
+        if enc == 0 {
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                ctx,
+                ffi::EVP_CTRL_GCM_SET_TAG,
+                TAG_LEN as c_int,
+                tag.as_mut_ptr() as *mut c_void,
+            ))?;
+        }
+
+        let mut len = 0;
+        if self.kind == Kind::Ccm {
+            cvt(ffi::EVP_CipherUpdate(
+                ctx,
+                ptr::null_mut(),
+                &mut len,
+                ptr::null(),
+                buf.len() as c_int,
+            ))?;
+        }
+
+        for aad in aad {
+            self.aad_update(enc, aad)?;
+        }
+        if self.nonce_is_aad() {
+            self.aad_update(enc, nonce)?;
+        }
+
+        // EVP_CipherUpdate supports input and output buffers which overlap exactly
+        cvt(ffi::EVP_CipherUpdate(
+            ctx,
+            buf.as_mut_ptr(),
+            &mut len,
+            buf.as_ptr(),
+            buf.len() as c_int,
+        ))?;
+
+        // CCM authenticates the message in EVP_CipherUpdate and does not support finalization
+        // when decrypting
+        if self.kind != Kind::Ccm || enc == 1 {
+            let mut rest = 0;
+            cvt(ffi::EVP_CipherFinal(
+                ctx,
+                buf.as_mut_ptr().add(len as usize),
+                &mut rest,
+            ))?;
+        }
+
+        if enc == 1 {
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                ctx,
+                ffi::EVP_CTRL_GCM_GET_TAG,
+                TAG_LEN as c_int,
+                tag.as_mut_ptr() as *mut c_void,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn seal(&mut self, nonce: &[u8], aad: &[u8], buf: &mut [u8]) -> Result<Tag, ErrorStack> {
+        let mut tag = [0; TAG_LEN];
+        unsafe {
+            self.process(1, nonce, &[aad], buf, &mut tag)?;
+        }
+        Ok(Tag(tag))
+    }
+
+    fn open(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), ErrorStack> {
+        let mut tag = tag.0;
+        unsafe {
+            let r = self.process(0, nonce, &[aad], buf, &mut tag);
+            if r.is_err() {
+                ffi::OPENSSL_cleanse(buf.as_mut_ptr() as *mut c_void, buf.len());
+            }
+            r
+        }
+    }
+}
+
+impl Drop for AeadCtx {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::EVP_CIPHER_CTX_free(self.encrypt);
+            ffi::EVP_CIPHER_CTX_free(self.decrypt);
+            ffi::EVP_CIPHER_CTX_free(self.encrypt_template);
+            ffi::EVP_CIPHER_CTX_free(self.decrypt_template);
+        }
+    }
+}
+
+macro_rules! aead {
+    ($(
+        $(#[$m:meta])*
+        pub struct $name:ident: cfg($($cfg:tt)*), key = $key_len:expr, nonce = $nonce_len:expr,
+            $kind:expr, $algorithm:expr;
+    )*) => {$(
+        $(#[$m])*
+        #[cfg($($cfg)*)]
+        pub struct $name(AeadCtx);
+
+        #[cfg($($cfg)*)]
+        impl Aead for $name {
+            type Key = [u8; $key_len];
+            type Nonce = [u8; $nonce_len];
+
+            fn new(key: &[u8; $key_len]) -> Result<$name, ErrorStack> {
+                AeadCtx::new($algorithm, $kind, key).map($name)
+            }
+
+            fn seal_in_place(
+                &mut self,
+                nonce: &[u8; $nonce_len],
+                aad: &[u8],
+                buf: &mut [u8],
+            ) -> Result<Tag, ErrorStack> {
+                self.0.seal(nonce, aad, buf)
+            }
+
+            fn open_in_place(
+                &mut self,
+                nonce: &[u8; $nonce_len],
+                aad: &[u8],
+                buf: &mut [u8],
+                tag: &Tag,
+            ) -> Result<(), ErrorStack> {
+                self.0.open(nonce, aad, buf, tag)
+            }
+        }
+    )*};
+}
+
+aead! {
+    /// AES-128 in Galois/Counter Mode.
+    pub struct Aes128Gcm: cfg(all()), key = 16, nonce = 12,
+        Kind::Standard, Algorithm::Builtin(Cipher::aes_128_gcm());
+
+    /// AES-256 in Galois/Counter Mode.
+    pub struct Aes256Gcm: cfg(all()), key = 32, nonce = 12,
+        Kind::Standard, Algorithm::Builtin(Cipher::aes_256_gcm());
+
+    /// AES-128 in Counter with CBC-MAC mode.
+    ///
+    /// The 12 byte nonce limits messages to 2^24 - 1 bytes.
+    pub struct Aes128Ccm: cfg(all()), key = 16, nonce = 12,
+        Kind::Ccm, Algorithm::Builtin(Cipher::aes_128_ccm());
+
+    /// AES-256 in Counter with CBC-MAC mode.
+    ///
+    /// The 12 byte nonce limits messages to 2^24 - 1 bytes.
+    pub struct Aes256Ccm: cfg(all()), key = 32, nonce = 12,
+        Kind::Ccm, Algorithm::Builtin(Cipher::aes_256_ccm());
+
+    /// AES-128 in Offset Codebook mode.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    pub struct Aes128Ocb: cfg(ossl110), key = 16, nonce = 12,
+        Kind::Standard, Algorithm::Builtin(Cipher::aes_128_ocb());
+
+    /// AES-256 in Offset Codebook mode.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    pub struct Aes256Ocb: cfg(ossl110), key = 32, nonce = 12,
+        Kind::Standard, Algorithm::Builtin(Cipher::aes_256_ocb());
+
+    /// ChaCha20-Poly1305, as described in RFC 8439.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    pub struct ChaCha20Poly1305: cfg(ossl110), key = 32, nonce = 12,
+        Kind::Standard, Algorithm::Builtin(Cipher::chacha20_poly1305());
+
+    /// AES-SIV with a 256 bit key, as described in RFC 5297.
+    ///
+    /// The nonce is passed as the last component of the associated data. AES-SIV is resistant
+    /// to nonce reuse, at the cost of processing each message twice and copying a keyed context
+    /// for each message.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer. Empty messages require OpenSSL 3.2.0 or newer.
+    pub struct Aes128Siv: cfg(ossl300), key = 32, nonce = 16,
+        Kind::Siv, Algorithm::Fetched("AES-128-SIV");
+
+    /// AES-SIV with a 512 bit key, as described in RFC 5297.
+    ///
+    /// The nonce is passed as the last component of the associated data. AES-SIV is resistant
+    /// to nonce reuse, at the cost of processing each message twice and copying a keyed context
+    /// for each message.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer. Empty messages require OpenSSL 3.2.0 or newer.
+    pub struct Aes256Siv: cfg(ossl300), key = 64, nonce = 16,
+        Kind::Siv, Algorithm::Fetched("AES-256-SIV");
+
+    /// AES-128-GCM-SIV, as described in RFC 8452.
+    ///
+    /// AES-GCM-SIV is resistant to nonce reuse, and derives new keys for each message.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    pub struct Aes128GcmSiv: cfg(ossl320), key = 16, nonce = 12,
+        Kind::GcmSiv, Algorithm::Fetched("AES-128-GCM-SIV");
+
+    /// AES-256-GCM-SIV, as described in RFC 8452.
+    ///
+    /// AES-GCM-SIV is resistant to nonce reuse, and derives new keys for each message.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    pub struct Aes256GcmSiv: cfg(ossl320), key = 32, nonce = 12,
+        Kind::GcmSiv, Algorithm::Fetched("AES-256-GCM-SIV");
+}
+
+#[cfg(test)]
+mod test {
+    use hex::{self, FromHex};
+
+    use super::*;
+
+    fn array<T: Default + AsMut<[u8]>>(s: &str) -> T {
+        let mut array = T::default();
+        array.as_mut().copy_from_slice(&Vec::from_hex(s).unwrap());
+        array
+    }
+
+    fn check<A: Aead>(key: &str, nonce: &str, aad: &str, pt: &str, ct: &str, tag: &str)
+    where
+        A::Key: Default + AsMut<[u8]>,
+        A::Nonce: Default + AsMut<[u8]>,
+    {
+        let mut aead = A::new(&array(key)).unwrap();
+        let nonce = array(nonce);
+        let aad = Vec::from_hex(aad).unwrap();
+        let pt = Vec::from_hex(pt).unwrap();
+
+        // the context is reused, so every message must give the same result
+        for _ in 0..2 {
+            let mut buf = pt.clone();
+            let actual_tag = aead.seal_in_place(&nonce, &aad, &mut buf).unwrap();
+            assert_eq!(hex::encode(&buf), ct);
+            assert_eq!(hex::encode(actual_tag), tag);
+
+            aead.open_in_place(&nonce, &aad, &mut buf, &actual_tag)
+                .unwrap();
+            assert_eq!(buf, pt);
+        }
+
+        let tag = Tag::from(array::<[u8; TAG_LEN]>(tag));
+        let mut buf = Vec::from_hex(ct).unwrap();
+        assert!(aead
+            .open_in_place(&nonce, b"other", &mut buf, &tag)
+            .is_err());
+        assert!(buf.iter().all(|&b| b == 0));
+
+        if !pt.is_empty() {
+            let mut buf = Vec::from_hex(ct).unwrap();
+            buf[0] ^= 1;
+            assert!(aead.open_in_place(&nonce, &aad, &mut buf, &tag).is_err());
+        }
+    }
+
+    fn check_empty<A: Aead>(mut aead: A, nonce: &A::Nonce) {
+        let mut buf = vec![];
+        let tag = aead.seal_in_place(nonce, &[], &mut buf).unwrap();
+        aead.open_in_place(nonce, &[], &mut buf, &tag).unwrap();
+
+        let tag = aead.seal_in_place(nonce, b"header", &mut buf).unwrap();
+        assert!(aead.open_in_place(nonce, &[], &mut buf, &tag).is_err());
+        aead.open_in_place(nonce, b"header", &mut buf, &tag)
+            .unwrap();
+    }
+
+    // The GCM specification, test cases 4 and 16
+    const GCM_NONCE: &str = "cafebabefacedbaddecaf888";
+    const GCM_AAD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    const GCM_PT: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95\
+                          956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+
+    #[test]
+    fn aes_128_gcm() {
+        check::<Aes128Gcm>(
+            "feffe9928665731c6d6a8f9467308308",
+            GCM_NONCE,
+            GCM_AAD,
+            GCM_PT,
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b2546693\
+             1c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+            "5bc94fbc3221a5db94fae95ae7121a47",
+        );
+    }
+
+    #[test]
+    fn aes_256_gcm() {
+        check::<Aes256Gcm>(
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+            GCM_NONCE,
+            GCM_AAD,
+            GCM_PT,
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb\
+             3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+            "76fc6ece0f4e1768cddf8853bb2d551b",
+        );
+    }
+
+    #[test]
+    fn aes_128_ccm() {
+        // NIST SP 800-38C example 3. The example uses an 8 byte tag, which does not change the
+        // ciphertext, while the tag covers the tag length and differs.
+        check::<Aes128Ccm>(
+            "404142434445464748494a4b4c4d4e4f",
+            "101112131415161718191a1b",
+            "000102030405060708090a0b0c0d0e0f10111213",
+            "202122232425262728292a2b2c2d2e2f3031323334353637",
+            "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5",
+            "c87ae488918de93f17dd3e4934347f44",
+        );
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn aes_128_ocb() {
+        // RFC 7253 appendix A
+        check::<Aes128Ocb>(
+            "000102030405060708090a0b0c0d0e0f",
+            "bbaa99887766554433221101",
+            "0001020304050607",
+            "0001020304050607",
+            "6820b3657b6f615a",
+            "5725bda0d3b4eb3a257c9af1f8f03009",
+        );
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn chacha20_poly1305() {
+        // RFC 8439 section 2.8.2
+        check::<ChaCha20Poly1305>(
+            "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+            "070000004041424344454647",
+            "50515253c0c1c2c3c4c5c6c7",
+            &hex::encode(
+                &b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip \
+                for the future, sunscreen would be it."[..],
+            ),
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca967128\
+             2fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fa\
+             b324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b6116",
+            "1ae10b594f09e26a7e902ecbd0600691",
+        );
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn aes_128_siv() {
+        // RFC 5297 appendix A.2, which has two associated data components ahead of the nonce
+        let key = Vec::from_hex("7f7e7d7c7b7a79787776757473727170404142434445464748494a4b4c4d4e4f")
+            .unwrap();
+        let aad = [
+            Vec::from_hex(
+                "00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa99887766554433221100",
+            )
+            .unwrap(),
+            Vec::from_hex("102030405060708090a0").unwrap(),
+        ];
+        let aad = [&aad[0][..], &aad[1][..]];
+        let nonce = Vec::from_hex("09f911029d74e35bd84156c5635688c0").unwrap();
+        let pt = Vec::from_hex(
+            "7468697320697320736f6d6520706c61696e7465787420746f20656e6372797074207573696e6720\
+             5349562d414553",
+        )
+        .unwrap();
+        let ct = "cb900f2fddbe404326601965c889bf17dba77ceb094fa663b7a3f748ba8af829ea64ad544a272e\
+                  9c485b62a3fd5c0d";
+
+        let mut aead = AeadCtx::new(Algorithm::Fetched("AES-128-SIV"), Kind::Siv, &key).unwrap();
+        for _ in 0..2 {
+            let mut buf = pt.clone();
+            let mut tag = [0; TAG_LEN];
+            unsafe {
+                aead.process(1, &nonce, &aad, &mut buf, &mut tag).unwrap();
+            }
+            assert_eq!(hex::encode(&buf), ct);
+            assert_eq!(hex::encode(tag), "7bdb6e3b432667eb06f4d14bff2fbd0f");
+
+            unsafe {
+                aead.process(0, &nonce, &aad, &mut buf, &mut tag).unwrap();
+            }
+            assert_eq!(buf, pt);
+        }
+    }
+
+    #[test]
+    #[cfg(ossl320)]
+    fn aes_128_gcm_siv() {
+        // RFC 8452 appendix C.1
+        check::<Aes128GcmSiv>(
+            "01000000000000000000000000000000",
+            "030000000000000000000000",
+            "",
+            "0100000000000000",
+            "b5d839330ac7b786",
+            "578782fff6013b815b287c22493a364c",
+        );
+    }
+
+    #[test]
+    #[cfg(ossl320)]
+    fn aes_256_gcm_siv() {
+        // RFC 8452 appendix C.2
+        check::<Aes256GcmSiv>(
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "030000000000000000000000",
+            "",
+            "0100000000000000",
+            "c2ef328e5c71c83b",
+            "843122130f7364b761e0b97427e3df28",
+        );
+    }
+
+    #[test]
+    fn empty_message() {
+        check_empty(Aes128Gcm::new(&[0; 16]).unwrap(), &[0; 12]);
+        check_empty(Aes256Ccm::new(&[0; 32]).unwrap(), &[0; 12]);
+        #[cfg(ossl110)]
+        {
+            check_empty(Aes128Ocb::new(&[0; 16]).unwrap(), &[0; 12]);
+            check_empty(ChaCha20Poly1305::new(&[0; 32]).unwrap(), &[0; 12]);
+        }
+        #[cfg(ossl320)]
+        {
+            check_empty(Aes128Siv::new(&[0; 32]).unwrap(), &[0; 16]);
+            check_empty(Aes128GcmSiv::new(&[0; 16]).unwrap(), &[0; 12]);
+        }
+        #[cfg(all(ossl300, not(ossl320)))]
+        {
+            let mut aead = Aes128Siv::new(&[0; 32]).unwrap();
+            assert!(aead.seal_in_place(&[0; 16], b"header", &mut []).is_err());
+        }
+    }
+
+    #[test]
+    fn tag_from_slice() {
+        assert!(Tag::from_slice(&[0; 15]).is_none());
+        assert_eq!(Tag::from_slice(&[1; 16]), Some(Tag::from([1; 16])));
+        assert_ne!(Tag::from([1; 16]), Tag::from([2; 16]));
+    }
+}
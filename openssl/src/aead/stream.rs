@@ -0,0 +1,460 @@
+//! Online authenticated encryption of large messages.
+//!
+//! This implements the STREAM construction described in [Online Authenticated-Encryption and its
+//! Nonce-Reuse Misuse-Resistance]. The plaintext is split into segments of a fixed size, and each
+//! segment is encrypted separately with a 12 byte nonce made of:
+//!
+//! * a 7 byte prefix, which must be unique for each message encrypted with a key,
+//! * the index of the segment as a 4 byte big-endian integer, and
+//! * a byte which is 1 for the last segment of the message and 0 otherwise.
+//!
+//! Each segment carries its own 16 byte tag, so a segment can be decrypted and authenticated as
+//! soon as it has been received, or independently of the others. Reordering, truncation, and
+//! extension of the ciphertext are all detected.
+//!
+//! AES GCM and ChaCha20-Poly1305 are supported.
+//!
+//! [Online Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance]: https://eprint.iacr.org/2015/189.pdf
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::aead::stream::{Decryptor, Encryptor};
+//! use openssl::rand::rand_bytes;
+//! use openssl::symm::Cipher;
+//!
+//! let cipher = Cipher::aes_256_gcm();
+//! let mut key = [0; 32];
+//! rand_bytes(&mut key).unwrap();
+//! let mut nonce_prefix = [0; 7];
+//! rand_bytes(&mut nonce_prefix).unwrap();
+//!
+//! let mut encryptor = Encryptor::new(cipher, &key, &nonce_prefix, 4);
+//! let mut ciphertext = encryptor.encrypt_next(b"some").unwrap();
+//! ciphertext.extend(encryptor.encrypt_next(b" dat").unwrap());
+//! ciphertext.extend(encryptor.encrypt_last(b"a").unwrap());
+//!
+//! let decryptor = Decryptor::new(cipher, &key, &nonce_prefix, 4);
+//! assert_eq!(decryptor.segment_count(ciphertext.len() as u64), Some(3));
+//! let range = decryptor.segment_range(1, ciphertext.len() as u64).unwrap();
+//! let segment = &ciphertext[range.start as usize..range.end as usize];
+//! assert_eq!(decryptor.decrypt_segment(1, false, segment).unwrap(), b" dat");
+//! ```
+use libc::c_void;
+use std::ops::Range;
+
+use crate::error::ErrorStack;
+use crate::symm::{self, Cipher};
+
+/// The length of the nonce prefix in bytes.
+pub const NONCE_PREFIX_LEN: usize = 7;
+
+/// The length of the tag appended to each segment in bytes.
+pub const TAG_LEN: usize = 16;
+
+struct Params {
+    cipher: Cipher,
+    key: Vec<u8>,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    segment_size: usize,
+}
+
+impl Params {
+    fn new(cipher: Cipher, key: &[u8], nonce_prefix: &[u8], segment_size: usize) -> Params {
+        assert!(is_supported(cipher), "unsupported cipher");
+        assert_eq!(key.len(), cipher.key_len());
+        assert_eq!(nonce_prefix.len(), NONCE_PREFIX_LEN);
+        assert!(segment_size > 0);
+
+        let mut prefix = [0; NONCE_PREFIX_LEN];
+        prefix.copy_from_slice(nonce_prefix);
+        Params {
+            cipher,
+            key: key.to_vec(),
+            nonce_prefix: prefix,
+            segment_size,
+        }
+    }
+
+    // The length of a full segment of ciphertext, including its tag.
+    fn segment_len(&self) -> Option<u64> {
+        (self.segment_size as u64).checked_add(TAG_LEN as u64)
+    }
+
+    fn nonce(&self, index: u32, last: bool) -> [u8; 12] {
+        let mut nonce = [0; 12];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&index.to_be_bytes());
+        nonce[11] = last as u8;
+        nonce
+    }
+
+    fn encrypt(&self, index: u32, last: bool, plaintext: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let nonce = self.nonce(index, last);
+        let mut tag = [0; TAG_LEN];
+        let mut out = symm::encrypt_aead(
+            self.cipher,
+            &self.key,
+            Some(&nonce),
+            &[],
+            plaintext,
+            &mut tag,
+        )?;
+        out.extend_from_slice(&tag);
+        Ok(out)
+    }
+}
+
+fn invalid_length(len: usize) -> ErrorStack {
+    ErrorStack::internal(
+        ffi::ERR_LIB_EVP,
+        ffi::ERR_R_PASSED_INVALID_ARGUMENT,
+        Some(format!("ciphertext length={}", len)),
+    )
+}
+
+impl Drop for Params {
+    fn drop(&mut self) {
+        unsafe { ffi::OPENSSL_cleanse(self.key.as_mut_ptr() as *mut c_void, self.key.len()) }
+    }
+}
+
+fn is_supported(cipher: Cipher) -> bool {
+    #[cfg(ossl110)]
+    {
+        if cipher == Cipher::chacha20_poly1305() {
+            return true;
+        }
+    }
+    cipher == Cipher::aes_128_gcm()
+        || cipher == Cipher::aes_192_gcm()
+        || cipher == Cipher::aes_256_gcm()
+}
+
+/// Encrypts a message one segment at a time.
+pub struct Encryptor {
+    params: Params,
+    index: u32,
+}
+
+impl Encryptor {
+    /// Creates a new `Encryptor`.
+    ///
+    /// Every segment except the last contains exactly `segment_size` bytes of plaintext.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher is not supported, the key or nonce prefix has the wrong length, or
+    /// `segment_size` is 0.
+    pub fn new(cipher: Cipher, key: &[u8], nonce_prefix: &[u8], segment_size: usize) -> Encryptor {
+        Encryptor {
+            params: Params::new(cipher, key, nonce_prefix, segment_size),
+            index: 0,
+        }
+    }
+
+    /// Encrypts the next segment, which is not the last segment of the message.
+    ///
+    /// The ciphertext of the segment, followed by its tag, is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plaintext` is not exactly as long as the segment size, or if the message has
+    /// more than 2^32 segments.
+    pub fn encrypt_next(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        assert_eq!(plaintext.len(), self.params.segment_size);
+        assert!(self.index < u32::max_value(), "too many segments");
+
+        let out = self.params.encrypt(self.index, false, plaintext)?;
+        self.index += 1;
+        Ok(out)
+    }
+
+    /// Encrypts the last segment of the message.
+    ///
+    /// The segment may be empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plaintext` is longer than the segment size.
+    pub fn encrypt_last(self, plaintext: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        assert!(plaintext.len() <= self.params.segment_size);
+
+        self.params.encrypt(self.index, true, plaintext)
+    }
+}
+
+/// Decrypts a message sequentially or one segment at a time.
+pub struct Decryptor {
+    params: Params,
+}
+
+impl Decryptor {
+    /// Creates a new `Decryptor`.
+    ///
+    /// The parameters must match those used to create the [`Encryptor`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher is not supported, the key or nonce prefix has the wrong length, or
+    /// `segment_size` is 0.
+    ///
+    /// [`Encryptor`]: struct.Encryptor.html
+    pub fn new(cipher: Cipher, key: &[u8], nonce_prefix: &[u8], segment_size: usize) -> Decryptor {
+        Decryptor {
+            params: Params::new(cipher, key, nonce_prefix, segment_size),
+        }
+    }
+
+    /// Decrypts and authenticates the segment at `index`, which includes its tag.
+    ///
+    /// `last` must be set if, and only if, the segment is the last segment of the message.
+    pub fn decrypt_segment(
+        &self,
+        index: u32,
+        last: bool,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, ErrorStack> {
+        let data_len = ciphertext
+            .len()
+            .checked_sub(TAG_LEN)
+            .ok_or_else(|| invalid_length(ciphertext.len()))?;
+        if data_len > self.params.segment_size || (!last && data_len != self.params.segment_size) {
+            return Err(invalid_length(ciphertext.len()));
+        }
+
+        let (data, tag) = ciphertext.split_at(ciphertext.len() - TAG_LEN);
+        let nonce = self.params.nonce(index, last);
+        symm::decrypt_aead(
+            self.params.cipher,
+            &self.params.key,
+            Some(&nonce),
+            &[],
+            data,
+            tag,
+        )
+    }
+
+    /// Returns the number of segments in a message with `ciphertext_len` bytes of ciphertext, or
+    /// `None` if no message has that length.
+    pub fn segment_count(&self, ciphertext_len: u64) -> Option<u64> {
+        let segment_len = self.params.segment_len()?;
+        let count = ciphertext_len.checked_add(segment_len - 1)? / segment_len;
+        let last_len = ciphertext_len - (count.checked_sub(1)? * segment_len);
+        if last_len < TAG_LEN as u64 || count - 1 > u64::from(u32::max_value()) {
+            return None;
+        }
+        Some(count)
+    }
+
+    /// Returns the byte range of the segment at `index` in a message with `ciphertext_len` bytes
+    /// of ciphertext, or `None` if there is no such segment.
+    ///
+    /// The segment is the last segment of the message if its range ends at `ciphertext_len`.
+    pub fn segment_range(&self, index: u32, ciphertext_len: u64) -> Option<Range<u64>> {
+        if u64::from(index) >= self.segment_count(ciphertext_len)? {
+            return None;
+        }
+        let segment_len = self.params.segment_len()?;
+        let start = u64::from(index) * segment_len;
+        let end = ciphertext_len.min(start + segment_len);
+        Some(start..end)
+    }
+
+    /// Decrypts and authenticates an entire message.
+    pub fn decrypt_all(&self, ciphertext: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let len = ciphertext.len() as u64;
+        let count = match self.segment_count(len) {
+            Some(count) => count,
+            None => return Err(invalid_length(ciphertext.len())),
+        };
+
+        let mut out = Vec::with_capacity(ciphertext.len());
+        // segment_count ensures that every index fits in a u32
+        for index in 0..count {
+            let range = self.segment_range(index as u32, len).unwrap();
+            let segment = &ciphertext[range.start as usize..range.end as usize];
+            out.extend(self.decrypt_segment(index as u32, index == count - 1, segment)?);
+        }
+        Ok(out)
+    }
+}
+
+/// Encrypts an entire message.
+///
+/// This is equivalent to passing each segment of the plaintext through an [`Encryptor`].
+///
+/// [`Encryptor`]: struct.Encryptor.html
+pub fn encrypt(
+    cipher: Cipher,
+    key: &[u8],
+    nonce_prefix: &[u8],
+    segment_size: usize,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut encryptor = Encryptor::new(cipher, key, nonce_prefix, segment_size);
+    let mut out = Vec::with_capacity(plaintext.len() + TAG_LEN);
+
+    let mut rest = plaintext;
+    while rest.len() > segment_size {
+        let (segment, tail) = rest.split_at(segment_size);
+        out.extend(encryptor.encrypt_next(segment)?);
+        rest = tail;
+    }
+    out.extend(encryptor.encrypt_last(rest)?);
+    Ok(out)
+}
+
+/// Decrypts and authenticates an entire message.
+///
+/// This is equivalent to [`Decryptor::decrypt_all`].
+///
+/// [`Decryptor::decrypt_all`]: struct.Decryptor.html#method.decrypt_all
+pub fn decrypt(
+    cipher: Cipher,
+    key: &[u8],
+    nonce_prefix: &[u8],
+    segment_size: usize,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    Decryptor::new(cipher, key, nonce_prefix, segment_size).decrypt_all(ciphertext)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: [u8; 32] = [1; 32];
+    const PREFIX: [u8; NONCE_PREFIX_LEN] = [2; NONCE_PREFIX_LEN];
+
+    #[test]
+    fn round_trip() {
+        let cipher = Cipher::aes_256_gcm();
+        for &len in &[0, 1, 15, 16, 17, 32, 100] {
+            let plaintext = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+            let ciphertext = encrypt(cipher, &KEY, &PREFIX, 16, &plaintext).unwrap();
+            let segments = if len == 0 { 1 } else { (len + 15) / 16 };
+            assert_eq!(ciphertext.len(), len + segments * TAG_LEN);
+            assert_eq!(
+                decrypt(cipher, &KEY, &PREFIX, 16, &ciphertext).unwrap(),
+                plaintext
+            );
+        }
+    }
+
+    #[test]
+    fn segment_nonces() {
+        let cipher = Cipher::aes_256_gcm();
+        let ciphertext = encrypt(cipher, &KEY, &PREFIX, 4, b"abcdefghij").unwrap();
+
+        let nonce = [2, 2, 2, 2, 2, 2, 2, 0, 0, 0, 2, 1];
+        let mut tag = [0; TAG_LEN];
+        let mut expected =
+            symm::encrypt_aead(cipher, &KEY, Some(&nonce), &[], b"ij", &mut tag).unwrap();
+        expected.extend_from_slice(&tag);
+        assert_eq!(&ciphertext[2 * (4 + TAG_LEN)..], &expected[..]);
+    }
+
+    #[test]
+    fn random_access() {
+        let cipher = Cipher::aes_256_gcm();
+        let plaintext = b"0123456789abcdefghij";
+        let ciphertext = encrypt(cipher, &KEY, &PREFIX, 8, plaintext).unwrap();
+        let len = ciphertext.len() as u64;
+
+        let decryptor = Decryptor::new(cipher, &KEY, &PREFIX, 8);
+        assert_eq!(decryptor.segment_count(len), Some(3));
+        assert_eq!(decryptor.segment_range(3, len), None);
+
+        let range = decryptor.segment_range(2, len).unwrap();
+        assert_eq!(range, 48..len);
+        let segment = &ciphertext[range.start as usize..range.end as usize];
+        assert_eq!(
+            decryptor.decrypt_segment(2, true, segment).unwrap(),
+            b"ghij"
+        );
+        assert!(decryptor.decrypt_segment(2, false, segment).is_err());
+        assert!(decryptor.decrypt_segment(1, true, segment).is_err());
+
+        let range = decryptor.segment_range(1, len).unwrap();
+        let segment = &ciphertext[range.start as usize..range.end as usize];
+        assert_eq!(
+            decryptor.decrypt_segment(1, false, segment).unwrap(),
+            b"89abcdef"
+        );
+    }
+
+    #[test]
+    fn segment_count() {
+        let decryptor = Decryptor::new(Cipher::aes_256_gcm(), &KEY, &PREFIX, 8);
+        assert_eq!(decryptor.segment_count(0), None);
+        assert_eq!(decryptor.segment_count(15), None);
+        assert_eq!(decryptor.segment_count(16), Some(1));
+        assert_eq!(decryptor.segment_count(24), Some(1));
+        assert_eq!(decryptor.segment_count(25), None);
+        assert_eq!(decryptor.segment_count(40), Some(2));
+        assert_eq!(decryptor.segment_count(48), Some(2));
+        assert_eq!(decryptor.segment_count(u64::max_value()), None);
+
+        // 2^32 segments is the most a message can have
+        let max = (1 << 32) * 24;
+        assert_eq!(decryptor.segment_count(max), Some(1 << 32));
+        assert_eq!(decryptor.segment_count(max + 16), None);
+        assert_eq!(
+            decryptor.segment_range(u32::max_value(), max),
+            Some(max - 24..max)
+        );
+
+        let decryptor = Decryptor::new(Cipher::aes_256_gcm(), &KEY, &PREFIX, usize::max_value());
+        assert_eq!(decryptor.segment_count(16), None);
+    }
+
+    #[test]
+    fn invalid_length() {
+        let cipher = Cipher::aes_256_gcm();
+        let decryptor = Decryptor::new(cipher, &KEY, &PREFIX, 8);
+
+        let err = decryptor.decrypt_all(&[0; 25]).unwrap_err();
+        assert_eq!(err.errors()[0].data(), Some("ciphertext length=25"));
+
+        let err = decryptor.decrypt_segment(0, true, &[0; 15]).unwrap_err();
+        assert_eq!(err.errors()[0].data(), Some("ciphertext length=15"));
+        let err = decryptor.decrypt_segment(0, false, &[0; 16]).unwrap_err();
+        assert_eq!(err.errors()[0].data(), Some("ciphertext length=16"));
+    }
+
+    #[test]
+    fn tampering() {
+        let cipher = Cipher::aes_256_gcm();
+        let ciphertext = encrypt(cipher, &KEY, &PREFIX, 8, b"0123456789abcdef").unwrap();
+
+        // truncation to a segment boundary
+        assert!(decrypt(cipher, &KEY, &PREFIX, 8, &ciphertext[..24]).is_err());
+
+        // reordering segments
+        let mut reordered = ciphertext[24..].to_vec();
+        reordered.extend_from_slice(&ciphertext[..24]);
+        assert!(decrypt(cipher, &KEY, &PREFIX, 8, &reordered).is_err());
+
+        // a different nonce prefix
+        assert!(decrypt(cipher, &KEY, &[3; NONCE_PREFIX_LEN], 8, &ciphertext).is_err());
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn chacha20_poly1305() {
+        let cipher = Cipher::chacha20_poly1305();
+        let plaintext = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+        let ciphertext = encrypt(cipher, &KEY, &PREFIX, 64, &plaintext).unwrap();
+        assert_eq!(
+            decrypt(cipher, &KEY, &PREFIX, 64, &ciphertext).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn unsupported_cipher() {
+        Encryptor::new(Cipher::aes_256_cbc(), &KEY, &PREFIX, 16);
+    }
+}
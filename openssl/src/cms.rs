@@ -259,6 +259,9 @@ mod test {
 
     #[test]
     fn cms_encrypt_decrypt() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         // load cert with public key only
         let pub_cert_bytes = include_bytes!("../test/cms_pubkey.der");
         let pub_cert = X509::from_der(pub_cert_bytes).expect("failed to load pub cert");
@@ -1,7 +1,86 @@
 //! Interface for processing OpenSSL configuration files.
 
-use crate::cvt_p;
+use bitflags::bitflags;
+use cfg_if::cfg_if;
+use foreign_types::ForeignTypeRef;
+use libc::{c_char, c_ulong};
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::str;
+
 use crate::error::ErrorStack;
+use crate::{cvt, cvt_p};
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        use ffi::{OPENSSL_sk_num, OPENSSL_sk_value};
+    } else {
+        use ffi::{sk_num as OPENSSL_sk_num, sk_value as OPENSSL_sk_value};
+    }
+}
+
+bitflags! {
+    /// Flags controlling how [`modules_load_file`] handles errors.
+    ///
+    /// [`modules_load_file`]: fn.modules_load_file.html
+    pub struct ModulesLoadFlags: c_ulong {
+        /// Continues loading the remaining modules if one fails to initialize.
+        const IGNORE_ERRORS = ffi::CONF_MFLAGS_IGNORE_ERRORS;
+
+        /// Reports success even if loading failed.
+        ///
+        /// On OpenSSL 3.0 and newer, this has no effect if `config_diagnostics` is enabled in the
+        /// configuration.
+        const IGNORE_RETURN_CODES = ffi::CONF_MFLAGS_IGNORE_RETURN_CODES;
+
+        /// Does not add module errors to the error queue.
+        const SILENT = ffi::CONF_MFLAGS_SILENT;
+
+        /// Does not try to load unknown modules from shared libraries.
+        const NO_DSO = ffi::CONF_MFLAGS_NO_DSO;
+
+        /// Treats a missing configuration file as empty.
+        const IGNORE_MISSING_FILE = ffi::CONF_MFLAGS_IGNORE_MISSING_FILE;
+
+        /// Falls back to the `openssl_conf` section if the application name is not found.
+        ///
+        /// Requires OpenSSL 1.1.0 or newer.
+        #[cfg(ossl110)]
+        const DEFAULT_SECTION = ffi::CONF_MFLAGS_DEFAULT_SECTION;
+    }
+}
+
+/// Loads a configuration file and initializes the modules it configures.
+///
+/// Modules such as `engines`, `alg_section` and `ssl_conf` are looked up in the section named by
+/// `appname`'s value in the default section, or `openssl_conf` if `appname` is `None`. If `path`
+/// is `None`, the library's default configuration file is used.
+///
+/// Unlike configuration loaded during library initialization, errors are reported unless
+/// suppressed by `flags`.
+///
+/// This corresponds to [`CONF_modules_load_file`].
+///
+/// [`CONF_modules_load_file`]: https://www.openssl.org/docs/man1.1.1/man3/CONF_modules_load_file.html
+pub fn modules_load_file(
+    path: Option<&Path>,
+    appname: Option<&str>,
+    flags: ModulesLoadFlags,
+) -> Result<(), ErrorStack> {
+    ffi::init();
+
+    let path = path.map(|p| CString::new(p.as_os_str().to_str().unwrap()).unwrap());
+    let appname = appname.map(|s| CString::new(s).unwrap());
+    unsafe {
+        cvt(ffi::CONF_modules_load_file(
+            path.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            appname.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            flags.bits(),
+        ))
+        .map(|_| ())
+    }
+}
 
 pub struct ConfMethod(*mut ffi::CONF_METHOD);
 
@@ -52,4 +131,172 @@ impl Conf {
     pub fn new(method: ConfMethod) -> Result<Conf, ErrorStack> {
         unsafe { cvt_p(ffi::NCONF_new(method.as_ptr())).map(Conf) }
     }
+
+    /// Loads a configuration file with the default configuration method.
+    ///
+    /// This corresponds to [`NCONF_load`].
+    ///
+    /// [`NCONF_load`]: https://www.openssl.org/docs/man1.1.1/man5/config.html
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Conf, ErrorStack> {
+        let conf = Conf::new(ConfMethod::default())?;
+        let path = CString::new(path.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::NCONF_load(
+                conf.as_ptr(),
+                path.as_ptr(),
+                ptr::null_mut(),
+            ))?;
+        }
+        Ok(conf)
+    }
+}
+
+impl ConfRef {
+    /// Returns the value of `name` in `section`, or `None` if it is not present or is not valid
+    /// UTF-8.
+    ///
+    /// Environment variable references are resolved when the file is loaded. Values in the
+    /// unnamed default section can be looked up with the section `"default"`.
+    ///
+    /// This corresponds to [`NCONF_get_string`].
+    ///
+    /// [`NCONF_get_string`]: https://www.openssl.org/docs/man1.1.1/man5/config.html
+    pub fn get_string(&self, section: &str, name: &str) -> Option<&str> {
+        let section = CString::new(section).unwrap();
+        let name = CString::new(name).unwrap();
+        unsafe {
+            let value = ffi::NCONF_get_string(self.as_ptr(), section.as_ptr(), name.as_ptr());
+            if value.is_null() {
+                // a missing value is reported through the error stack
+                ErrorStack::get();
+                return None;
+            }
+            CStr::from_ptr(value).to_str().ok()
+        }
+    }
+
+    /// Returns the name-value pairs of `section` in the order they appear in the file, or `None` if
+    /// the section does not exist.
+    ///
+    /// Pairs which are not valid UTF-8 are skipped.
+    ///
+    /// This corresponds to [`NCONF_get_section`].
+    ///
+    /// [`NCONF_get_section`]: https://www.openssl.org/docs/man1.1.1/man5/config.html
+    pub fn get_section(&self, section: &str) -> Option<Vec<(&str, &str)>> {
+        self.section(section).ok()
+    }
+
+    pub(crate) fn section(&self, section: &str) -> Result<Vec<(&str, &str)>, ErrorStack> {
+        let name = CString::new(section).unwrap();
+        unsafe {
+            let values = ffi::NCONF_get_section(self.as_ptr(), name.as_ptr());
+            if values.is_null() {
+                // OpenSSL does not report an error for missing sections
+                ErrorStack::get();
+                return Err(ErrorStack::internal(
+                    ffi::ERR_LIB_CONF,
+                    ffi::CONF_R_NO_SECTION,
+                    Some(format!("section={}", section)),
+                ));
+            }
+
+            let mut pairs = vec![];
+            for i in 0..OPENSSL_sk_num(values as *const _) {
+                let value = OPENSSL_sk_value(values as *const _, i) as *const ffi::CONF_VALUE;
+                if let (Some(name), Some(value)) = (to_str((*value).name), to_str((*value).value)) {
+                    pairs.push((name, value));
+                }
+            }
+            Ok(pairs)
+        }
+    }
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        None
+    } else {
+        str::from_utf8(CStr::from_ptr(s).to_bytes()).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn load_file() {
+        let dir = TempDir::new("conf").unwrap();
+        let path = dir.path().join("openssl.cnf");
+        File::create(&path)
+            .unwrap()
+            .write_all(
+                b"top = level\n[tls_server]\nMinProtocol = TLSv1.2\nCipherString = DEFAULT\n",
+            )
+            .unwrap();
+
+        let conf = Conf::load_file(&path).unwrap();
+        assert_eq!(conf.get_string("default", "top"), Some("level"));
+        assert_eq!(
+            conf.get_string("tls_server", "MinProtocol"),
+            Some("TLSv1.2")
+        );
+        assert_eq!(conf.get_string("tls_server", "missing"), None);
+        assert_eq!(
+            conf.get_section("tls_server"),
+            Some(vec![
+                ("MinProtocol", "TLSv1.2"),
+                ("CipherString", "DEFAULT")
+            ])
+        );
+        assert_eq!(conf.get_section("missing"), None);
+    }
+
+    #[test]
+    fn load_missing_file() {
+        assert!(Conf::load_file("/does/not/exist.cnf").is_err());
+    }
+
+    #[test]
+    fn modules_load() {
+        let dir = TempDir::new("conf").unwrap();
+        let path = dir.path().join("openssl.cnf");
+        // the system configuration may enable diagnostics, which override IGNORE_RETURN_CODES
+        File::create(&path)
+            .unwrap()
+            .write_all(
+                b"rust_app = app_sect\n\
+                  rust_bad_app = bad_sect\n\
+                  config_diagnostics = 0\n\
+                  [app_sect]\n\
+                  ssl_conf = ssl_sect\n\
+                  [ssl_sect]\n\
+                  rust_modules_load = tls\n\
+                  [tls]\n\
+                  MinProtocol = TLSv1.2\n\
+                  [bad_sect]\n\
+                  no_such_module = value\n",
+            )
+            .unwrap();
+
+        modules_load_file(Some(&path), Some("rust_app"), ModulesLoadFlags::empty()).unwrap();
+        assert!(
+            modules_load_file(Some(&path), Some("rust_bad_app"), ModulesLoadFlags::NO_DSO).is_err()
+        );
+        modules_load_file(
+            Some(&path),
+            Some("rust_bad_app"),
+            ModulesLoadFlags::NO_DSO | ModulesLoadFlags::IGNORE_RETURN_CODES,
+        )
+        .unwrap();
+
+        let missing = dir.path().join("missing.cnf");
+        assert!(modules_load_file(Some(&missing), None, ModulesLoadFlags::empty()).is_err());
+        modules_load_file(Some(&missing), None, ModulesLoadFlags::IGNORE_MISSING_FILE).unwrap();
+    }
 }
@@ -17,6 +17,7 @@
 //! [Eliptic Curve Cryptography]: https://wiki.openssl.org/index.php/Elliptic_Curve_Cryptography
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::c_int;
+use std::ffi::CStr;
 use std::fmt;
 use std::ptr;
 
@@ -125,6 +126,45 @@ impl EcGroup {
             cvt_p(ffi::EC_GROUP_new_by_curve_name(nid.as_raw())).map(EcGroup)
         }
     }
+
+    /// Returns the named curves built into the library.
+    ///
+    /// This corresponds to [`EC_get_builtin_curves`].
+    ///
+    /// [`EC_get_builtin_curves`]: https://www.openssl.org/docs/man1.1.1/man3/EC_get_builtin_curves.html
+    pub fn builtin_curves() -> Vec<BuiltinCurve> {
+        unsafe {
+            init();
+            let len = ffi::EC_get_builtin_curves(ptr::null_mut(), 0);
+            let mut curves = Vec::with_capacity(len);
+            let len = ffi::EC_get_builtin_curves(curves.as_mut_ptr(), len);
+            curves.set_len(len);
+
+            curves
+                .iter()
+                .map(|curve: &ffi::EC_builtin_curve| BuiltinCurve {
+                    nid: Nid::from_raw(curve.nid),
+                    comment: if curve.comment.is_null() {
+                        ""
+                    } else {
+                        CStr::from_ptr(curve.comment).to_str().unwrap_or("")
+                    },
+                })
+                .collect()
+        }
+    }
+}
+
+/// A named curve built into the library.
+#[derive(Debug, Copy, Clone)]
+pub struct BuiltinCurve {
+    /// The `Nid` of the curve, which can be passed to [`EcGroup::from_curve_name`].
+    ///
+    /// [`EcGroup::from_curve_name`]: struct.EcGroup.html#method.from_curve_name
+    pub nid: Nid,
+
+    /// A human readable description of the curve.
+    pub comment: &'static str,
 }
 
 impl EcGroupRef {
@@ -964,6 +1004,19 @@ mod test {
         EcKey::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
     }
 
+    #[test]
+    fn builtin_curves() {
+        let curves = EcGroup::builtin_curves();
+        let p256 = curves
+            .iter()
+            .find(|c| c.nid == Nid::X9_62_PRIME256V1)
+            .unwrap();
+        assert!(!p256.comment.is_empty());
+        for curve in &curves {
+            EcGroup::from_curve_name(curve.nid).unwrap();
+        }
+    }
+
     #[test]
     fn generate() {
         let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
@@ -46,6 +46,18 @@ impl ErrorStack {
             error.put();
         }
     }
+
+    /// Returns a stack holding a single error detected by this crate rather than by OpenSSL.
+    pub(crate) fn internal(lib: c_int, reason: c_int, data: Option<String>) -> ErrorStack {
+        ErrorStack(vec![Error {
+            code: ffi::ERR_PACK(lib, 0, reason),
+            file: concat!(file!(), "\0").as_ptr() as *const c_char,
+            line: line!() as c_int,
+            #[cfg(ossl300)]
+            func: ptr::null(),
+            data: data.map(Cow::Owned),
+        }])
+    }
 }
 
 impl ErrorStack {
@@ -93,6 +105,8 @@ pub struct Error {
     code: c_ulong,
     file: *const c_char,
     line: c_int,
+    #[cfg(ossl300)]
+    func: *const c_char,
     data: Option<Cow<'static, str>>,
 }
 
@@ -109,7 +123,14 @@ impl Error {
             let mut line = 0;
             let mut data = ptr::null();
             let mut flags = 0;
-            match ffi::ERR_get_error_line_data(&mut file, &mut line, &mut data, &mut flags) {
+            #[cfg(ossl300)]
+            let mut func = ptr::null();
+            #[cfg(ossl300)]
+            let code =
+                ffi::ERR_get_error_all(&mut file, &mut line, &mut func, &mut data, &mut flags);
+            #[cfg(not(ossl300))]
+            let code = ffi::ERR_get_error_line_data(&mut file, &mut line, &mut data, &mut flags);
+            match code {
                 0 => None,
                 code => {
                     // The memory referenced by data is only valid until that slot is overwritten
@@ -130,6 +151,8 @@ impl Error {
                         code,
                         file,
                         line,
+                        #[cfg(ossl300)]
+                        func,
                         data,
                     })
                 }
@@ -140,13 +163,7 @@ impl Error {
     /// Pushes the error back onto the OpenSSL error stack.
     pub fn put(&self) {
         unsafe {
-            ffi::ERR_put_error(
-                ffi::ERR_GET_LIB(self.code),
-                ffi::ERR_GET_FUNC(self.code),
-                ffi::ERR_GET_REASON(self.code),
-                self.file,
-                self.line,
-            );
+            self.put_error();
             let data = match self.data {
                 Some(Cow::Borrowed(data)) => Some((data.as_ptr() as *mut c_char, 0)),
                 Some(Cow::Owned(ref data)) => {
@@ -171,6 +188,28 @@ impl Error {
         }
     }
 
+    #[cfg(ossl300)]
+    unsafe fn put_error(&self) {
+        ffi::ERR_new();
+        ffi::ERR_set_debug(self.file, self.line, self.func);
+        ffi::ERR_set_error(
+            ffi::ERR_GET_LIB(self.code),
+            ffi::ERR_GET_REASON(self.code),
+            ptr::null(),
+        );
+    }
+
+    #[cfg(not(ossl300))]
+    unsafe fn put_error(&self) {
+        ffi::ERR_put_error(
+            ffi::ERR_GET_LIB(self.code),
+            ffi::ERR_GET_FUNC(self.code),
+            ffi::ERR_GET_REASON(self.code),
+            self.file,
+            self.line,
+        );
+    }
+
     /// Returns the raw OpenSSL error code for this error.
     pub fn code(&self) -> c_ulong {
         self.code
@@ -191,6 +230,9 @@ impl Error {
     /// Returns the name of the function reporting the error.
     pub fn function(&self) -> Option<&'static str> {
         unsafe {
+            #[cfg(ossl300)]
+            let cstr = self.func;
+            #[cfg(not(ossl300))]
             let cstr = ffi::ERR_func_error_string(self.code);
             if cstr.is_null() {
                 return None;
@@ -2,21 +2,39 @@
 //!
 //! See [OpenSSL's documentation] for details.
 //!
+//! On OpenSSL 3.0.0 and newer, FIPS mode is controlled by the default property query, and the
+//! `fips` provider must be loaded with [`Provider::load`] for algorithms to be available.
+//!
 //! [OpenSSL's documentation]: https://www.openssl.org/docs/fips/UserGuide-2.0.pdf
+//! [`Provider::load`]: ../provider/struct.Provider.html#method.load
 use crate::cvt;
 use crate::error::ErrorStack;
 
 /// Moves the library into or out of the FIPS 140-2 mode of operation.
 ///
-/// This corresponds to `FIPS_mode_set`.
+/// This corresponds to `FIPS_mode_set`, or `EVP_default_properties_enable_fips` on OpenSSL 3.0.0
+/// and newer.
 pub fn enable(enabled: bool) -> Result<(), ErrorStack> {
     ffi::init();
-    unsafe { cvt(ffi::FIPS_mode_set(enabled as _)).map(|_| ()) }
+    unsafe {
+        #[cfg(ossl300)]
+        let r = ffi::EVP_default_properties_enable_fips(std::ptr::null_mut(), enabled as _);
+        #[cfg(not(ossl300))]
+        let r = ffi::FIPS_mode_set(enabled as _);
+        cvt(r).map(|_| ())
+    }
 }
 
 /// Determines if the library is running in the FIPS 140-2 mode of operation.
 ///
-/// This corresponds to `FIPS_mode`.
+/// This corresponds to `FIPS_mode`, or `EVP_default_properties_is_fips_enabled` on OpenSSL 3.0.0
+/// and newer.
 pub fn enabled() -> bool {
-    unsafe { ffi::FIPS_mode() != 0 }
+    unsafe {
+        #[cfg(ossl300)]
+        let r = ffi::EVP_default_properties_is_fips_enabled(std::ptr::null_mut());
+        #[cfg(not(ossl300))]
+        let r = ffi::FIPS_mode();
+        r != 0
+    }
 }
@@ -1,12 +1,19 @@
 use cfg_if::cfg_if;
+#[cfg(ossl300)]
+use foreign_types::ForeignTypeRef;
+use libc::{c_char, c_void};
 use std::ffi::CString;
 use std::fmt;
 use std::io;
 use std::io::prelude::*;
+#[cfg(ossl300)]
+use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::ptr;
 
 use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 use crate::nid::Nid;
 use crate::{cvt, cvt_p};
 
@@ -65,6 +72,70 @@ impl MessageDigest {
         }
     }
 
+    /// Fetches a digest implementation by name from a provider.
+    ///
+    /// The implementation is looked up in `ctx`, or the default library context if `None`, and
+    /// must match the property query `properties`, for example `"provider=fips"`.
+    ///
+    /// The fetched implementation is released when the returned value is dropped. Use
+    /// [`Hasher::with_digest`] to hash data with it.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// This corresponds to [`EVP_MD_fetch`].
+    ///
+    /// [`Hasher::with_digest`]: struct.Hasher.html#method.with_digest
+    /// [`EVP_MD_fetch`]: https://www.openssl.org/docs/man3.0/man3/EVP_MD_fetch.html
+    #[cfg(ossl300)]
+    pub fn fetch<'a>(
+        ctx: Option<&'a LibCtxRef>,
+        algorithm: &str,
+        properties: Option<&str>,
+    ) -> Result<FetchedMessageDigest<'a>, ErrorStack> {
+        ffi::init();
+
+        let algorithm = CString::new(algorithm).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        unsafe {
+            cvt_p(ffi::EVP_MD_fetch(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                algorithm.as_ptr(),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            ))
+            .map(|md| FetchedMessageDigest(md, PhantomData))
+        }
+    }
+
+    /// Returns every digest known to the library, sorted by name.
+    ///
+    /// Each digest is returned once, even if it is known by several names.
+    ///
+    /// This corresponds to [`EVP_MD_do_all_sorted`].
+    ///
+    /// [`EVP_MD_do_all_sorted`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_MD_do_all_sorted.html
+    pub fn all() -> Vec<MessageDigest> {
+        ffi::init();
+
+        unsafe extern "C" fn callback(
+            md: *const ffi::EVP_MD,
+            _: *const c_char,
+            _: *const c_char,
+            arg: *mut c_void,
+        ) {
+            let mds = &mut *(arg as *mut Vec<MessageDigest>);
+            // aliases are reported without a digest
+            if !md.is_null() && !mds.iter().any(|m| m.0 == md) {
+                mds.push(MessageDigest(md));
+            }
+        }
+
+        let mut mds = vec![];
+        unsafe {
+            ffi::EVP_MD_do_all_sorted(Some(callback), &mut mds as *mut _ as *mut c_void);
+        }
+        mds
+    }
+
     pub fn null() -> MessageDigest {
         unsafe { MessageDigest(ffi::EVP_md_null()) }
     }
@@ -93,6 +164,16 @@ impl MessageDigest {
         unsafe { MessageDigest(ffi::EVP_sha512()) }
     }
 
+    #[cfg(ossl111)]
+    pub fn sha512_224() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_sha512_224()) }
+    }
+
+    #[cfg(ossl111)]
+    pub fn sha512_256() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_sha512_256()) }
+    }
+
     #[cfg(ossl111)]
     pub fn sha3_224() -> MessageDigest {
         unsafe { MessageDigest(ffi::EVP_sha3_224()) }
@@ -128,6 +209,16 @@ impl MessageDigest {
         unsafe { MessageDigest(ffi::EVP_ripemd160()) }
     }
 
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_BLAKE2")))]
+    pub fn blake2b512() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_blake2b512()) }
+    }
+
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_BLAKE2")))]
+    pub fn blake2s256() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_blake2s256()) }
+    }
+
     #[cfg(all(any(ossl111, libressl291), not(osslconf = "OPENSSL_NO_SM3")))]
     pub fn sm3() -> MessageDigest {
         unsafe { MessageDigest(ffi::EVP_sm3()) }
@@ -154,6 +245,61 @@ impl MessageDigest {
 unsafe impl Sync for MessageDigest {}
 unsafe impl Send for MessageDigest {}
 
+/// A digest implementation fetched from a provider with [`MessageDigest::fetch`].
+///
+/// The implementation is released when this value is dropped, and it cannot outlive the library
+/// context it was fetched from. Cloning it takes another reference to the same implementation.
+///
+/// Requires OpenSSL 3.0.0 or newer.
+///
+/// [`MessageDigest::fetch`]: struct.MessageDigest.html#method.fetch
+#[cfg(ossl300)]
+pub struct FetchedMessageDigest<'a>(*mut ffi::EVP_MD, PhantomData<&'a LibCtxRef>);
+
+#[cfg(ossl300)]
+unsafe impl<'a> Sync for FetchedMessageDigest<'a> {}
+#[cfg(ossl300)]
+unsafe impl<'a> Send for FetchedMessageDigest<'a> {}
+
+#[cfg(ossl300)]
+impl<'a> Drop for FetchedMessageDigest<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_MD_free(self.0) }
+    }
+}
+
+#[cfg(ossl300)]
+impl<'a> Clone for FetchedMessageDigest<'a> {
+    fn clone(&self) -> FetchedMessageDigest<'a> {
+        unsafe {
+            ffi::EVP_MD_up_ref(self.0);
+        }
+        FetchedMessageDigest(self.0, PhantomData)
+    }
+}
+
+#[cfg(ossl300)]
+impl<'a> FetchedMessageDigest<'a> {
+    // The returned `MessageDigest` must not outlive `self`, so it is never handed out.
+    fn digest(&self) -> MessageDigest {
+        MessageDigest(self.0)
+    }
+
+    pub fn as_ptr(&self) -> *mut ffi::EVP_MD {
+        self.0
+    }
+
+    /// The size of the digest in bytes.
+    pub fn size(&self) -> usize {
+        self.digest().size()
+    }
+
+    /// The name of the digest.
+    pub fn type_(&self) -> Nid {
+        self.digest().type_()
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 enum State {
     Reset,
@@ -220,6 +366,9 @@ pub struct Hasher {
     md: *const ffi::EVP_MD,
     type_: MessageDigest,
     state: State,
+    // a reference to a fetched `md`, which is released when the hasher is dropped
+    #[cfg(ossl300)]
+    fetched: *mut ffi::EVP_MD,
 }
 
 unsafe impl Sync for Hasher {}
@@ -237,11 +386,28 @@ impl Hasher {
             md: ty.as_ptr(),
             type_: ty,
             state: Finalized,
+            #[cfg(ossl300)]
+            fetched: ptr::null_mut(),
         };
         h.init()?;
         Ok(h)
     }
 
+    /// Creates a new `Hasher` using a digest fetched from a provider.
+    ///
+    /// The hasher keeps its own reference to the digest.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn with_digest(md: &FetchedMessageDigest<'_>) -> Result<Hasher, ErrorStack> {
+        let mut h = Hasher::new(md.digest())?;
+        unsafe {
+            cvt(ffi::EVP_MD_up_ref(md.as_ptr()))?;
+        }
+        h.fetched = md.as_ptr();
+        Ok(h)
+    }
+
     fn init(&mut self) -> Result<(), ErrorStack> {
         match self.state {
             Reset => return Ok(()),
@@ -257,6 +423,26 @@ impl Hasher {
         Ok(())
     }
 
+    /// Discards any data fed into the hasher and starts a new hash.
+    ///
+    /// Unlike [`finish`], no digest is computed, and the underlying context
+    /// is reused rather than reallocated.
+    ///
+    /// This corresponds to [`EVP_DigestInit_ex`].
+    ///
+    /// [`finish`]: #method.finish
+    /// [`EVP_DigestInit_ex`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_DigestInit.html
+    pub fn reset(&mut self) -> Result<(), ErrorStack> {
+        if self.state == Reset {
+            return Ok(());
+        }
+        unsafe {
+            cvt(ffi::EVP_DigestInit_ex(self.ctx, self.md, ptr::null_mut()))?;
+        }
+        self.state = Reset;
+        Ok(())
+    }
+
     /// Feeds data into the hasher.
     pub fn update(&mut self, data: &[u8]) -> Result<(), ErrorStack> {
         if self.state == Finalized {
@@ -311,6 +497,76 @@ impl Hasher {
             Ok(())
         }
     }
+
+    /// Finalizes the XOF hasher and returns a reader over its output.
+    ///
+    /// Unlike [`finish_xof`], the output length does not need to be known up
+    /// front: each read squeezes the next bytes of the output stream.
+    ///
+    /// This corresponds to [`EVP_DigestSqueeze`].
+    ///
+    /// Requires OpenSSL 3.3.0 or newer.
+    ///
+    /// [`finish_xof`]: #method.finish_xof
+    /// [`EVP_DigestSqueeze`]: https://www.openssl.org/docs/man3.3/man3/EVP_DigestSqueeze.html
+    #[cfg(ossl330)]
+    pub fn finalize_xof_reader(mut self) -> Result<XofReader, ErrorStack> {
+        if self.state == Finalized {
+            self.init()?;
+        }
+        // The context is owned by the reader from here on, and must not be
+        // finalized again when it is dropped.
+        self.state = Finalized;
+        Ok(XofReader { hasher: self })
+    }
+}
+
+/// A reader over the output of an extendable output function.
+///
+/// Created by [`Hasher::finalize_xof_reader`].
+///
+/// The output is unbounded, so [`Read::read`] never returns 0 and never signals end of file.
+/// Functions which read to the end, such as [`Read::read_to_end`], never return. Use
+/// [`Read::take`] to read a fixed amount of output.
+///
+/// Requires OpenSSL 3.3.0 or newer.
+///
+/// [`Hasher::finalize_xof_reader`]: struct.Hasher.html#method.finalize_xof_reader
+/// [`Read::read`]: https://doc.rust-lang.org/std/io/trait.Read.html#tymethod.read
+/// [`Read::read_to_end`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.read_to_end
+/// [`Read::take`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.take
+#[cfg(ossl330)]
+pub struct XofReader {
+    hasher: Hasher,
+}
+
+#[cfg(ossl330)]
+impl XofReader {
+    /// Fills `buf` with the next bytes of the output.
+    ///
+    /// This corresponds to [`EVP_DigestSqueeze`].
+    ///
+    /// [`EVP_DigestSqueeze`]: https://www.openssl.org/docs/man3.3/man3/EVP_DigestSqueeze.html
+    pub fn squeeze(&mut self, buf: &mut [u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_DigestSqueeze(
+                self.hasher.ctx,
+                buf.as_mut_ptr(),
+                buf.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
+/// Reads fill the entire buffer and never return 0, so `read_to_end` and `read_to_string` loop
+/// forever. Use `take` to bound the amount of output read.
+#[cfg(ossl330)]
+impl Read for XofReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.squeeze(buf)?;
+        Ok(buf.len())
+    }
 }
 
 impl Write for Hasher {
@@ -334,11 +590,21 @@ impl Clone for Hasher {
             assert_eq!(r, 1);
             ctx
         };
+        #[cfg(ossl300)]
+        {
+            if !self.fetched.is_null() {
+                unsafe {
+                    ffi::EVP_MD_up_ref(self.fetched);
+                }
+            }
+        }
         Hasher {
             ctx,
             md: self.md,
             type_: self.type_,
             state: self.state,
+            #[cfg(ossl300)]
+            fetched: self.fetched,
         }
     }
 }
@@ -350,6 +616,12 @@ impl Drop for Hasher {
                 drop(self.finish());
             }
             EVP_MD_CTX_free(self.ctx);
+            #[cfg(ossl300)]
+            {
+                if !self.fetched.is_null() {
+                    ffi::EVP_MD_free(self.fetched);
+                }
+            }
         }
     }
 }
@@ -544,6 +816,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reset() {
+        let mut h = Hasher::new(MessageDigest::md5()).unwrap();
+        h.reset().unwrap();
+        h.write_all(&Vec::from_hex(MD5_TESTS[6].0).unwrap())
+            .unwrap();
+        h.reset().unwrap();
+        h.write_all(&Vec::from_hex(MD5_TESTS[7].0).unwrap())
+            .unwrap();
+        let res = h.finish().unwrap();
+        assert_eq!(hex::encode(res), MD5_TESTS[7].1);
+
+        h.reset().unwrap();
+        for test in MD5_TESTS.iter() {
+            hash_recycle_test(&mut h, test);
+        }
+    }
+
+    #[cfg(ossl111)]
+    #[test]
+    fn test_sha512_224() {
+        let tests = [(
+            "616263",
+            "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa",
+        )];
+
+        for test in tests.iter() {
+            hash_test(MessageDigest::sha512_224(), test);
+        }
+    }
+
+    #[cfg(ossl111)]
+    #[test]
+    fn test_sha512_256() {
+        let tests = [(
+            "616263",
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+        )];
+
+        for test in tests.iter() {
+            hash_test(MessageDigest::sha512_256(), test);
+        }
+    }
+
+    // Test vectors from RFC 7693
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_BLAKE2")))]
+    #[test]
+    fn test_blake2b512() {
+        let tests = [(
+            "616263",
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        )];
+
+        for test in tests.iter() {
+            hash_test(MessageDigest::blake2b512(), test);
+        }
+    }
+
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_BLAKE2")))]
+    #[test]
+    fn test_blake2s256() {
+        let tests = [(
+            "616263",
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+        )];
+
+        for test in tests.iter() {
+            hash_test(MessageDigest::blake2s256(), test);
+        }
+    }
+
     #[cfg(ossl111)]
     #[test]
     fn test_sha3_224() {
@@ -622,6 +966,57 @@ mod tests {
         }
     }
 
+    #[cfg(ossl330)]
+    #[test]
+    fn test_xof_reader() {
+        let data = b"All your base are belong to us";
+        let mut expected = vec![0; 300];
+        hash_xof(MessageDigest::shake_256(), data, &mut expected).unwrap();
+
+        let mut h = Hasher::new(MessageDigest::shake_256()).unwrap();
+        h.update(data).unwrap();
+        let mut reader = h.finalize_xof_reader().unwrap();
+        let mut out = vec![0; 300];
+        let (first, rest) = out.split_at_mut(7);
+        reader.read_exact(first).unwrap();
+        let (second, third) = rest.split_at_mut(136);
+        reader.read_exact(second).unwrap();
+        reader.squeeze(&mut []).unwrap();
+        reader.squeeze(third).unwrap();
+        assert_eq!(out, expected);
+
+        // byte at a time across the 168 byte SHAKE128 block boundary
+        let mut expected = vec![0; 200];
+        hash_xof(MessageDigest::shake_128(), data, &mut expected).unwrap();
+        let mut h = Hasher::new(MessageDigest::shake_128()).unwrap();
+        h.update(data).unwrap();
+        let mut reader = h.finalize_xof_reader().unwrap();
+        let mut out = vec![0; 200];
+        for b in &mut out {
+            reader.read_exact(std::slice::from_mut(b)).unwrap();
+        }
+        assert_eq!(out, expected);
+
+        // the output never ends, so reading to the end must be bounded
+        let mut h = Hasher::new(MessageDigest::shake_128()).unwrap();
+        h.update(data).unwrap();
+        let mut out = vec![];
+        h.finalize_xof_reader()
+            .unwrap()
+            .take(200)
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, expected);
+
+        // only XOFs can be squeezed
+        let h = Hasher::new(MessageDigest::sha256()).unwrap();
+        assert!(h
+            .finalize_xof_reader()
+            .unwrap()
+            .squeeze(&mut [0; 32])
+            .is_err());
+    }
+
     #[test]
     fn test_ripemd160() {
         let tests = [("616263", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")];
@@ -659,4 +1054,67 @@ mod tests {
             MessageDigest::sha256().as_ptr()
         )
     }
+
+    #[test]
+    fn all() {
+        let mds = MessageDigest::all();
+        assert!(mds.contains(&MessageDigest::sha256()));
+        assert!(mds.contains(&MessageDigest::sha512()));
+        for (i, md) in mds.iter().enumerate() {
+            assert!(!mds[..i].contains(md));
+        }
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetch() {
+        let md = MessageDigest::fetch(None, "SHA2-256", Some("provider=default")).unwrap();
+        assert_eq!(md.type_(), Nid::SHA256);
+        assert_eq!(md.size(), 32);
+
+        let mut h = Hasher::with_digest(&md.clone()).unwrap();
+        drop(md);
+        h.update(b"abc").unwrap();
+        let mut copy = h.clone();
+        assert_eq!(
+            &*h.finish().unwrap(),
+            &*hash(MessageDigest::sha256(), b"abc").unwrap()
+        );
+        copy.update(b"def").unwrap();
+        assert_eq!(
+            &*copy.finish().unwrap(),
+            &*hash(MessageDigest::sha256(), b"abcdef").unwrap()
+        );
+
+        // the hasher is reinitialized with the fetched digest
+        h.update(b"abc").unwrap();
+        assert_eq!(
+            &*h.finish().unwrap(),
+            &*hash(MessageDigest::sha256(), b"abc").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetch_lib_ctx() {
+        use crate::lib_ctx::LibCtx;
+        use crate::provider::Provider;
+
+        let ctx = LibCtx::new().unwrap();
+        let _default = Provider::load(Some(&ctx), "default").unwrap();
+        let md = MessageDigest::fetch(Some(&ctx), "SHA2-512", None).unwrap();
+        let mut h = Hasher::with_digest(&md).unwrap();
+        h.update(b"abc").unwrap();
+        assert_eq!(
+            &*h.finish().unwrap(),
+            &*hash(MessageDigest::sha512(), b"abc").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetch_missing() {
+        assert!(MessageDigest::fetch(None, "NOT-A-DIGEST", None).is_err());
+        assert!(MessageDigest::fetch(None, "SHA256", Some("provider=missing")).is_err());
+    }
 }
@@ -0,0 +1,193 @@
+//! Library initialization.
+//!
+//! OpenSSL is initialized automatically the first time this crate uses it, loading the system's
+//! default configuration file. [`InitOptions`] allows the configuration to be chosen explicitly
+//! instead, which must happen before anything else in the process uses OpenSSL. On OpenSSL 1.1.1b
+//! and newer, [`InitOptions::set_config_file`] can additionally select a different file.
+//!
+//! [`InitOptions`]: struct.InitOptions.html
+//! [`InitOptions::set_config_file`]: struct.InitOptions.html#method.set_config_file
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use openssl::init::InitOptions;
+//!
+//! let mut options = InitOptions::new();
+//! options.set_config_appname("myapp");
+//! options.init().unwrap();
+//! ```
+use std::ffi::CString;
+#[cfg(ossl111b)]
+use std::path::Path;
+
+use crate::error::ErrorStack;
+use crate::{cvt, cvt_p};
+
+/// Options used to initialize OpenSSL.
+///
+/// The configuration file is loaded at most once per process, by the first initialization which
+/// requests it. Because this crate initializes OpenSSL with the default configuration before its
+/// first use, [`init`] must be called before any other function in this crate to take effect.
+///
+/// Errors in the configuration file are ignored. Use [`conf::modules_load_file`] to load a
+/// configuration file strictly.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// [`init`]: #method.init
+/// [`conf::modules_load_file`]: ../conf/fn.modules_load_file.html
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    config_file: Option<CString>,
+    config_appname: Option<CString>,
+    load_config: bool,
+    load_strings: bool,
+    no_atexit: bool,
+}
+
+impl Default for InitOptions {
+    fn default() -> InitOptions {
+        InitOptions::new()
+    }
+}
+
+impl InitOptions {
+    /// Creates options which load the default configuration file and error strings.
+    ///
+    /// Like the initialization performed automatically by this crate, no `atexit` handler is
+    /// registered where supported.
+    pub fn new() -> InitOptions {
+        InitOptions {
+            config_file: None,
+            config_appname: None,
+            load_config: true,
+            load_strings: true,
+            no_atexit: true,
+        }
+    }
+
+    /// Sets the configuration file to load instead of the system default.
+    ///
+    /// This corresponds to [`OPENSSL_INIT_set_config_filename`].
+    ///
+    /// Requires OpenSSL 1.1.1b or newer.
+    ///
+    /// [`OPENSSL_INIT_set_config_filename`]: https://www.openssl.org/docs/man1.1.1/man3/OPENSSL_INIT_set_config_filename.html
+    #[cfg(ossl111b)]
+    pub fn set_config_file<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref().as_os_str().to_str().unwrap();
+        self.config_file = Some(CString::new(path).unwrap());
+    }
+
+    /// Sets the name of the section in the configuration file to use.
+    ///
+    /// Defaults to `openssl_conf`.
+    ///
+    /// This corresponds to [`OPENSSL_INIT_set_config_appname`].
+    ///
+    /// [`OPENSSL_INIT_set_config_appname`]: https://www.openssl.org/docs/man1.1.1/man3/OPENSSL_INIT_set_config_appname.html
+    pub fn set_config_appname(&mut self, appname: &str) {
+        self.config_appname = Some(CString::new(appname).unwrap());
+    }
+
+    /// Controls whether a configuration file is loaded at all.
+    ///
+    /// Defaults to `true`.
+    pub fn set_load_config(&mut self, load_config: bool) {
+        self.load_config = load_config;
+    }
+
+    /// Controls whether human readable error strings are loaded.
+    ///
+    /// Defaults to `true`.
+    pub fn set_load_strings(&mut self, load_strings: bool) {
+        self.load_strings = load_strings;
+    }
+
+    /// Prevents OpenSSL from registering an `atexit` handler to free its resources.
+    ///
+    /// This avoids crashes when other threads still use OpenSSL while the process exits.
+    ///
+    /// Defaults to `true`.
+    ///
+    /// Requires OpenSSL 1.1.1b or newer.
+    #[cfg(ossl111b)]
+    pub fn set_no_atexit(&mut self, no_atexit: bool) {
+        self.no_atexit = no_atexit;
+    }
+
+    /// Initializes OpenSSL with these options.
+    ///
+    /// This corresponds to [`OPENSSL_init_crypto`].
+    ///
+    /// [`OPENSSL_init_crypto`]: https://www.openssl.org/docs/man1.1.1/man3/OPENSSL_init_crypto.html
+    pub fn init(&self) -> Result<(), ErrorStack> {
+        let mut opts = if self.load_config {
+            ffi::OPENSSL_INIT_LOAD_CONFIG
+        } else {
+            ffi::OPENSSL_INIT_NO_LOAD_CONFIG
+        };
+        opts |= if self.load_strings {
+            ffi::OPENSSL_INIT_LOAD_CRYPTO_STRINGS
+        } else {
+            ffi::OPENSSL_INIT_NO_LOAD_CRYPTO_STRINGS
+        };
+        #[cfg(ossl111b)]
+        {
+            if self.no_atexit {
+                opts |= ffi::OPENSSL_INIT_NO_ATEXIT;
+            }
+        }
+
+        unsafe {
+            let settings = InitSettings(cvt_p(ffi::OPENSSL_INIT_new())?);
+            #[cfg(ossl111b)]
+            {
+                if let Some(path) = &self.config_file {
+                    cvt(ffi::OPENSSL_INIT_set_config_filename(
+                        settings.0,
+                        path.as_ptr(),
+                    ))?;
+                }
+            }
+            if let Some(appname) = &self.config_appname {
+                cvt(ffi::OPENSSL_INIT_set_config_appname(
+                    settings.0,
+                    appname.as_ptr(),
+                ))?;
+            }
+
+            cvt(ffi::OPENSSL_init_crypto(opts, settings.0))?;
+        }
+
+        ffi::init();
+        Ok(())
+    }
+}
+
+struct InitSettings(*mut ffi::OPENSSL_INIT_SETTINGS);
+
+impl Drop for InitSettings {
+    fn drop(&mut self) {
+        unsafe { ffi::OPENSSL_INIT_free(self.0) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash::{hash, MessageDigest};
+
+    #[test]
+    fn init_with_settings() {
+        let mut options = InitOptions::new();
+        options.set_config_appname("rust_init");
+        options.set_load_strings(true);
+        options.init().unwrap();
+
+        // initialization is idempotent and leaves the library usable
+        InitOptions::default().init().unwrap();
+        hash(MessageDigest::sha256(), b"").unwrap();
+    }
+}
@@ -0,0 +1,884 @@
+//! Key derivation functions.
+//!
+//! [`Hkdf`] implements HKDF, and [`Kdf`] provides access to the other key derivation functions
+//! supported by OpenSSL, such as the TLS 1.2 PRF.
+//!
+//! Requires OpenSSL 1.1.0 or newer. The TLS 1.2 PRF is the only function available through
+//! [`Kdf`] before OpenSSL 3.0.0, which adds SSKDF, X9.63 and KBKDF as well as any other
+//! function supplied by a provider.
+//!
+//! [`Hkdf`]: struct.Hkdf.html
+//! [`Kdf`]: struct.Kdf.html
+//!
+//! # Examples
+//!
+//! Derive a 42 byte key with HKDF-SHA256.
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::kdf::hkdf;
+//!
+//! let mut key = [0; 42];
+//! hkdf(MessageDigest::sha256(), b"input key material", b"salt", b"context", &mut key).unwrap();
+//! ```
+//!
+//! Derive a TLS 1.2 master secret from a premaster secret.
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::kdf::Kdf;
+//!
+//! # let premaster_secret = [0; 48];
+//! # let client_random = [0; 32];
+//! # let server_random = [0; 32];
+//! let mut kdf = Kdf::tls1_prf().unwrap();
+//! kdf.set_digest(MessageDigest::sha256());
+//! kdf.set_secret(&premaster_secret);
+//! kdf.add_seed(b"master secret");
+//! kdf.add_seed(&client_random);
+//! kdf.add_seed(&server_random);
+//!
+//! let mut master_secret = [0; 48];
+//! kdf.derive(&mut master_secret).unwrap();
+//! ```
+#[cfg(ossl300)]
+use foreign_types::ForeignTypeRef;
+#[cfg(any(ossl111, not(ossl300)))]
+use libc::c_int;
+use libc::c_void;
+#[cfg(ossl300)]
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
+#[cfg(ossl300)]
+use crate::symm::Cipher;
+use crate::{cvt, cvt_p};
+
+/// The stages of HKDF to perform.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HkdfMode(c_int);
+
+#[cfg(ossl111)]
+impl HkdfMode {
+    /// Extracts a pseudorandom key from the input key material, then expands it into the output.
+    pub const EXTRACT_AND_EXPAND: HkdfMode = HkdfMode(ffi::EVP_PKEY_HKDEF_MODE_EXTRACT_AND_EXPAND);
+
+    /// Only extracts a pseudorandom key from the input key material.
+    ///
+    /// The output must be exactly as long as the digest.
+    pub const EXTRACT_ONLY: HkdfMode = HkdfMode(ffi::EVP_PKEY_HKDEF_MODE_EXTRACT_ONLY);
+
+    /// Only expands the key, which must already be a pseudorandom key, into the output.
+    pub const EXPAND_ONLY: HkdfMode = HkdfMode(ffi::EVP_PKEY_HKDEF_MODE_EXPAND_ONLY);
+}
+
+/// The HMAC-based key derivation function described in [RFC 5869].
+///
+/// By default, a key is both extracted and expanded.
+///
+/// [RFC 5869]: https://tools.ietf.org/html/rfc5869
+pub struct Hkdf {
+    md: MessageDigest,
+    #[cfg(ossl111)]
+    mode: HkdfMode,
+    key: Vec<u8>,
+    salt: Vec<u8>,
+    info: Vec<u8>,
+}
+
+impl Drop for Hkdf {
+    fn drop(&mut self) {
+        cleanse(&mut self.key);
+        cleanse(&mut self.salt);
+        cleanse(&mut self.info);
+    }
+}
+
+impl Hkdf {
+    /// Creates a new `Hkdf` using the specified digest.
+    pub fn new(md: MessageDigest) -> Hkdf {
+        Hkdf {
+            md,
+            #[cfg(ossl111)]
+            mode: HkdfMode::EXTRACT_AND_EXPAND,
+            key: vec![],
+            salt: vec![],
+            info: vec![],
+        }
+    }
+
+    /// Selects the stages of HKDF to perform.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn set_mode(&mut self, mode: HkdfMode) {
+        self.mode = mode;
+    }
+
+    /// Sets the input key material, or the pseudorandom key in [`HkdfMode::EXPAND_ONLY`] mode.
+    ///
+    /// The key must not be empty.
+    ///
+    /// [`HkdfMode::EXPAND_ONLY`]: struct.HkdfMode.html#associatedconstant.EXPAND_ONLY
+    pub fn set_key(&mut self, key: &[u8]) {
+        cleanse(&mut self.key);
+        self.key.clear();
+        self.key.extend_from_slice(key);
+    }
+
+    /// Sets the salt used during extraction.
+    ///
+    /// Defaults to an empty salt, which is equivalent to a string of zeros as long as the digest.
+    pub fn set_salt(&mut self, salt: &[u8]) {
+        cleanse(&mut self.salt);
+        self.salt.clear();
+        self.salt.extend_from_slice(salt);
+    }
+
+    /// Appends to the context and application specific information used during expansion.
+    ///
+    /// Before OpenSSL 3.0.0, the total length of the information is limited to 1024 bytes.
+    pub fn add_info(&mut self, info: &[u8]) {
+        self.info.extend_from_slice(info);
+    }
+
+    /// Derives a key into `out`, filling it completely.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mode is [`HkdfMode::EXTRACT_ONLY`] and `out` is not exactly as long as the
+    /// digest.
+    ///
+    /// [`HkdfMode::EXTRACT_ONLY`]: struct.HkdfMode.html#associatedconstant.EXTRACT_ONLY
+    pub fn derive(&self, out: &mut [u8]) -> Result<(), ErrorStack> {
+        #[cfg(ossl111)]
+        {
+            if self.mode == HkdfMode::EXTRACT_ONLY {
+                assert_eq!(out.len(), self.md.size());
+            }
+        }
+
+        ffi::init();
+        self.derive_inner(out)
+    }
+
+    #[cfg(not(ossl300))]
+    fn derive_inner(&self, out: &mut [u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let ctx = PkeyCtx(cvt_p(ffi::EVP_PKEY_CTX_new_id(
+                ffi::EVP_PKEY_HKDF,
+                ptr::null_mut(),
+            ))?);
+            cvt(ffi::EVP_PKEY_derive_init(ctx.0))?;
+            ctx.ctrl(ffi::EVP_PKEY_CTRL_HKDF_MD, 0, self.md.as_ptr() as *mut _)?;
+            #[cfg(ossl111)]
+            ctx.ctrl(ffi::EVP_PKEY_CTRL_HKDF_MODE, self.mode.0, ptr::null_mut())?;
+            ctx.ctrl_bytes(ffi::EVP_PKEY_CTRL_HKDF_SALT, &self.salt)?;
+            ctx.ctrl_bytes(ffi::EVP_PKEY_CTRL_HKDF_KEY, &self.key)?;
+            ctx.ctrl_bytes(ffi::EVP_PKEY_CTRL_HKDF_INFO, &self.info)?;
+
+            let mut len = out.len();
+            cvt(ffi::EVP_PKEY_derive(ctx.0, out.as_mut_ptr(), &mut len))?;
+            Ok(())
+        }
+    }
+
+    #[cfg(ossl300)]
+    fn derive_inner(&self, out: &mut [u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let mut mode = self.mode.0;
+            let mut params = vec![
+                ffi::OSSL_PARAM_construct_utf8_string(
+                    b"digest\0".as_ptr() as *const _,
+                    ffi::EVP_MD_get0_name(self.md.as_ptr()) as *mut _,
+                    0,
+                ),
+                ffi::OSSL_PARAM_construct_int(b"mode\0".as_ptr() as *const _, &mut mode),
+            ];
+            if !self.key.is_empty() {
+                params.push(octet_string(b"key\0", &self.key));
+            }
+            if !self.salt.is_empty() {
+                params.push(octet_string(b"salt\0", &self.salt));
+            }
+            if !self.info.is_empty() {
+                params.push(octet_string(b"info\0", &self.info));
+            }
+            params.push(ffi::OSSL_PARAM_construct_end());
+
+            derive_by_name(b"HKDF\0", &params, out)
+        }
+    }
+}
+
+/// Derives a key into `out` with HKDF, extracting and expanding it from the input key material
+/// `key`.
+///
+/// This is equivalent to configuring an [`Hkdf`] with the same parameters.
+///
+/// [`Hkdf`]: struct.Hkdf.html
+pub fn hkdf(
+    md: MessageDigest,
+    key: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let mut hkdf = Hkdf::new(md);
+    hkdf.set_key(key);
+    hkdf.set_salt(salt);
+    hkdf.add_info(info);
+    hkdf.derive(out)
+}
+
+/// The mode of operation of KBKDF.
+///
+/// Requires OpenSSL 3.0.0 or newer.
+#[cfg(ossl300)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KbkdfMode(&'static [u8]);
+
+#[cfg(ossl300)]
+impl KbkdfMode {
+    /// Counter mode, as described in section 4.1 of NIST SP 800-108.
+    pub const COUNTER: KbkdfMode = KbkdfMode(b"counter\0");
+
+    /// Feedback mode, as described in section 4.2 of NIST SP 800-108.
+    ///
+    /// The initial value is set with [`Kdf::add_seed`].
+    ///
+    /// [`Kdf::add_seed`]: struct.Kdf.html#method.add_seed
+    pub const FEEDBACK: KbkdfMode = KbkdfMode(b"feedback\0");
+}
+
+enum Param {
+    Digest(MessageDigest),
+    Octets(Vec<u8>),
+    #[cfg(ossl300)]
+    Utf8(CString),
+    #[cfg(ossl300)]
+    Int(c_int),
+}
+
+/// A key derivation function.
+///
+/// Parameters are buffered by the `Kdf` and passed to OpenSSL when a key is derived, so a
+/// configured `Kdf` can derive any number of keys. Which parameters are required depends on the
+/// algorithm:
+///
+/// * TLS1-PRF takes a digest, a secret and a seed.
+/// * SSKDF and X963KDF take a digest, a key and optionally info. SSKDF can also use a MAC and
+///   salt instead of a plain digest. Requires OpenSSL 3.0.0 or newer.
+/// * KBKDF takes a MAC, a digest or cipher for the MAC, a key, and optionally a label, set with
+///   [`set_salt`], and a context, set with [`add_info`]. Requires OpenSSL 3.0.0 or newer.
+///
+/// Before OpenSSL 3.0.0, only TLS1-PRF is supported, and only the parameters it takes can be set.
+///
+/// [`set_salt`]: #method.set_salt
+/// [`add_info`]: #method.add_info
+pub struct Kdf {
+    #[cfg(ossl300)]
+    kdf: KdfAlg,
+    #[cfg(not(ossl300))]
+    id: c_int,
+    params: Vec<(&'static [u8], Param)>,
+}
+
+unsafe impl Sync for Kdf {}
+unsafe impl Send for Kdf {}
+
+impl Drop for Kdf {
+    fn drop(&mut self) {
+        for (_, param) in &mut self.params {
+            if let Param::Octets(buf) = param {
+                cleanse(buf);
+            }
+        }
+    }
+}
+
+impl Kdf {
+    /// Fetches a key derivation function implementation by name, such as `"SSKDF"`.
+    ///
+    /// The implementation is looked up in `ctx`, or the default library context if `None`, and
+    /// must match the property query `properties`.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// This corresponds to [`EVP_KDF_fetch`].
+    ///
+    /// [`EVP_KDF_fetch`]: https://www.openssl.org/docs/man3.0/man3/EVP_KDF_fetch.html
+    #[cfg(ossl300)]
+    pub fn fetch(
+        ctx: Option<&LibCtxRef>,
+        algorithm: &str,
+        properties: Option<&str>,
+    ) -> Result<Kdf, ErrorStack> {
+        ffi::init();
+
+        let algorithm = CString::new(algorithm).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        unsafe {
+            let kdf = KdfAlg::fetch(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                algorithm.as_ptr(),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            )?;
+            Ok(Kdf {
+                kdf,
+                params: vec![],
+            })
+        }
+    }
+
+    /// Returns the TLS 1.0-1.2 pseudorandom function described in [RFC 5246].
+    ///
+    /// With TLS 1.2, the digest is that of the cipher suite. Earlier versions use
+    /// [`MessageDigest::md5_sha1`].
+    ///
+    /// [RFC 5246]: https://tools.ietf.org/html/rfc5246#section-5
+    /// [`MessageDigest::md5_sha1`]: ../hash/struct.MessageDigest.html#method.md5_sha1
+    pub fn tls1_prf() -> Result<Kdf, ErrorStack> {
+        #[cfg(ossl300)]
+        {
+            Kdf::fetch(None, "TLS1-PRF", None)
+        }
+        #[cfg(not(ossl300))]
+        {
+            ffi::init();
+            Ok(Kdf {
+                id: ffi::EVP_PKEY_TLS1_PRF,
+                params: vec![],
+            })
+        }
+    }
+
+    /// Returns the single-step key derivation function described in NIST SP 800-56C.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn sskdf() -> Result<Kdf, ErrorStack> {
+        Kdf::fetch(None, "SSKDF", None)
+    }
+
+    /// Returns the ANSI X9.63 key derivation function.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn x963() -> Result<Kdf, ErrorStack> {
+        Kdf::fetch(None, "X963KDF", None)
+    }
+
+    /// Returns the key-based key derivation function described in NIST SP 800-108.
+    ///
+    /// Counter mode is used unless another mode is selected with [`set_kbkdf_mode`].
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`set_kbkdf_mode`]: #method.set_kbkdf_mode
+    #[cfg(ossl300)]
+    pub fn kbkdf() -> Result<Kdf, ErrorStack> {
+        Kdf::fetch(None, "KBKDF", None)
+    }
+
+    /// Sets the digest used by the function.
+    pub fn set_digest(&mut self, md: MessageDigest) {
+        self.set(b"digest\0", Param::Digest(md));
+    }
+
+    /// Sets the secret to derive the key from.
+    pub fn set_secret(&mut self, secret: &[u8]) {
+        self.set(b"secret\0", Param::Octets(secret.to_vec()));
+    }
+
+    /// Appends to the seed.
+    ///
+    /// For TLS1-PRF, the seed is the label followed by the seed of the PRF. Before OpenSSL
+    /// 3.0.0, its total length is limited to 1024 bytes.
+    pub fn add_seed(&mut self, seed: &[u8]) {
+        self.append(b"seed\0", seed);
+    }
+
+    /// Sets the key to derive the key from.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_key(&mut self, key: &[u8]) {
+        self.set(b"key\0", Param::Octets(key.to_vec()));
+    }
+
+    /// Sets the salt.
+    ///
+    /// For KBKDF, this is the label.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_salt(&mut self, salt: &[u8]) {
+        self.set(b"salt\0", Param::Octets(salt.to_vec()));
+    }
+
+    /// Appends to the context and application specific information.
+    ///
+    /// For KBKDF, this is the context.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn add_info(&mut self, info: &[u8]) {
+        self.append(b"info\0", info);
+    }
+
+    /// Sets the name of the MAC used by the function, such as `"HMAC"`.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_mac(&mut self, mac: &str) {
+        self.set(b"mac\0", Param::Utf8(CString::new(mac).unwrap()));
+    }
+
+    /// Sets the cipher used by a CMAC.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_cipher(&mut self, cipher: Cipher) {
+        let name = unsafe { CStr::from_ptr(ffi::EVP_CIPHER_get0_name(cipher.as_ptr())) };
+        self.set(b"cipher\0", Param::Utf8(name.to_owned()));
+    }
+
+    /// Selects the mode of operation of KBKDF.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_kbkdf_mode(&mut self, mode: KbkdfMode) {
+        let mode = CStr::from_bytes_with_nul(mode.0).unwrap();
+        self.set(b"mode\0", Param::Utf8(mode.to_owned()));
+    }
+
+    /// Sets whether KBKDF places a zero byte between the label and the context.
+    ///
+    /// The separator is included by default.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_kbkdf_separator(&mut self, separator: bool) {
+        self.set(b"use-separator\0", Param::Int(separator as c_int));
+    }
+
+    /// Sets whether KBKDF appends the length of the derived key in bits to the context.
+    ///
+    /// The length is included by default. Disabling both it and the separator allows arbitrary
+    /// fixed input data to be passed as the label.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_kbkdf_length(&mut self, length: bool) {
+        self.set(b"use-l\0", Param::Int(length as c_int));
+    }
+
+    /// Derives a key into `out`, filling it completely.
+    ///
+    /// This corresponds to [`EVP_KDF_derive`], or [`EVP_PKEY_derive`] before OpenSSL 3.0.0.
+    ///
+    /// [`EVP_KDF_derive`]: https://www.openssl.org/docs/man3.0/man3/EVP_KDF_derive.html
+    /// [`EVP_PKEY_derive`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_derive.html
+    pub fn derive(&self, out: &mut [u8]) -> Result<(), ErrorStack> {
+        self.derive_inner(out)
+    }
+
+    #[cfg(not(ossl300))]
+    fn derive_inner(&self, out: &mut [u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let ctx = PkeyCtx(cvt_p(ffi::EVP_PKEY_CTX_new_id(self.id, ptr::null_mut()))?);
+            cvt(ffi::EVP_PKEY_derive_init(ctx.0))?;
+            if let Some(Param::Digest(md)) = self.get(b"digest\0") {
+                ctx.ctrl(ffi::EVP_PKEY_CTRL_TLS_MD, 0, md.as_ptr() as *mut _)?;
+            }
+            // setting the secret clears the seed, so it must come first
+            if let Some(Param::Octets(secret)) = self.get(b"secret\0") {
+                ctx.ctrl_bytes(ffi::EVP_PKEY_CTRL_TLS_SECRET, secret)?;
+            }
+            if let Some(Param::Octets(seed)) = self.get(b"seed\0") {
+                ctx.ctrl_bytes(ffi::EVP_PKEY_CTRL_TLS_SEED, seed)?;
+            }
+
+            let mut len = out.len();
+            cvt(ffi::EVP_PKEY_derive(ctx.0, out.as_mut_ptr(), &mut len))?;
+            Ok(())
+        }
+    }
+
+    #[cfg(ossl300)]
+    fn derive_inner(&self, out: &mut [u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let mut params = self
+                .params
+                .iter()
+                .map(|(key, param)| match param {
+                    Param::Digest(md) => ffi::OSSL_PARAM_construct_utf8_string(
+                        key.as_ptr() as *const _,
+                        ffi::EVP_MD_get0_name(md.as_ptr()) as *mut _,
+                        0,
+                    ),
+                    Param::Octets(buf) => octet_string(key, buf),
+                    Param::Utf8(s) => ffi::OSSL_PARAM_construct_utf8_string(
+                        key.as_ptr() as *const _,
+                        s.as_ptr() as *mut _,
+                        0,
+                    ),
+                    Param::Int(value) => ffi::OSSL_PARAM_construct_int(
+                        key.as_ptr() as *const _,
+                        value as *const c_int as *mut _,
+                    ),
+                })
+                .collect::<Vec<_>>();
+            params.push(ffi::OSSL_PARAM_construct_end());
+
+            let ctx = KdfCtx::new(&self.kdf)?;
+            cvt(ffi::EVP_KDF_derive(
+                ctx.0,
+                out.as_mut_ptr(),
+                out.len(),
+                params.as_ptr(),
+            ))?;
+            Ok(())
+        }
+    }
+
+    #[cfg(not(ossl300))]
+    fn get(&self, key: &[u8]) -> Option<&Param> {
+        self.params.iter().find(|p| p.0 == key).map(|p| &p.1)
+    }
+
+    fn set(&mut self, key: &'static [u8], param: Param) {
+        match self.params.iter_mut().find(|p| p.0 == key) {
+            Some(p) => {
+                if let Param::Octets(buf) = &mut p.1 {
+                    cleanse(buf);
+                }
+                p.1 = param;
+            }
+            None => self.params.push((key, param)),
+        }
+    }
+
+    fn append(&mut self, key: &'static [u8], data: &[u8]) {
+        match self.params.iter_mut().find(|p| p.0 == key) {
+            Some((_, Param::Octets(buf))) => buf.extend_from_slice(data),
+            _ => self.set(key, Param::Octets(data.to_vec())),
+        }
+    }
+}
+
+fn cleanse(buf: &mut Vec<u8>) {
+    unsafe { ffi::OPENSSL_cleanse(buf.as_mut_ptr() as *mut c_void, buf.len()) }
+}
+
+#[cfg(not(ossl300))]
+struct PkeyCtx(*mut ffi::EVP_PKEY_CTX);
+
+#[cfg(not(ossl300))]
+impl Drop for PkeyCtx {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_PKEY_CTX_free(self.0) }
+    }
+}
+
+#[cfg(not(ossl300))]
+impl PkeyCtx {
+    unsafe fn ctrl(
+        &self,
+        cmd: libc::c_int,
+        p1: libc::c_int,
+        p2: *mut c_void,
+    ) -> Result<(), ErrorStack> {
+        cvt(ffi::EVP_PKEY_CTX_ctrl(
+            self.0,
+            -1,
+            ffi::EVP_PKEY_OP_DERIVE,
+            cmd,
+            p1,
+            p2,
+        ))
+        .map(|_| ())
+    }
+
+    unsafe fn ctrl_bytes(&self, cmd: libc::c_int, data: &[u8]) -> Result<(), ErrorStack> {
+        assert!(data.len() <= libc::c_int::max_value() as usize);
+        self.ctrl(cmd, data.len() as libc::c_int, data.as_ptr() as *mut _)
+    }
+}
+
+#[cfg(ossl300)]
+struct KdfAlg(*mut ffi::EVP_KDF);
+
+#[cfg(ossl300)]
+impl Drop for KdfAlg {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_KDF_free(self.0) }
+    }
+}
+
+#[cfg(ossl300)]
+impl KdfAlg {
+    unsafe fn fetch(
+        ctx: *mut ffi::OSSL_LIB_CTX,
+        algorithm: *const libc::c_char,
+        properties: *const libc::c_char,
+    ) -> Result<KdfAlg, ErrorStack> {
+        cvt_p(ffi::EVP_KDF_fetch(ctx, algorithm, properties)).map(KdfAlg)
+    }
+}
+
+#[cfg(ossl300)]
+struct KdfCtx(*mut ffi::EVP_KDF_CTX);
+
+#[cfg(ossl300)]
+impl Drop for KdfCtx {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_KDF_CTX_free(self.0) }
+    }
+}
+
+#[cfg(ossl300)]
+impl KdfCtx {
+    unsafe fn new(kdf: &KdfAlg) -> Result<KdfCtx, ErrorStack> {
+        cvt_p(ffi::EVP_KDF_CTX_new(kdf.0)).map(KdfCtx)
+    }
+}
+
+#[cfg(ossl300)]
+pub(crate) unsafe fn derive_by_name(
+    name: &'static [u8],
+    params: &[ffi::OSSL_PARAM],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let kdf = KdfAlg::fetch(ptr::null_mut(), name.as_ptr() as *const _, ptr::null())?;
+    let ctx = KdfCtx::new(&kdf)?;
+    cvt(ffi::EVP_KDF_derive(
+        ctx.0,
+        out.as_mut_ptr(),
+        out.len(),
+        params.as_ptr(),
+    ))
+    .map(|_| ())
+}
+
+#[cfg(ossl300)]
+pub(crate) unsafe fn octet_string(key: &'static [u8], data: &[u8]) -> ffi::OSSL_PARAM {
+    ffi::OSSL_PARAM_construct_octet_string(
+        key.as_ptr() as *const _,
+        data.as_ptr() as *mut _,
+        data.len(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use hex::{self, FromHex};
+
+    use super::*;
+
+    // RFC 5869 test case 1
+    const IKM: &str = "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b";
+    const SALT: &str = "000102030405060708090a0b0c";
+    const INFO: &str = "f0f1f2f3f4f5f6f7f8f9";
+    const PRK: &str = "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5";
+    const OKM: &str = "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+                       34007208d5b887185865";
+
+    #[test]
+    fn hkdf_sha256() {
+        let mut out = [0; 42];
+        hkdf(
+            MessageDigest::sha256(),
+            &Vec::from_hex(IKM).unwrap(),
+            &Vec::from_hex(SALT).unwrap(),
+            &Vec::from_hex(INFO).unwrap(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&out[..]), OKM);
+    }
+
+    #[test]
+    fn hkdf_info_chunks() {
+        let info = Vec::from_hex(INFO).unwrap();
+
+        let mut hkdf = Hkdf::new(MessageDigest::sha256());
+        hkdf.set_key(&Vec::from_hex(IKM).unwrap());
+        hkdf.set_salt(&Vec::from_hex(SALT).unwrap());
+        hkdf.add_info(&info[..3]);
+        hkdf.add_info(&info[3..]);
+
+        let mut out = [0; 42];
+        hkdf.derive(&mut out).unwrap();
+        assert_eq!(hex::encode(&out[..]), OKM);
+    }
+
+    #[test]
+    fn hkdf_missing_key() {
+        let mut out = [0; 32];
+        assert!(Hkdf::new(MessageDigest::sha256()).derive(&mut out).is_err());
+    }
+
+    #[test]
+    #[cfg(ossl111)]
+    fn hkdf_modes() {
+        let mut extract = Hkdf::new(MessageDigest::sha256());
+        extract.set_mode(HkdfMode::EXTRACT_ONLY);
+        extract.set_key(&Vec::from_hex(IKM).unwrap());
+        extract.set_salt(&Vec::from_hex(SALT).unwrap());
+        let mut prk = [0; 32];
+        extract.derive(&mut prk).unwrap();
+        assert_eq!(hex::encode(&prk[..]), PRK);
+
+        let mut expand = Hkdf::new(MessageDigest::sha256());
+        expand.set_mode(HkdfMode::EXPAND_ONLY);
+        expand.set_key(&prk);
+        expand.add_info(&Vec::from_hex(INFO).unwrap());
+        let mut okm = [0; 42];
+        expand.derive(&mut okm).unwrap();
+        assert_eq!(hex::encode(&okm[..]), OKM);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(ossl111)]
+    fn hkdf_extract_wrong_length() {
+        let mut hkdf = Hkdf::new(MessageDigest::sha256());
+        hkdf.set_mode(HkdfMode::EXTRACT_ONLY);
+        hkdf.set_key(b"key");
+        let mut out = [0; 16];
+        let _ = hkdf.derive(&mut out);
+    }
+
+    #[test]
+    fn tls1_prf() {
+        let mut kdf = Kdf::tls1_prf().unwrap();
+        // the seed may be supplied before the secret
+        kdf.add_seed(b"master secret");
+        kdf.add_seed(
+            &Vec::from_hex("36c129d01a3200894b9179faac589d9835d58775f9b5ea3587cb8fd0364cae8c")
+                .unwrap(),
+        );
+        kdf.add_seed(
+            &Vec::from_hex("f6c9575ed7ddd73e1f7d16eca115415812a43c2b747daaaae043abfb50053fce")
+                .unwrap(),
+        );
+        kdf.set_secret(
+            &Vec::from_hex(
+                "f8938ecc9edebc5030c0c6a441e213cd24e6f770a50dda07876f8d55da062bca\
+                 db386b411fd4fe4313a604fce6c17fbc",
+            )
+            .unwrap(),
+        );
+        kdf.set_digest(MessageDigest::sha256());
+
+        let mut out = [0; 48];
+        kdf.derive(&mut out).unwrap();
+        assert_eq!(
+            hex::encode(&out[..]),
+            "202c88c00f84a17a20027079604787461176455539e705be730890602c289a50\
+             01e34eeb3a043e5d52a65e66125188bf"
+        );
+    }
+
+    #[test]
+    fn tls1_prf_missing_digest() {
+        let mut kdf = Kdf::tls1_prf().unwrap();
+        kdf.set_secret(b"secret");
+        kdf.add_seed(b"seed");
+        let mut out = [0; 48];
+        assert!(kdf.derive(&mut out).is_err());
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn x963() {
+        // NIST CAVP ANSI X9.63 KDF, SHA-256 without and with shared info
+        let mut kdf = Kdf::x963().unwrap();
+        kdf.set_digest(MessageDigest::sha256());
+        kdf.set_key(&Vec::from_hex("96c05619d56c328ab95fe84b18264b08725b85e33fd34f08").unwrap());
+
+        let mut out = [0; 16];
+        kdf.derive(&mut out).unwrap();
+        assert_eq!(hex::encode(&out[..]), "443024c3dae66b95e6f5670601558f71");
+
+        kdf.set_key(&Vec::from_hex("22518b10e70f2a3f243810ae3254139efbee04aa57c7af7d").unwrap());
+        kdf.add_info(&Vec::from_hex("75eef81aa3041e33b80971203d2c0c52").unwrap());
+
+        let mut out = [0; 128];
+        kdf.derive(&mut out).unwrap();
+        assert_eq!(
+            hex::encode(&out[..]),
+            "c498af77161cc59f2962b9a713e2b215152d139766ce34a776df11866a69bf2e\
+             52a13d9c7c6fc878c50c5ea0bc7b00e0da2447cfd874f6cf92f30d0097111485\
+             500c90c3af8b487872d04685d14c8d1dc8d7fa08beb0ce0ababc11f0bd496269\
+             142d43525a78e5bc79a17f59676a5706dc54d54d4d1f0bd7e386128ec26afc21"
+        );
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn sskdf() {
+        // NIST SP 800-56C one-step KDF with SHA-256
+        let info = Vec::from_hex(
+            "a1b2c3d4e53728157e634612c12d6d5223e204aeea4341565369647bd184bcd2\
+             46f72971f292badaa2fe4124612cba",
+        )
+        .unwrap();
+
+        let mut kdf = Kdf::sskdf().unwrap();
+        kdf.set_digest(MessageDigest::sha256());
+        kdf.set_key(
+            &Vec::from_hex("52169af5c485dcc2321eb8d26d5efa21fb9b93c98e38412ee2484cf14f0d0d23")
+                .unwrap(),
+        );
+        kdf.add_info(&info[..20]);
+        kdf.add_info(&info[20..]);
+
+        let mut out = [0; 16];
+        kdf.derive(&mut out).unwrap();
+        assert_eq!(hex::encode(&out[..]), "1c3bc9e7c4547c5191c0d478cccaed55");
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn kbkdf_counter() {
+        // NIST CAVP SP 800-108 counter mode, HMAC-SHA256 with a 32 bit counter before the
+        // fixed input data
+        let mut kdf = Kdf::kbkdf().unwrap();
+        kdf.set_kbkdf_mode(KbkdfMode::COUNTER);
+        kdf.set_mac("HMAC");
+        kdf.set_digest(MessageDigest::sha256());
+        kdf.set_key(
+            &Vec::from_hex("dd1d91b7d90b2bd3138533ce92b272fbf8a369316aefe242e659cc0ae238afe0")
+                .unwrap(),
+        );
+        kdf.set_kbkdf_separator(false);
+        kdf.set_kbkdf_length(false);
+        kdf.set_salt(
+            &Vec::from_hex(
+                "01322b96b30acd197979444e468e1c5c6859bf1b1cf951b7e725303e237e46b8\
+                 64a145fab25e517b08f8683d0315bb2911d80a0e8aba17f3b413faac",
+            )
+            .unwrap(),
+        );
+
+        let mut out = [0; 16];
+        kdf.derive(&mut out).unwrap();
+        assert_eq!(hex::encode(&out[..]), "10621342bfb0fd40046c0e29f2cfdbf0");
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetch_missing() {
+        assert!(Kdf::fetch(None, "NOT-A-KDF", None).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! Bindings to OpenSSL
 //!
 //! This crate provides a safe interface to the popular OpenSSL cryptography library. OpenSSL versions 1.0.1 through
-//! 1.1.1 and LibreSSL versions 2.5 through 2.8 are supported.
+//! 1.1.1, OpenSSL 3.x, and LibreSSL versions 2.5 through 2.8 are supported.
 //!
 //! # Building
 //!
@@ -130,6 +130,7 @@ mod macros;
 mod bio;
 #[macro_use]
 mod util;
+pub mod aead;
 pub mod aes;
 pub mod asn1;
 pub mod base64;
@@ -149,6 +150,14 @@ pub mod ex_data;
 #[cfg(not(libressl))]
 pub mod fips;
 pub mod hash;
+#[cfg(ossl110)]
+pub mod init;
+#[cfg(ossl110)]
+pub mod kdf;
+#[cfg(ossl300)]
+pub mod lib_ctx;
+#[cfg(ossl111)]
+pub mod mac;
 pub mod memcmp;
 pub mod nid;
 #[cfg(not(osslconf = "OPENSSL_NO_OCSP"))]
@@ -157,6 +166,8 @@ pub mod pkcs12;
 pub mod pkcs5;
 pub mod pkcs7;
 pub mod pkey;
+#[cfg(ossl300)]
+pub mod provider;
 pub mod rand;
 pub mod rsa;
 pub mod sha;
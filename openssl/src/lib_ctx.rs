@@ -0,0 +1,120 @@
+//! OpenSSL library contexts.
+//!
+//! A library context holds the providers and configuration used to look up algorithm
+//! implementations. Functions which accept an `Option<&LibCtxRef>` use the process-wide default
+//! context when passed `None`.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use std::ffi::CString;
+use std::path::Path;
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::{cvt, cvt_p};
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::OSSL_LIB_CTX;
+    fn drop = ffi::OSSL_LIB_CTX_free;
+
+    /// A library context.
+    pub struct LibCtx;
+    /// Reference to [`LibCtx`].
+    ///
+    /// [`LibCtx`]: struct.LibCtx.html
+    pub struct LibCtxRef;
+}
+
+impl LibCtx {
+    /// Creates a new library context with no providers loaded.
+    ///
+    /// This corresponds to [`OSSL_LIB_CTX_new`].
+    ///
+    /// [`OSSL_LIB_CTX_new`]: https://www.openssl.org/docs/man3.0/man3/OSSL_LIB_CTX_new.html
+    pub fn new() -> Result<LibCtx, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::OSSL_LIB_CTX_new()).map(LibCtx)
+        }
+    }
+}
+
+impl LibCtxRef {
+    /// Loads a configuration file into the context, loading any providers it configures.
+    ///
+    /// This corresponds to [`OSSL_LIB_CTX_load_config`].
+    ///
+    /// [`OSSL_LIB_CTX_load_config`]: https://www.openssl.org/docs/man3.0/man3/OSSL_LIB_CTX_load_config.html
+    pub fn load_config<P: AsRef<Path>>(&self, path: P) -> Result<(), ErrorStack> {
+        let path = path_cstring(ffi::ERR_LIB_CONF, path.as_ref())?;
+        unsafe { cvt(ffi::OSSL_LIB_CTX_load_config(self.as_ptr(), path.as_ptr())).map(|_| ()) }
+    }
+}
+
+/// Converts a path to a C string, failing if it is not valid UTF-8 or contains a NUL byte.
+pub(crate) fn path_cstring(lib: c_int, path: &Path) -> Result<CString, ErrorStack> {
+    path.to_str()
+        .and_then(|path| CString::new(path).ok())
+        .ok_or_else(|| {
+            ErrorStack::internal(
+                lib,
+                ffi::ERR_R_PASSED_INVALID_ARGUMENT,
+                Some(format!("path={}", path.display())),
+            )
+        })
+}
+
+/// Sets the property query used when fetching algorithms without explicit properties.
+///
+/// For example, `"fips=yes"` restricts algorithms to those provided by the FIPS provider.
+///
+/// This corresponds to [`EVP_set_default_properties`].
+///
+/// [`EVP_set_default_properties`]: https://www.openssl.org/docs/man3.0/man3/EVP_set_default_properties.html
+pub fn set_default_properties(ctx: Option<&LibCtxRef>, properties: &str) -> Result<(), ErrorStack> {
+    ffi::init();
+
+    let properties = CString::new(properties).unwrap();
+    unsafe {
+        cvt(ffi::EVP_set_default_properties(
+            ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+            properties.as_ptr(),
+        ))
+        .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::provider::Provider;
+
+    #[test]
+    fn isolated_context() {
+        let ctx = LibCtx::new().unwrap();
+
+        // explicitly loading a provider prevents the default one from being loaded as a fallback
+        let _base = Provider::load(Some(&ctx), "base").unwrap();
+        assert!(Provider::available(Some(&ctx), "base"));
+        assert!(!Provider::available(Some(&ctx), "default"));
+
+        let _default = Provider::load(Some(&ctx), "default").unwrap();
+        assert!(Provider::available(Some(&ctx), "default"));
+        set_default_properties(Some(&ctx), "provider=default").unwrap();
+    }
+
+    #[test]
+    fn load_config_invalid_path() {
+        let ctx = LibCtx::new().unwrap();
+        assert!(ctx.load_config("openssl\0.cnf").is_err());
+
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+
+            assert!(ctx.load_config(OsStr::from_bytes(b"\xff.cnf")).is_err());
+        }
+    }
+}
@@ -0,0 +1,786 @@
+//! Message authentication codes.
+//!
+//! A [`Mac`] computes a MAC directly from a key, without going through a [`PKey`] and
+//! [`Signer`]. HMAC, CMAC, SipHash and KMAC are reset after each tag is produced, so a single
+//! instance can authenticate any number of messages under the same key. Poly1305 keys and GMAC
+//! IVs must only be used once, so those MACs return an error after producing a tag until they are
+//! given a new key with [`set_key`], or a new IV with [`set_iv`].
+//!
+//! Requires OpenSSL 1.1.1 or newer.
+//!
+//! [`Mac`]: struct.Mac.html
+//! [`set_key`]: struct.Mac.html#method.set_key
+//! [`set_iv`]: struct.Mac.html#method.set_iv
+//! [`PKey`]: ../pkey/struct.PKey.html
+//! [`Signer`]: ../sign/struct.Signer.html
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::mac::Mac;
+//!
+//! let mut mac = Mac::hmac(MessageDigest::sha256(), b"key").unwrap();
+//! mac.update(b"hello ").unwrap();
+//! mac.update(b"world").unwrap();
+//! let tag = mac.finalize_to_vec().unwrap();
+//!
+//! mac.update(b"hello world").unwrap();
+//! assert!(mac.verify(&tag).unwrap());
+//! ```
+#[cfg(not(ossl300))]
+use foreign_types::ForeignType;
+use std::io::{self, Write};
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::memcmp;
+#[cfg(not(ossl300))]
+use crate::pkey::{PKey, Private};
+use crate::symm::Cipher;
+use crate::{cvt, cvt_p};
+
+#[derive(PartialEq, Copy, Clone)]
+enum State {
+    Reset,
+    Updated,
+    Finalized,
+}
+
+use self::State::*;
+
+/// A message authentication code computation.
+///
+/// On OpenSSL 3.0.0 and newer this uses the `EVP_MAC` API; earlier versions use the `HMAC` and
+/// `CMAC` APIs, or a MAC key in an `EVP_PKEY`.
+pub struct Mac {
+    #[cfg(ossl300)]
+    ctx: *mut ffi::EVP_MAC_CTX,
+    #[cfg(not(ossl300))]
+    ctx: Ctx,
+    state: State,
+    // Poly1305 and GMAC must not produce a second tag with the same key or IV
+    one_time: bool,
+}
+
+// HMAC and CMAC contexts retain their key schedule and are restarted with a null key. Poly1305
+// and SipHash use their key directly, so the `EVP_PKEY_CTX` is simply rekeyed in place.
+#[cfg(not(ossl300))]
+enum Ctx {
+    Hmac(*mut ffi::HMAC_CTX, MessageDigest),
+    Cmac(*mut ffi::CMAC_CTX, Cipher),
+    Pkey(*mut ffi::EVP_MD_CTX, *mut ffi::EVP_PKEY_CTX, PKey<Private>),
+}
+
+unsafe impl Sync for Mac {}
+unsafe impl Send for Mac {}
+
+impl Drop for Mac {
+    fn drop(&mut self) {
+        unsafe {
+            #[cfg(ossl300)]
+            ffi::EVP_MAC_CTX_free(self.ctx);
+            #[cfg(not(ossl300))]
+            match self.ctx {
+                Ctx::Hmac(ctx, _) => ffi::HMAC_CTX_free(ctx),
+                Ctx::Cmac(ctx, _) => ffi::CMAC_CTX_free(ctx),
+                Ctx::Pkey(ctx, _, _) => ffi::EVP_MD_CTX_free(ctx),
+            }
+        }
+    }
+}
+
+impl Mac {
+    /// Creates a new HMAC using the specified digest.
+    pub fn hmac(md: MessageDigest, key: &[u8]) -> Result<Mac, ErrorStack> {
+        #[cfg(ossl300)]
+        unsafe {
+            let params = [
+                utf8_string(b"digest\0", ffi::EVP_MD_get0_name(md.as_ptr())),
+                ffi::OSSL_PARAM_construct_end(),
+            ];
+            Mac::fetch(b"HMAC\0", key, &params)
+        }
+        #[cfg(not(ossl300))]
+        unsafe {
+            ffi::init();
+            let mac = Mac {
+                ctx: Ctx::Hmac(cvt_p(ffi::HMAC_CTX_new())?, md),
+                state: Reset,
+                one_time: false,
+            };
+            if let Ctx::Hmac(ctx, _) = mac.ctx {
+                cvt(ffi::HMAC_Init_ex(
+                    ctx,
+                    key.as_ptr() as *const _,
+                    key.len() as libc::c_int,
+                    md.as_ptr(),
+                    ptr::null_mut(),
+                ))?;
+            }
+            Ok(mac)
+        }
+    }
+
+    /// Creates a new CMAC using the specified block cipher in CBC mode, such as
+    /// [`Cipher::aes_128_cbc`].
+    ///
+    /// [`Cipher::aes_128_cbc`]: ../symm/struct.Cipher.html#method.aes_128_cbc
+    pub fn cmac(cipher: Cipher, key: &[u8]) -> Result<Mac, ErrorStack> {
+        #[cfg(ossl300)]
+        unsafe {
+            let params = [
+                utf8_string(b"cipher\0", ffi::EVP_CIPHER_get0_name(cipher.as_ptr())),
+                ffi::OSSL_PARAM_construct_end(),
+            ];
+            Mac::fetch(b"CMAC\0", key, &params)
+        }
+        #[cfg(not(ossl300))]
+        unsafe {
+            ffi::init();
+            let mac = Mac {
+                ctx: Ctx::Cmac(cvt_p(ffi::CMAC_CTX_new())?, cipher),
+                state: Reset,
+                one_time: false,
+            };
+            if let Ctx::Cmac(ctx, _) = mac.ctx {
+                cvt(ffi::CMAC_Init(
+                    ctx,
+                    key.as_ptr() as *const _,
+                    key.len(),
+                    cipher.as_ptr(),
+                    ptr::null_mut(),
+                ))?;
+            }
+            Ok(mac)
+        }
+    }
+
+    /// Creates a new Poly1305 MAC.
+    ///
+    /// The key must be 32 bytes long, and must only be used to authenticate a single message.
+    /// Once a tag has been produced, [`set_key`] must be called before the MAC can be used again.
+    ///
+    /// [`set_key`]: #method.set_key
+    pub fn poly1305(key: &[u8]) -> Result<Mac, ErrorStack> {
+        #[cfg(ossl300)]
+        let mut mac =
+            unsafe { Mac::fetch(b"POLY1305\0", key, &[ffi::OSSL_PARAM_construct_end()])? };
+        #[cfg(not(ossl300))]
+        let mut mac = Mac::from_pkey(raw_key(ffi::EVP_PKEY_POLY1305, key)?)?;
+        mac.one_time = true;
+        Ok(mac)
+    }
+
+    /// Creates a new SipHash-2-4 MAC.
+    ///
+    /// The key must be 16 bytes long. The tag is 16 bytes long unless changed with
+    /// [`set_size`].
+    ///
+    /// [`set_size`]: #method.set_size
+    pub fn siphash(key: &[u8]) -> Result<Mac, ErrorStack> {
+        #[cfg(ossl300)]
+        unsafe {
+            Mac::fetch(b"SIPHASH\0", key, &[ffi::OSSL_PARAM_construct_end()])
+        }
+        #[cfg(not(ossl300))]
+        {
+            Mac::from_pkey(raw_key(ffi::EVP_PKEY_SIPHASH, key)?)
+        }
+    }
+
+    /// Creates a new GMAC using the specified GCM cipher, such as [`Cipher::aes_128_gcm`].
+    ///
+    /// Each IV must only be used once with a given key. Once a tag has been produced, [`set_iv`]
+    /// or [`set_key`] must be called before the MAC can be used again.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`Cipher::aes_128_gcm`]: ../symm/struct.Cipher.html#method.aes_128_gcm
+    /// [`set_iv`]: #method.set_iv
+    /// [`set_key`]: #method.set_key
+    #[cfg(ossl300)]
+    pub fn gmac(cipher: Cipher, key: &[u8], iv: &[u8]) -> Result<Mac, ErrorStack> {
+        let mut mac = unsafe {
+            let params = [
+                utf8_string(b"cipher\0", ffi::EVP_CIPHER_get0_name(cipher.as_ptr())),
+                octet_string(b"iv\0", iv),
+                ffi::OSSL_PARAM_construct_end(),
+            ];
+            Mac::fetch(b"GMAC\0", key, &params)?
+        };
+        mac.one_time = true;
+        Ok(mac)
+    }
+
+    /// Creates a new KMAC128 with the customization string `custom`.
+    ///
+    /// The tag is 32 bytes long unless changed with [`set_size`].
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`set_size`]: #method.set_size
+    #[cfg(ossl300)]
+    pub fn kmac128(key: &[u8], custom: &[u8]) -> Result<Mac, ErrorStack> {
+        unsafe {
+            let params = [
+                octet_string(b"custom\0", custom),
+                ffi::OSSL_PARAM_construct_end(),
+            ];
+            Mac::fetch(b"KMAC-128\0", key, &params)
+        }
+    }
+
+    /// Creates a new KMAC256 with the customization string `custom`.
+    ///
+    /// The tag is 64 bytes long unless changed with [`set_size`].
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`set_size`]: #method.set_size
+    #[cfg(ossl300)]
+    pub fn kmac256(key: &[u8], custom: &[u8]) -> Result<Mac, ErrorStack> {
+        unsafe {
+            let params = [
+                octet_string(b"custom\0", custom),
+                ffi::OSSL_PARAM_construct_end(),
+            ];
+            Mac::fetch(b"KMAC-256\0", key, &params)
+        }
+    }
+
+    #[cfg(ossl300)]
+    unsafe fn fetch(
+        name: &[u8],
+        key: &[u8],
+        params: &[ffi::OSSL_PARAM],
+    ) -> Result<Mac, ErrorStack> {
+        ffi::init();
+
+        let mac = cvt_p(ffi::EVP_MAC_fetch(
+            ptr::null_mut(),
+            name.as_ptr() as *const _,
+            ptr::null(),
+        ))?;
+        // the context holds its own reference to the algorithm
+        let ctx = ffi::EVP_MAC_CTX_new(mac);
+        ffi::EVP_MAC_free(mac);
+        let mac = Mac {
+            ctx: cvt_p(ctx)?,
+            state: Reset,
+            one_time: false,
+        };
+        cvt(ffi::EVP_MAC_init(
+            mac.ctx,
+            key.as_ptr(),
+            key.len(),
+            params.as_ptr(),
+        ))?;
+        Ok(mac)
+    }
+
+    #[cfg(not(ossl300))]
+    fn from_pkey(pkey: PKey<Private>) -> Result<Mac, ErrorStack> {
+        unsafe {
+            let mut mac = Mac {
+                ctx: Ctx::Pkey(cvt_p(ffi::EVP_MD_CTX_new())?, ptr::null_mut(), pkey),
+                state: Reset,
+                one_time: false,
+            };
+            mac.init()?;
+            Ok(mac)
+        }
+    }
+
+    fn check_reusable(&self) -> Result<(), ErrorStack> {
+        if self.one_time && self.state == Finalized {
+            return Err(ErrorStack::internal(
+                ffi::ERR_LIB_EVP,
+                ffi::ERR_R_PASSED_INVALID_ARGUMENT,
+                Some("a new key or IV must be set after producing a tag".to_string()),
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(ossl300)]
+    fn init(&mut self) -> Result<(), ErrorStack> {
+        self.check_reusable()?;
+        unsafe {
+            // a null key reuses the key and parameters already set
+            cvt(ffi::EVP_MAC_init(self.ctx, ptr::null(), 0, ptr::null()))?;
+        }
+        self.state = Reset;
+        Ok(())
+    }
+
+    #[cfg(not(ossl300))]
+    fn init(&mut self) -> Result<(), ErrorStack> {
+        self.check_reusable()?;
+        unsafe {
+            match self.ctx {
+                Ctx::Hmac(ctx, _) => {
+                    cvt(ffi::HMAC_Init_ex(
+                        ctx,
+                        ptr::null(),
+                        0,
+                        ptr::null(),
+                        ptr::null_mut(),
+                    ))?;
+                }
+                Ctx::Cmac(ctx, _) => {
+                    cvt(ffi::CMAC_Init(
+                        ctx,
+                        ptr::null(),
+                        0,
+                        ptr::null(),
+                        ptr::null_mut(),
+                    ))?;
+                }
+                Ctx::Pkey(ctx, ref mut pctx, ref pkey) => {
+                    // the context's existing EVP_PKEY_CTX is reused, along with the tag size
+                    cvt(ffi::EVP_DigestSignInit(
+                        ctx,
+                        pctx,
+                        ptr::null(),
+                        ptr::null_mut(),
+                        pkey.as_ptr(),
+                    ))?;
+                }
+            }
+        }
+        self.state = Reset;
+        Ok(())
+    }
+
+    /// Replaces the key, discarding any data already supplied.
+    ///
+    /// The tag size and other parameters are retained.
+    pub fn set_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
+        #[cfg(ossl300)]
+        unsafe {
+            cvt(ffi::EVP_MAC_init(
+                self.ctx,
+                key.as_ptr(),
+                key.len(),
+                ptr::null(),
+            ))?;
+        }
+        #[cfg(not(ossl300))]
+        {
+            // the key is part of the underlying context, so a new MAC is created in its place
+            let mut mac = match self.ctx {
+                Ctx::Hmac(_, md) => Mac::hmac(md, key)?,
+                Ctx::Cmac(_, cipher) => Mac::cmac(cipher, key)?,
+                Ctx::Pkey(_, _, ref pkey) => {
+                    let id = unsafe { ffi::EVP_PKEY_id(pkey.as_ptr()) };
+                    let mut mac = Mac::from_pkey(raw_key(id, key)?)?;
+                    if id == ffi::EVP_PKEY_SIPHASH {
+                        mac.set_size(self.size())?;
+                    }
+                    mac
+                }
+            };
+            mac.one_time = self.one_time;
+            *self = mac;
+        }
+        self.state = Reset;
+        Ok(())
+    }
+
+    /// Replaces the IV of a GMAC, discarding any data already supplied.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn set_iv(&mut self, iv: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let params = [octet_string(b"iv\0", iv), ffi::OSSL_PARAM_construct_end()];
+            // a null key reuses the key and sets the new IV
+            cvt(ffi::EVP_MAC_init(self.ctx, ptr::null(), 0, params.as_ptr()))?;
+        }
+        self.state = Reset;
+        Ok(())
+    }
+
+    /// Sets the length of the tag in bytes.
+    ///
+    /// This is supported by SipHash, which produces 8 or 16 byte tags, and KMAC. Any data already
+    /// supplied is discarded.
+    pub fn set_size(&mut self, size: usize) -> Result<(), ErrorStack> {
+        #[cfg(ossl300)]
+        unsafe {
+            let mut size = size;
+            let params = [
+                ffi::OSSL_PARAM_construct_size_t(b"size\0".as_ptr() as *const _, &mut size),
+                ffi::OSSL_PARAM_construct_end(),
+            ];
+            cvt(ffi::EVP_MAC_CTX_set_params(self.ctx, params.as_ptr()))?;
+            self.init()
+        }
+        #[cfg(not(ossl300))]
+        unsafe {
+            match self.ctx {
+                Ctx::Pkey(_, pctx, _) => {
+                    cvt(ffi::EVP_PKEY_CTX_ctrl(
+                        pctx,
+                        -1,
+                        -1,
+                        ffi::EVP_PKEY_CTRL_SET_DIGEST_SIZE,
+                        size as libc::c_int,
+                        ptr::null_mut(),
+                    ))?;
+                }
+                _ => {
+                    return Err(ErrorStack::internal(
+                        ffi::ERR_LIB_EVP,
+                        ffi::EVP_R_COMMAND_NOT_SUPPORTED,
+                        None,
+                    ))
+                }
+            }
+            self.init()
+        }
+    }
+
+    /// Returns the length of the tag in bytes.
+    pub fn size(&self) -> usize {
+        unsafe {
+            #[cfg(ossl300)]
+            {
+                ffi::EVP_MAC_CTX_get_mac_size(self.ctx)
+            }
+            #[cfg(not(ossl300))]
+            match self.ctx {
+                Ctx::Hmac(_, md) => md.size(),
+                Ctx::Cmac(_, cipher) => cipher.block_size(),
+                Ctx::Pkey(ctx, _, _) => {
+                    let mut len = 0;
+                    // querying the length does not finalize the context
+                    ffi::EVP_DigestSignFinal(ctx, ptr::null_mut(), &mut len);
+                    len
+                }
+            }
+        }
+    }
+
+    /// Feeds data into the MAC.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), ErrorStack> {
+        if self.state == Finalized {
+            self.init()?;
+        }
+        unsafe {
+            #[cfg(ossl300)]
+            cvt(ffi::EVP_MAC_update(self.ctx, data.as_ptr(), data.len()))?;
+            #[cfg(not(ossl300))]
+            match self.ctx {
+                Ctx::Hmac(ctx, _) => {
+                    cvt(ffi::HMAC_Update(ctx, data.as_ptr(), data.len()))?;
+                }
+                Ctx::Cmac(ctx, _) => {
+                    cvt(ffi::CMAC_Update(ctx, data.as_ptr() as *const _, data.len()))?;
+                }
+                Ctx::Pkey(ctx, _, _) => {
+                    cvt(ffi::EVP_DigestUpdate(
+                        ctx,
+                        data.as_ptr() as *const _,
+                        data.len(),
+                    ))?;
+                }
+            }
+        }
+        self.state = Updated;
+        Ok(())
+    }
+
+    /// Writes the tag of the data into `out`, returning the number of bytes written, and resets
+    /// the MAC.
+    ///
+    /// Poly1305 and GMAC cannot be reset, and return an error from further calls until a new key
+    /// or IV is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is shorter than [`size`].
+    ///
+    /// [`size`]: #method.size
+    pub fn finalize(&mut self, out: &mut [u8]) -> Result<usize, ErrorStack> {
+        if self.state == Finalized {
+            self.init()?;
+        }
+        assert!(out.len() >= self.size());
+        unsafe {
+            #[cfg(ossl300)]
+            let len = {
+                let mut len = 0;
+                cvt(ffi::EVP_MAC_final(
+                    self.ctx,
+                    out.as_mut_ptr(),
+                    &mut len,
+                    out.len(),
+                ))?;
+                len
+            };
+            #[cfg(not(ossl300))]
+            let len = match self.ctx {
+                Ctx::Hmac(ctx, _) => {
+                    let mut len = 0;
+                    cvt(ffi::HMAC_Final(ctx, out.as_mut_ptr(), &mut len))?;
+                    len as usize
+                }
+                Ctx::Cmac(ctx, _) => {
+                    let mut len = 0;
+                    cvt(ffi::CMAC_Final(ctx, out.as_mut_ptr(), &mut len))?;
+                    len
+                }
+                Ctx::Pkey(ctx, _, _) => {
+                    let mut len = out.len();
+                    cvt(ffi::EVP_DigestSignFinal(ctx, out.as_mut_ptr(), &mut len))?;
+                    len
+                }
+            };
+            self.state = Finalized;
+            Ok(len)
+        }
+    }
+
+    /// Returns the tag of the data in a new buffer, and resets the MAC.
+    pub fn finalize_to_vec(&mut self) -> Result<Vec<u8>, ErrorStack> {
+        let mut out = vec![0; self.size()];
+        let len = self.finalize(&mut out)?;
+        out.truncate(len);
+        Ok(out)
+    }
+
+    /// Determines if `tag` is the tag of the data, and resets the MAC.
+    ///
+    /// The comparison is performed in constant time.
+    pub fn verify(&mut self, tag: &[u8]) -> Result<bool, ErrorStack> {
+        let actual = self.finalize_to_vec()?;
+        Ok(actual.len() == tag.len() && memcmp::eq(&actual, tag))
+    }
+}
+
+impl Write for Mac {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(ossl300))]
+fn raw_key(id: libc::c_int, key: &[u8]) -> Result<PKey<Private>, ErrorStack> {
+    ffi::init();
+    unsafe {
+        cvt_p(ffi::EVP_PKEY_new_raw_private_key(
+            id,
+            ptr::null_mut(),
+            key.as_ptr(),
+            key.len(),
+        ))
+        .map(|p| PKey::from_ptr(p))
+    }
+}
+
+#[cfg(ossl300)]
+unsafe fn utf8_string(key: &'static [u8], value: *const libc::c_char) -> ffi::OSSL_PARAM {
+    ffi::OSSL_PARAM_construct_utf8_string(key.as_ptr() as *const _, value as *mut _, 0)
+}
+
+#[cfg(ossl300)]
+unsafe fn octet_string(key: &'static [u8], data: &[u8]) -> ffi::OSSL_PARAM {
+    ffi::OSSL_PARAM_construct_octet_string(
+        key.as_ptr() as *const _,
+        data.as_ptr() as *mut _,
+        data.len(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use hex::{self, FromHex};
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn hmac_sha256() {
+        // RFC 4231 test case 2
+        let mut mac = Mac::hmac(MessageDigest::sha256(), b"Jefe").unwrap();
+        assert_eq!(mac.size(), 32);
+        mac.write_all(b"what do ya want ").unwrap();
+        mac.write_all(b"for nothing?").unwrap();
+        let tag = mac.finalize_to_vec().unwrap();
+        assert_eq!(
+            hex::encode(&tag[..]),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        // the mac is reset after finalization
+        mac.update(b"what do ya want for nothing?").unwrap();
+        assert!(mac.verify(&tag).unwrap());
+        mac.update(b"what do ya want for something?").unwrap();
+        assert!(!mac.verify(&tag).unwrap());
+        mac.update(b"what do ya want for nothing?").unwrap();
+        assert!(!mac.verify(&tag[..16]).unwrap());
+
+        let mut mac = Mac::hmac(MessageDigest::sha256(), b"other key").unwrap();
+        mac.update(b"discarded").unwrap();
+        mac.set_key(b"Jefe").unwrap();
+        mac.update(b"what do ya want for nothing?").unwrap();
+        assert!(mac.verify(&tag).unwrap());
+    }
+
+    #[test]
+    fn cmac_aes_128() {
+        // RFC 4493 example 2
+        let key = Vec::from_hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let mut mac = Mac::cmac(Cipher::aes_128_cbc(), &key).unwrap();
+        mac.update(&Vec::from_hex("6bc1bee22e409f96e93d7e117393172a").unwrap())
+            .unwrap();
+        let mut tag = [0; 16];
+        assert_eq!(mac.finalize(&mut tag).unwrap(), 16);
+        assert_eq!(hex::encode(&tag[..]), "070a16b46b4d4144f79bdd9dd04a287c");
+
+        // RFC 4493 example 1
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "bb1d6929e95937287fa37d129b756746"
+        );
+    }
+
+    #[test]
+    fn poly1305() {
+        // RFC 8439 section 2.5.2
+        let key = Vec::from_hex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .unwrap();
+        let mut mac = Mac::poly1305(&key).unwrap();
+        mac.update(b"Cryptographic Forum Research Group").unwrap();
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "a8061dc1305136c6c22b8baf0c0127a9"
+        );
+
+        // the key must not be used for a second message
+        assert!(mac.update(b"Cryptographic Forum Research Group").is_err());
+        assert!(mac.finalize_to_vec().is_err());
+
+        mac.set_key(&key).unwrap();
+        mac.update(b"Cryptographic Forum Research Group").unwrap();
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "a8061dc1305136c6c22b8baf0c0127a9"
+        );
+    }
+
+    #[test]
+    fn siphash() {
+        // SipHash reference implementation, 15 byte message
+        let key = Vec::from_hex("000102030405060708090a0b0c0d0e0f").unwrap();
+        let data = Vec::from_hex("000102030405060708090a0b0c0d0e").unwrap();
+        let mut mac = Mac::siphash(&key).unwrap();
+        assert_eq!(mac.size(), 16);
+        mac.set_size(8).unwrap();
+        assert_eq!(mac.size(), 8);
+        mac.update(&data).unwrap();
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "e545be4961ca29a1"
+        );
+
+        // the size persists across resets
+        mac.update(&data).unwrap();
+        assert_eq!(mac.finalize_to_vec().unwrap().len(), 8);
+
+        // an invalid size leaves the previous one in place
+        assert!(mac.set_size(7).is_err());
+        assert_eq!(mac.size(), 8);
+        mac.update(&data).unwrap();
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "e545be4961ca29a1"
+        );
+
+        // and when the key is replaced
+        mac.set_key(&[0; 16]).unwrap();
+        mac.set_key(&key).unwrap();
+        assert_eq!(mac.size(), 8);
+        mac.update(&data).unwrap();
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "e545be4961ca29a1"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn finalize_short_buffer() {
+        let mut mac = Mac::hmac(MessageDigest::sha256(), b"key").unwrap();
+        let mut tag = [0; 16];
+        let _ = mac.finalize(&mut tag);
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn gmac() {
+        use crate::symm;
+
+        let key = [1; 16];
+        let gcm_tag = |iv: &[u8]| {
+            let mut tag = [0; 16];
+            symm::encrypt_aead(
+                Cipher::aes_128_gcm(),
+                &key,
+                Some(iv),
+                b"additional data",
+                &[],
+                &mut tag,
+            )
+            .unwrap();
+            tag
+        };
+
+        let mut mac = Mac::gmac(Cipher::aes_128_gcm(), &key, &[2; 12]).unwrap();
+        mac.update(b"additional data").unwrap();
+        assert_eq!(mac.finalize_to_vec().unwrap(), gcm_tag(&[2; 12]));
+
+        // the IV must not be used for a second message
+        assert!(mac.update(b"additional data").is_err());
+
+        mac.set_iv(&[3; 12]).unwrap();
+        mac.update(b"additional data").unwrap();
+        assert_eq!(mac.finalize_to_vec().unwrap(), gcm_tag(&[3; 12]));
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn kmac128() {
+        // NIST SP 800-185 KMAC samples 1 and 2
+        let key = Vec::from_hex("404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f")
+            .unwrap();
+        let data = Vec::from_hex("00010203").unwrap();
+
+        let mut mac = Mac::kmac128(&key, b"").unwrap();
+        mac.update(&data).unwrap();
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e"
+        );
+
+        let mut mac = Mac::kmac128(&key, b"My Tagged Application").unwrap();
+        mac.update(&data).unwrap();
+        assert_eq!(
+            hex::encode(mac.finalize_to_vec().unwrap()),
+            "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5"
+        );
+
+        mac.set_size(16).unwrap();
+        mac.update(&data).unwrap();
+        assert_eq!(mac.finalize_to_vec().unwrap().len(), 16);
+    }
+}
@@ -1,9 +1,17 @@
 use libc::c_int;
+#[cfg(ossl110)]
+use std::cmp;
 use std::ptr;
 
+#[cfg(ossl110)]
+use crate::base64;
 use crate::cvt;
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+#[cfg(ossl320)]
+use crate::kdf;
+#[cfg(ossl110)]
+use crate::memcmp;
 use crate::symm::Cipher;
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -80,6 +88,8 @@ pub fn bytes_to_key(
 }
 
 /// Derives a key from a password and salt using the PBKDF2-HMAC algorithm with a digest function.
+///
+/// Any digest may be used, including the SHA-3 family on OpenSSL 1.1.1 and newer.
 pub fn pbkdf2_hmac(
     pass: &[u8],
     salt: &[u8],
@@ -110,7 +120,7 @@ pub fn pbkdf2_hmac(
 /// Derives a key from a password and salt using the scrypt algorithm.
 ///
 /// Requires OpenSSL 1.1.0 or newer.
-#[cfg(any(ossl110))]
+#[cfg(ossl110)]
 pub fn scrypt(
     pass: &[u8],
     salt: &[u8],
@@ -138,13 +148,327 @@ pub fn scrypt(
     }
 }
 
+/// A variant of the Argon2 password hashing function.
+///
+/// Requires OpenSSL 3.2.0 or newer.
+#[cfg(ossl320)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Argon2Variant {
+    name: &'static [u8],
+    id: &'static str,
+}
+
+#[cfg(ossl320)]
+impl Argon2Variant {
+    /// Argon2d, which uses data-dependent memory access.
+    pub const ARGON2D: Argon2Variant = Argon2Variant {
+        name: b"ARGON2D\0",
+        id: "argon2d",
+    };
+
+    /// Argon2i, which uses data-independent memory access.
+    pub const ARGON2I: Argon2Variant = Argon2Variant {
+        name: b"ARGON2I\0",
+        id: "argon2i",
+    };
+
+    /// Argon2id, a hybrid of Argon2i and Argon2d recommended for password hashing.
+    pub const ARGON2ID: Argon2Variant = Argon2Variant {
+        name: b"ARGON2ID\0",
+        id: "argon2id",
+    };
+}
+
+/// Derives a key from a password and salt using the Argon2 algorithm described in [RFC 9106].
+///
+/// `t_cost` is the number of passes over memory, `m_cost` the amount of memory to use in
+/// kibibytes, and `lanes` the degree of parallelism. The computation itself is single threaded.
+///
+/// Requires OpenSSL 3.2.0 or newer.
+///
+/// [RFC 9106]: https://tools.ietf.org/html/rfc9106
+#[cfg(ossl320)]
+pub fn argon2(
+    variant: Argon2Variant,
+    pass: &[u8],
+    salt: &[u8],
+    t_cost: u32,
+    m_cost: u32,
+    lanes: u32,
+    key: &mut [u8],
+) -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+
+        let mut t_cost = t_cost;
+        let mut m_cost = m_cost;
+        let mut lanes = lanes;
+        let params = [
+            kdf::octet_string(b"pass\0", pass),
+            kdf::octet_string(b"salt\0", salt),
+            ffi::OSSL_PARAM_construct_uint32(b"iter\0".as_ptr() as *const _, &mut t_cost),
+            ffi::OSSL_PARAM_construct_uint32(b"memcost\0".as_ptr() as *const _, &mut m_cost),
+            ffi::OSSL_PARAM_construct_uint32(b"lanes\0".as_ptr() as *const _, &mut lanes),
+            ffi::OSSL_PARAM_construct_end(),
+        ];
+        kdf::derive_by_name(variant.name, &params, key)
+    }
+}
+
+/// Hashes a password with Argon2, returning the result in the [PHC string format].
+///
+/// The hash is `hash_len` bytes long. See [`argon2`] for the meaning of the other parameters.
+///
+/// Requires OpenSSL 3.2.0 or newer.
+///
+/// [PHC string format]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+/// [`argon2`]: fn.argon2.html
+#[cfg(ossl320)]
+pub fn argon2_phc(
+    variant: Argon2Variant,
+    pass: &[u8],
+    salt: &[u8],
+    t_cost: u32,
+    m_cost: u32,
+    lanes: u32,
+    hash_len: usize,
+) -> Result<String, ErrorStack> {
+    let mut hash = vec![0; hash_len];
+    argon2(variant, pass, salt, t_cost, m_cost, lanes, &mut hash)?;
+    Ok(format!(
+        "${}$v=19$m={},t={},p={}${}${}",
+        variant.id,
+        m_cost,
+        t_cost,
+        lanes,
+        phc_encode(salt),
+        phc_encode(&hash)
+    ))
+}
+
+/// Hashes a password with scrypt, returning the result in the [PHC string format].
+///
+/// The CPU/memory cost parameter of scrypt is `2^log_n`. The hash is `hash_len` bytes long.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// # Panics
+///
+/// Panics if `log_n` is 64 or larger.
+///
+/// [PHC string format]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+#[cfg(ossl110)]
+pub fn scrypt_phc(
+    pass: &[u8],
+    salt: &[u8],
+    log_n: u32,
+    r: u32,
+    p: u32,
+    hash_len: usize,
+) -> Result<String, ErrorStack> {
+    assert!(log_n < 64);
+
+    let mut hash = vec![0; hash_len];
+    scrypt_log_n(pass, salt, log_n, r, p, &mut hash)?;
+    Ok(format!(
+        "$scrypt$ln={},r={},p={}${}${}",
+        log_n,
+        r,
+        p,
+        phc_encode(salt),
+        phc_encode(&hash)
+    ))
+}
+
+/// The default limit on the cost of hashes checked by [`verify_phc`], 256 MiB.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// [`verify_phc`]: fn.verify_phc.html
+#[cfg(ossl110)]
+pub const PHC_DEFAULT_MAX_COST: u64 = 256 * 1024 * 1024;
+
+/// Verifies a password against a hash in the [PHC string format].
+///
+/// Hashes produced by [`scrypt_phc`] and [`argon2_phc`], or by other implementations of those
+/// formats, are supported. Returns `false` if the password does not match, the string is
+/// malformed, it uses an algorithm which is not supported by the linked OpenSSL, or OpenSSL
+/// rejects its parameters, such as a zero cost or a salt which is too short.
+///
+/// Hashes whose cost exceeds [`PHC_DEFAULT_MAX_COST`] are rejected without being computed. See
+/// [`verify_phc_with_max_cost`] for details.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// [PHC string format]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+/// [`scrypt_phc`]: fn.scrypt_phc.html
+/// [`argon2_phc`]: fn.argon2_phc.html
+/// [`PHC_DEFAULT_MAX_COST`]: constant.PHC_DEFAULT_MAX_COST.html
+/// [`verify_phc_with_max_cost`]: fn.verify_phc_with_max_cost.html
+#[cfg(ossl110)]
+pub fn verify_phc(pass: &[u8], phc: &str) -> Result<bool, ErrorStack> {
+    verify_phc_with_max_cost(pass, phc, PHC_DEFAULT_MAX_COST)
+}
+
+/// Like [`verify_phc`], but with a caller-supplied limit on the cost of the hash.
+///
+/// The cost is the number of bytes of memory the hash function allocates or processes, which
+/// bounds both its memory use and the time it takes: the larger of `128 * r * (N + p + 2)` and
+/// `128 * r * N * p` for scrypt, and `1024 * m * t` for Argon2. As the parameters are taken from
+/// the string, a hash exceeding `max_cost` is rejected by returning `false` rather than being
+/// computed.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// [`verify_phc`]: fn.verify_phc.html
+#[cfg(ossl110)]
+pub fn verify_phc_with_max_cost(pass: &[u8], phc: &str, max_cost: u64) -> Result<bool, ErrorStack> {
+    let mut fields = phc.split('$');
+    if fields.next() != Some("") {
+        return Ok(false);
+    }
+
+    let (id, params, salt, hash) = match (
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+    ) {
+        (Some("scrypt"), Some(params), Some(salt), Some(hash), None, None) => {
+            ("scrypt", params, salt, hash)
+        }
+        (Some(id), Some("v=19"), Some(params), Some(salt), Some(hash), None) => {
+            (id, params, salt, hash)
+        }
+        _ => return Ok(false),
+    };
+    let (salt, expected) = match (phc_decode(salt), phc_decode(hash)) {
+        (Some(salt), Some(hash)) if !hash.is_empty() => (salt, hash),
+        _ => return Ok(false),
+    };
+
+    let mut actual = vec![0; expected.len()];
+    let r = match id {
+        "scrypt" => match phc_params(params, ["ln", "r", "p"]) {
+            Some([log_n, r, p])
+                if scrypt_cost(log_n, r, p)
+                    .filter(|&c| c <= max_cost)
+                    .is_some() =>
+            {
+                scrypt_log_n(pass, &salt, log_n, r, p, &mut actual)
+            }
+            _ => return Ok(false),
+        },
+        #[cfg(ossl320)]
+        "argon2d" | "argon2i" | "argon2id" => {
+            let variant = match id {
+                "argon2d" => Argon2Variant::ARGON2D,
+                "argon2i" => Argon2Variant::ARGON2I,
+                _ => Argon2Variant::ARGON2ID,
+            };
+            match phc_params(params, ["m", "t", "p"]) {
+                Some([m_cost, t_cost, lanes])
+                    if (u64::from(m_cost) * u64::from(t_cost))
+                        .checked_mul(1024)
+                        .filter(|&c| c <= max_cost)
+                        .is_some() =>
+                {
+                    argon2(variant, pass, &salt, t_cost, m_cost, lanes, &mut actual)
+                }
+                _ => return Ok(false),
+            }
+        }
+        _ => return Ok(false),
+    };
+
+    // the string is untrusted, so parameters which OpenSSL rejects do not match any password
+    match r {
+        Ok(()) => Ok(memcmp::eq(&actual, &expected)),
+        Err(_) => Ok(false),
+    }
+}
+
+// Runs scrypt with a memory limit large enough for the parameters.
+#[cfg(ossl110)]
+fn scrypt_log_n(
+    pass: &[u8],
+    salt: &[u8],
+    log_n: u32,
+    r: u32,
+    p: u32,
+    key: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let n = 1u64 << log_n;
+    let maxmem = scrypt_mem(n, u64::from(r), u64::from(p)).unwrap_or_else(u64::max_value);
+    scrypt(pass, salt, n, u64::from(r), u64::from(p), maxmem, key)
+}
+
+// OpenSSL requires 128 * r * (n + p + 2) bytes of working memory.
+#[cfg(ossl110)]
+fn scrypt_mem(n: u64, r: u64, p: u64) -> Option<u64> {
+    n.checked_add(p + 2)?.checked_mul(r)?.checked_mul(128)
+}
+
+// The larger of the memory used by scrypt and the number of bytes it processes.
+#[cfg(ossl110)]
+fn scrypt_cost(log_n: u32, r: u32, p: u32) -> Option<u64> {
+    if log_n >= 64 {
+        return None;
+    }
+    let (n, r, p) = (1u64 << log_n, u64::from(r), u64::from(p));
+    let work = n.checked_mul(r)?.checked_mul(p)?.checked_mul(128)?;
+    Some(cmp::max(scrypt_mem(n, r, p)?, work))
+}
+
+// Parses comma separated `name=value` pairs, which must appear in the order of `names`.
+#[cfg(ossl110)]
+fn phc_params(params: &str, names: [&str; 3]) -> Option<[u32; 3]> {
+    let mut values = [0; 3];
+    let mut params = params.split(',');
+    for (value, name) in values.iter_mut().zip(&names) {
+        let mut parts = params.next()?.splitn(2, '=');
+        if parts.next() != Some(*name) {
+            return None;
+        }
+        *value = parts.next()?.parse().ok()?;
+    }
+    if params.next().is_some() {
+        return None;
+    }
+    Some(values)
+}
+
+// PHC strings use the standard base64 alphabet without padding.
+#[cfg(ossl110)]
+fn phc_encode(data: &[u8]) -> String {
+    base64::encode_block(data).trim_end_matches('=').to_string()
+}
+
+#[cfg(ossl110)]
+fn phc_decode(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 4 == 1
+        || !s
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+    {
+        return None;
+    }
+
+    let mut padded = s.to_string();
+    for _ in 0..(4 - s.len() % 4) % 4 {
+        padded.push('=');
+    }
+    base64::decode_block(&padded).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::hash::MessageDigest;
     use crate::symm::Cipher;
 
-    // Test vectors from
-    // https://git.lysator.liu.se/nettle/nettle/blob/nettle_3.1.1_release_20150424/testsuite/pbkdf2-test.c
+    // Test vectors from testsuite/pbkdf2-test.c in nettle 3.1.1
     #[test]
     fn pbkdf2_hmac_sha256() {
         let mut buf = [0; 16];
@@ -175,8 +499,7 @@ mod tests {
         );
     }
 
-    // Test vectors from
-    // https://git.lysator.liu.se/nettle/nettle/blob/nettle_3.1.1_release_20150424/testsuite/pbkdf2-test.c
+    // Test vectors from testsuite/pbkdf2-test.c in nettle 3.1.1
     #[test]
     fn pbkdf2_hmac_sha512() {
         let mut buf = [0; 64];
@@ -241,6 +564,24 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(ossl111)]
+    fn pbkdf2_hmac_sha3_256() {
+        let mut buf = [0; 32];
+        super::pbkdf2_hmac(
+            b"password",
+            b"salt",
+            4096,
+            MessageDigest::sha3_256(),
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(&buf[..]),
+            "778b6e237a0f49621549ff70d218d2080756b9fb38d71b5d7ef447fa2254af61"
+        );
+    }
+
     #[test]
     fn bytes_to_key() {
         let salt = [16_u8, 34_u8, 19_u8, 23_u8, 141_u8, 4_u8, 207_u8, 221_u8];
@@ -278,7 +619,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     fn scrypt() {
         let pass = "pleaseletmein";
         let salt = "SodiumChloride";
@@ -299,4 +640,111 @@ mod tests {
         .unwrap();
         assert_eq!(hex::encode(&actual[..]), expected);
     }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn scrypt_phc() {
+        let phc = super::scrypt_phc(b"password", b"saltsaltsaltsalt", 10, 8, 1, 32).unwrap();
+        assert_eq!(
+            phc,
+            "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+             BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4"
+        );
+
+        assert!(super::verify_phc(b"password", &phc).unwrap());
+        assert!(!super::verify_phc(b"passw0rd", &phc).unwrap());
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn verify_phc_malformed() {
+        let hash = "BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4";
+        for &(prefix, hash) in &[
+            ("", ""),
+            ("scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$", hash),
+            ("$scrypt$ln=10,r=8$c2FsdHNhbHRzYWx0c2FsdA$", hash),
+            ("$scrypt$r=8,ln=10,p=1$c2FsdHNhbHRzYWx0c2FsdA$", hash),
+            ("$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA==$", hash),
+            ("$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$", ""),
+            ("$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA", ""),
+            ("$md5$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$", hash),
+            // parameters which OpenSSL rejects
+            ("$scrypt$ln=0,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$", hash),
+            ("$scrypt$ln=10,r=0,p=1$c2FsdHNhbHRzYWx0c2FsdA$", hash),
+            ("$scrypt$ln=10,r=8,p=0$c2FsdHNhbHRzYWx0c2FsdA$", hash),
+        ] {
+            let phc = format!("{}{}", prefix, hash);
+            assert!(!super::verify_phc(b"password", &phc).unwrap(), "{}", phc);
+        }
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn verify_phc_max_cost() {
+        let phc = "$scrypt$ln=10,r=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+                   BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4";
+        // 128 * r * (N + p + 2) bytes of working memory
+        let cost = 128 * 8 * (1024 + 1 + 2);
+        assert!(super::verify_phc_with_max_cost(b"password", phc, cost).unwrap());
+        assert!(!super::verify_phc_with_max_cost(b"password", phc, cost - 1).unwrap());
+
+        for params in &[
+            "ln=40,r=8,p=1",
+            "ln=1,r=1,p=1073741823",
+            "ln=63,r=4294967295,p=4294967295",
+            "ln=64,r=8,p=1",
+        ] {
+            let phc = format!(
+                "$scrypt${}$c2FsdHNhbHRzYWx0c2FsdA$BVMRKqdiVYikKAaPR1wucsKUKvw4TuPLkdEYtoSHas4",
+                params
+            );
+            assert!(!super::verify_phc(b"password", &phc).unwrap(), "{}", phc);
+        }
+    }
+
+    #[test]
+    #[cfg(ossl320)]
+    fn argon2_phc() {
+        use super::Argon2Variant;
+
+        // from the reference implementation
+        assert!(super::verify_phc(
+            b"password",
+            "$argon2i$v=19$m=65536,t=2,p=4$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG"
+        )
+        .unwrap());
+        assert!(!super::verify_phc_with_max_cost(
+            b"password",
+            "$argon2i$v=19$m=65536,t=2,p=4$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+            128 * 1024 * 1024 - 1,
+        )
+        .unwrap());
+        assert!(!super::verify_phc(
+            b"password",
+            "$argon2i$v=19$m=4294967295,t=4294967295,p=4$c29tZXNhbHQ$\
+             RdescudvJCsgt3ub+b+dWRWJTmaaJObG"
+        )
+        .unwrap());
+        // OpenSSL requires at least 8 bytes of salt and one lane
+        for phc in &[
+            "$argon2i$v=19$m=65536,t=2,p=4$c2FsdA$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+            "$argon2i$v=19$m=65536,t=2,p=0$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+        ] {
+            assert!(!super::verify_phc(b"password", phc).unwrap(), "{}", phc);
+        }
+
+        let phc = super::argon2_phc(
+            Argon2Variant::ARGON2ID,
+            b"password",
+            b"somesalt",
+            2,
+            256,
+            2,
+            32,
+        )
+        .unwrap();
+        assert!(phc.starts_with("$argon2id$v=19$m=256,t=2,p=2$c29tZXNhbHQ$"));
+        assert!(super::verify_phc(b"password", &phc).unwrap());
+        assert!(!super::verify_phc(b"passw0rd", &phc).unwrap());
+    }
 }
@@ -53,6 +53,8 @@ use crate::dh::Dh;
 use crate::dsa::Dsa;
 use crate::ec::EcKey;
 use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 use crate::rsa::Rsa;
 #[cfg(ossl110)]
 use crate::symm::Cipher;
@@ -517,6 +519,46 @@ impl PKey<Private> {
         PKey::generate_eddsa(ffi::EVP_PKEY_ED448)
     }
 
+    /// Generates a new private key of the named algorithm with its default parameters.
+    ///
+    /// The implementation is fetched from `ctx`, or the default library context if `None`,
+    /// matching the property query `properties`. For example, `"ED25519"` with
+    /// `Some("provider=fips")` generates an Ed25519 key with the FIPS provider.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_new_from_name`].
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`EVP_PKEY_CTX_new_from_name`]: https://www.openssl.org/docs/man3.0/man3/EVP_PKEY_CTX_new_from_name.html
+    #[cfg(ossl300)]
+    pub fn generate_from_name(
+        ctx: Option<&LibCtxRef>,
+        name: &str,
+        properties: Option<&str>,
+    ) -> Result<PKey<Private>, ErrorStack> {
+        let name = CString::new(name).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        unsafe {
+            let kctx = cvt_p(ffi::EVP_PKEY_CTX_new_from_name(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                name.as_ptr(),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            ))?;
+            let ret = cvt(ffi::EVP_PKEY_keygen_init(kctx));
+            if let Err(e) = ret {
+                ffi::EVP_PKEY_CTX_free(kctx);
+                return Err(e);
+            }
+            let mut key = ptr::null_mut();
+            let ret = cvt(ffi::EVP_PKEY_keygen(kctx, &mut key));
+
+            ffi::EVP_PKEY_CTX_free(kctx);
+
+            ret?;
+            Ok(PKey::from_ptr(key))
+        }
+    }
+
     private_key_from_pem! {
         /// Deserializes a private key from a PEM-encoded key type specific format.
         ///
@@ -792,4 +834,12 @@ mod tests {
         assert_eq!(pkey.id(), Id::EC);
         assert!(pkey.rsa().is_err());
     }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn test_generate_from_name() {
+        let pkey = PKey::generate_from_name(None, "ED25519", Some("provider=default")).unwrap();
+        assert_eq!(pkey.id(), Id::ED25519);
+        assert!(PKey::generate_from_name(None, "ED25519", Some("provider=missing")).is_err());
+    }
 }
@@ -0,0 +1,184 @@
+//! OpenSSL providers.
+//!
+//! Providers supply the implementations of algorithms. The `default` provider is loaded
+//! automatically unless another provider has been loaded explicitly. Older algorithms, such as
+//! those used by legacy PKCS#12 files, live in the `legacy` provider, and FIPS validated
+//! implementations live in the `fips` provider.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use openssl::provider::Provider;
+//!
+//! // keep the default algorithms available alongside the legacy ones
+//! let _default = Provider::load(None, "default").unwrap();
+//! let _legacy = Provider::load(None, "legacy").unwrap();
+//! ```
+use foreign_types::{ForeignTypeRef, Opaque};
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::Path;
+use std::ptr;
+use std::str;
+
+use crate::error::ErrorStack;
+use crate::lib_ctx::{self, LibCtxRef};
+use crate::{cvt, cvt_p};
+
+/// A loaded provider.
+///
+/// The provider is unloaded when this value is dropped, unless it is still in use. It borrows the
+/// library context it was loaded into, as a provider must be unloaded before its context is freed.
+pub struct Provider<'a>(*mut ffi::OSSL_PROVIDER, PhantomData<&'a LibCtxRef>);
+
+unsafe impl<'a> Sync for Provider<'a> {}
+unsafe impl<'a> Send for Provider<'a> {}
+
+impl<'a> Drop for Provider<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::OSSL_PROVIDER_unload(self.0);
+        }
+    }
+}
+
+impl<'a> Deref for Provider<'a> {
+    type Target = ProviderRef;
+
+    fn deref(&self) -> &ProviderRef {
+        unsafe { ProviderRef::from_ptr(self.0) }
+    }
+}
+
+/// Reference to a [`Provider`].
+///
+/// [`Provider`]: struct.Provider.html
+pub struct ProviderRef(Opaque);
+
+impl ForeignTypeRef for ProviderRef {
+    type CType = ffi::OSSL_PROVIDER;
+}
+
+unsafe impl Sync for ProviderRef {}
+unsafe impl Send for ProviderRef {}
+
+fn ctx_ptr(ctx: Option<&LibCtxRef>) -> *mut ffi::OSSL_LIB_CTX {
+    ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr)
+}
+
+impl<'a> Provider<'a> {
+    /// Loads a provider into a library context.
+    ///
+    /// Loading any provider explicitly disables the automatic loading of the `default` provider.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_load`].
+    ///
+    /// [`OSSL_PROVIDER_load`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_load.html
+    pub fn load(ctx: Option<&'a LibCtxRef>, name: &str) -> Result<Provider<'a>, ErrorStack> {
+        ffi::init();
+
+        let name = CString::new(name).unwrap();
+        unsafe {
+            cvt_p(ffi::OSSL_PROVIDER_load(ctx_ptr(ctx), name.as_ptr()))
+                .map(|p| Provider(p, PhantomData))
+        }
+    }
+
+    /// Loads a provider into a library context.
+    ///
+    /// If `retain_fallbacks` is `true`, the `default` provider is still loaded automatically.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_try_load`].
+    ///
+    /// [`OSSL_PROVIDER_try_load`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_try_load.html
+    pub fn try_load(
+        ctx: Option<&'a LibCtxRef>,
+        name: &str,
+        retain_fallbacks: bool,
+    ) -> Result<Provider<'a>, ErrorStack> {
+        ffi::init();
+
+        let name = CString::new(name).unwrap();
+        unsafe {
+            cvt_p(ffi::OSSL_PROVIDER_try_load(
+                ctx_ptr(ctx),
+                name.as_ptr(),
+                retain_fallbacks as _,
+            ))
+            .map(|p| Provider(p, PhantomData))
+        }
+    }
+
+    /// Sets the directory searched for provider modules.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_set_default_search_path`].
+    ///
+    /// [`OSSL_PROVIDER_set_default_search_path`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_set_default_search_path.html
+    pub fn set_default_search_path<P: AsRef<Path>>(
+        ctx: Option<&LibCtxRef>,
+        path: P,
+    ) -> Result<(), ErrorStack> {
+        ffi::init();
+
+        let path = lib_ctx::path_cstring(ffi::ERR_LIB_PROV, path.as_ref())?;
+        unsafe {
+            cvt(ffi::OSSL_PROVIDER_set_default_search_path(
+                ctx_ptr(ctx),
+                path.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Determines if a provider is loaded in a library context.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_available`].
+    ///
+    /// [`OSSL_PROVIDER_available`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_available.html
+    pub fn available(ctx: Option<&LibCtxRef>, name: &str) -> bool {
+        ffi::init();
+
+        let name = CString::new(name).unwrap();
+        unsafe { ffi::OSSL_PROVIDER_available(ctx_ptr(ctx), name.as_ptr()) == 1 }
+    }
+}
+
+impl ProviderRef {
+    /// Returns the name of the provider.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_get0_name`].
+    ///
+    /// [`OSSL_PROVIDER_get0_name`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_get0_name.html
+    pub fn name(&self) -> &str {
+        unsafe {
+            let name = ffi::OSSL_PROVIDER_get0_name(self.as_ptr());
+            str::from_utf8(CStr::from_ptr(name).to_bytes()).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn load_default() {
+        let provider = Provider::load(None, "default").unwrap();
+        assert_eq!(provider.name(), "default");
+        assert!(Provider::available(None, "default"));
+    }
+
+    #[test]
+    fn try_load_missing() {
+        assert!(Provider::try_load(None, "does-not-exist", true).is_err());
+        assert!(!Provider::available(None, "does-not-exist"));
+    }
+
+    #[test]
+    fn set_default_search_path_invalid() {
+        assert!(Provider::set_default_search_path(None, "modules\0").is_err());
+    }
+}
@@ -1,9 +1,11 @@
+#[cfg(ossl110)]
+use bitflags::bitflags;
 use cfg_if::cfg_if;
 use foreign_types::ForeignType;
 use foreign_types::ForeignTypeRef;
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use libc::c_char;
-#[cfg(ossl111)]
+#[cfg(ossl110)]
 use libc::size_t;
 use libc::{c_int, c_uchar, c_uint, c_void};
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
@@ -13,21 +15,25 @@ use std::ptr;
 use std::slice;
 #[cfg(ossl111)]
 use std::str;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::dh::Dh;
 #[cfg(all(ossl101, not(ossl110)))]
 use crate::ec::EcKey;
 use crate::error::ErrorStack;
+#[cfg(ossl111)]
+use crate::hash::MessageDigest;
 use crate::pkey::Params;
 #[cfg(any(ossl102, libressl261))]
 use crate::ssl::AlpnError;
+#[cfg(ossl320)]
+use crate::ssl::CertCompressionAlgorithm;
 use crate::ssl::{
     try_get_session_ctx_index, SniError, Ssl, SslAlert, SslContext, SslContextRef, SslRef,
-    SslSession, SslSessionRef,
+    SslSession, SslSessionRef, SslStats,
 };
 #[cfg(ossl111)]
-use crate::ssl::{ClientHelloResponse, ExtensionContext};
+use crate::ssl::{ClientHelloFingerprint, ClientHelloResponse, ExtensionContext};
 use crate::util::ForeignTypeRefExt;
 #[cfg(ossl111)]
 use crate::x509::X509Ref;
@@ -134,6 +140,268 @@ where
     }
 }
 
+/// The identity handed to OpenSSL by the PSK use session callback.
+///
+/// It is kept in the `Ssl`'s ex data so that it outlives the callback.
+#[cfg(ossl111)]
+struct PskIdentity(Vec<u8>);
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_psk_use_session<F>(
+    ssl: *mut ffi::SSL,
+    md: *const ffi::EVP_MD,
+    id: *mut *const c_uchar,
+    idlen: *mut size_t,
+    sess: *mut *mut ffi::SSL_SESSION,
+) -> c_int
+where
+    F: Fn(&mut SslRef, Option<MessageDigest>) -> Result<Option<(Vec<u8>, SslSession)>, ErrorStack>
+        + 'static
+        + Sync
+        + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: psk use session callback missing") as *const F;
+    let md = if md.is_null() {
+        None
+    } else {
+        Some(MessageDigest::from_ptr(md))
+    };
+
+    match (*callback)(ssl, md) {
+        Ok(Some((identity, session))) => {
+            // the callback runs again after a HelloRetryRequest
+            let idx = Ssl::cached_ex_index::<PskIdentity>();
+            match ssl.ex_data_mut(idx) {
+                Some(old) => *old = PskIdentity(identity),
+                None => ssl.set_ex_data(idx, PskIdentity(identity)),
+            }
+            let identity = &ssl.ex_data(idx).unwrap().0;
+            *id = identity.as_ptr();
+            *idlen = identity.len();
+            *sess = session.as_ptr();
+            mem::forget(session);
+            1
+        }
+        Ok(None) => {
+            *id = ptr::null();
+            *idlen = 0;
+            *sess = ptr::null_mut();
+            1
+        }
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_psk_find_session<F>(
+    ssl: *mut ffi::SSL,
+    identity: *const c_uchar,
+    identity_len: size_t,
+    sess: *mut *mut ffi::SSL_SESSION,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &[u8]) -> Result<Option<SslSession>, ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: psk find session callback missing") as *const F;
+    let identity = if identity_len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(identity, identity_len)
+    };
+
+    match (*callback)(ssl, identity) {
+        Ok(Some(session)) => {
+            *sess = session.as_ptr();
+            mem::forget(session);
+            1
+        }
+        Ok(None) => {
+            *sess = ptr::null_mut();
+            1
+        }
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+/// Returns the connection's statistics, creating them if necessary.
+fn connection_stats(ssl: &SslRef) -> &Mutex<SslStats> {
+    let idx = Ssl::cached_ex_index::<Mutex<SslStats>>();
+    if let Some(stats) = ssl.ex_data(idx) {
+        return stats;
+    }
+
+    unsafe {
+        let stats = Box::into_raw(Box::new(Mutex::new(SslStats::default())));
+        ffi::SSL_set_ex_data(ssl.as_ptr(), idx.as_raw(), stats as *mut c_void);
+        &*stats
+    }
+}
+
+/// The info callback which was configured before connection statistics were enabled.
+pub struct PreviousInfoCallback(pub unsafe extern "C" fn(*const ffi::SSL, c_int, c_int));
+
+pub unsafe extern "C" fn raw_stats_info(ssl: *const ffi::SSL, type_: c_int, val: c_int) {
+    let ssl_ref = SslRef::from_ptr(ssl as *mut ffi::SSL);
+
+    {
+        let mut stats = connection_stats(ssl_ref)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if type_ & ffi::SSL_CB_HANDSHAKE_START != 0 {
+            stats.record_handshake_start();
+        }
+        if type_ & ffi::SSL_CB_HANDSHAKE_DONE != 0 {
+            stats.record_handshake_done();
+        }
+    }
+
+    let previous = ssl_ref
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<PreviousInfoCallback>());
+    if let Some(&PreviousInfoCallback(previous)) = previous {
+        previous(ssl, type_, val);
+    }
+}
+
+#[cfg(ossl110)]
+bitflags! {
+    /// The observers fed by `raw_msg`.
+    pub struct MsgObservers: u8 {
+        const CLIENT_HELLO = 0x1;
+        const CERT_COMP = 0x2;
+        const STATS = 0x4;
+    }
+}
+
+/// Returns the observers enabled for the connection, preferring those set on the `Ssl` itself.
+#[cfg(ossl110)]
+fn msg_observers(ssl: &SslRef) -> MsgObservers {
+    ssl.ex_data(Ssl::cached_ex_index::<MsgObservers>())
+        .or_else(|| {
+            ssl.ssl_context()
+                .ex_data(SslContext::cached_ex_index::<MsgObservers>())
+        })
+        .cloned()
+        .unwrap_or_else(MsgObservers::empty)
+}
+
+/// The message callback shared by every feature which observes the raw protocol messages.
+#[cfg(ossl110)]
+pub unsafe extern "C" fn raw_msg(
+    write_p: c_int,
+    _: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: size_t,
+    ssl: *mut ffi::SSL,
+    _: *mut c_void,
+) {
+    if buf.is_null() {
+        return;
+    }
+
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let observers = msg_observers(ssl);
+    let msg = slice::from_raw_parts(buf as *const u8, len);
+
+    #[cfg(ossl110)]
+    observe_record_header(ssl, observers, write_p, content_type, msg);
+    #[cfg(ossl111)]
+    observe_client_hello(ssl, observers, write_p, content_type, msg);
+    #[cfg(ossl320)]
+    observe_compressed_certificate(ssl, observers, write_p, content_type, msg);
+}
+
+#[cfg(ossl110)]
+fn observe_record_header(
+    ssl: &mut SslRef,
+    observers: MsgObservers,
+    write_p: c_int,
+    content_type: c_int,
+    header: &[u8],
+) {
+    if observers.contains(MsgObservers::STATS) && content_type == ffi::SSL3_RT_HEADER {
+        connection_stats(ssl)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record_header(write_p != 0, header);
+    }
+}
+
+#[cfg(ossl111)]
+fn observe_client_hello(
+    ssl: &mut SslRef,
+    observers: MsgObservers,
+    write_p: c_int,
+    content_type: c_int,
+    msg: &[u8],
+) {
+    if !observers.contains(MsgObservers::CLIENT_HELLO)
+        || write_p != 0
+        || content_type != ffi::SSL3_RT_HANDSHAKE
+        || msg.first().map(|&t| c_int::from(t)) != Some(ffi::SSL3_MT_CLIENT_HELLO)
+    {
+        return;
+    }
+
+    let dtls = unsafe { ffi::SSL_is_dtls(ssl.as_ptr()) != 0 };
+    if let Some(fingerprint) = ClientHelloFingerprint::from_raw_client_hello(msg, dtls) {
+        ssl.set_client_hello_fingerprint(fingerprint);
+    }
+}
+
+#[cfg(ossl320)]
+fn observe_compressed_certificate(
+    ssl: &mut SslRef,
+    observers: MsgObservers,
+    write_p: c_int,
+    content_type: c_int,
+    msg: &[u8],
+) {
+    if !observers.contains(MsgObservers::CERT_COMP)
+        || write_p != 0
+        || content_type != ffi::SSL3_RT_HANDSHAKE
+    {
+        return;
+    }
+
+    let alg = match compressed_certificate_alg(msg) {
+        Some(alg) => alg,
+        None => return,
+    };
+    let idx = Ssl::cached_ex_index::<CertCompressionAlgorithm>();
+    match ssl.ex_data_mut(idx) {
+        Some(peer_alg) => *peer_alg = alg,
+        None => ssl.set_ex_data(idx, alg),
+    }
+}
+
+/// Returns the algorithm of a `CompressedCertificate` handshake message.
+#[cfg(ossl320)]
+pub(crate) fn compressed_certificate_alg(msg: &[u8]) -> Option<CertCompressionAlgorithm> {
+    // msg_type(1) length(3) algorithm(2) uncompressed_length(3) compressed_certificate_message<..>
+    if msg.len() < 6 || c_int::from(msg[0]) != ffi::SSL3_MT_COMPRESSED_CERTIFICATE {
+        return None;
+    }
+
+    let alg = u16::from_be_bytes([msg[4], msg[5]]);
+    Some(CertCompressionAlgorithm::from_raw(c_int::from(alg)))
+}
+
 pub extern "C" fn ssl_raw_verify<F>(
     preverify_ok: c_int,
     x509_ctx: *mut ffi::X509_STORE_CTX,
@@ -666,6 +934,17 @@ where
     }
 }
 
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_allow_early_data<F>(ssl: *mut ffi::SSL, arg: *mut c_void) -> c_int
+where
+    F: Fn(&mut SslRef) -> bool + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = arg as *const F;
+
+    (*callback)(ssl) as c_int
+}
+
 #[cfg(ossl111)]
 pub unsafe extern "C" fn raw_client_hello<F>(
     ssl: *mut ffi::SSL,
@@ -0,0 +1,235 @@
+//! Decoders for a client's hello message and its TLS extensions.
+//!
+//! Every decoder returns `None` if its input is malformed.
+
+/// A cursor over the body of a TLS extension.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u8_prefixed(&mut self) -> Option<Reader<'a>> {
+        let len = self.u8()?;
+        self.bytes(usize::from(len)).map(Reader)
+    }
+
+    fn u16_prefixed(&mut self) -> Option<Reader<'a>> {
+        let len = self.u16()?;
+        self.bytes(usize::from(len)).map(Reader)
+    }
+
+    fn u16_list(mut self) -> Option<Vec<u16>> {
+        let mut values = Vec::with_capacity(self.0.len() / 2);
+        while !self.is_empty() {
+            values.push(self.u16()?);
+        }
+        Some(values)
+    }
+
+    /// Returns the contents of a single length-prefixed vector spanning the entire extension.
+    fn single<F>(mut self, f: F) -> Option<Reader<'a>>
+    where
+        F: FnOnce(&mut Reader<'a>) -> Option<Reader<'a>>,
+    {
+        let inner = f(&mut self)?;
+        if self.is_empty() {
+            Some(inner)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn server_name(ext: &[u8]) -> Option<&str> {
+    let mut names = Reader(ext).single(Reader::u16_prefixed)?;
+
+    while !names.is_empty() {
+        let name_type = names.u8()?;
+        let name = names.u16_prefixed()?;
+        // host_name
+        if name_type == 0 {
+            return std::str::from_utf8(name.0).ok();
+        }
+    }
+
+    None
+}
+
+pub fn alpn_protocols(ext: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut protocols = Reader(ext).single(Reader::u16_prefixed)?;
+
+    let mut out = vec![];
+    while !protocols.is_empty() {
+        out.push(protocols.u8_prefixed()?.0);
+    }
+    Some(out)
+}
+
+pub fn supported_versions(ext: &[u8]) -> Option<Vec<u16>> {
+    Reader(ext).single(Reader::u8_prefixed)?.u16_list()
+}
+
+pub fn u16_list(ext: &[u8]) -> Option<Vec<u16>> {
+    Reader(ext).single(Reader::u16_prefixed)?.u16_list()
+}
+
+pub fn key_shares(ext: &[u8]) -> Option<Vec<(u16, &[u8])>> {
+    let mut shares = Reader(ext).single(Reader::u16_prefixed)?;
+
+    let mut out = vec![];
+    while !shares.is_empty() {
+        let group = shares.u16()?;
+        let key_exchange = shares.u16_prefixed()?;
+        out.push((group, key_exchange.0));
+    }
+    Some(out)
+}
+
+/// The fields of a raw client hello message.
+pub struct RawClientHello<'a> {
+    pub legacy_version: u16,
+    pub ciphers: Vec<u16>,
+    pub extensions: Vec<(u16, &'a [u8])>,
+}
+
+impl<'a> RawClientHello<'a> {
+    /// Returns the body of the first extension of the specified type.
+    pub fn ext(&self, ext_type: u16) -> Option<&'a [u8]> {
+        self.extensions
+            .iter()
+            .find(|&&(t, _)| t == ext_type)
+            .map(|&(_, body)| body)
+    }
+}
+
+/// Decodes a client hello handshake message, including its handshake header.
+pub fn client_hello(msg: &[u8], dtls: bool) -> Option<RawClientHello<'_>> {
+    let mut msg = Reader(msg);
+    // client_hello
+    if msg.u8()? != 1 {
+        return None;
+    }
+    let len = msg.bytes(3)?;
+    let len = usize::from(len[0]) << 16 | usize::from(len[1]) << 8 | usize::from(len[2]);
+    if dtls {
+        // message_seq, fragment_offset and fragment_length
+        msg.bytes(8)?;
+    }
+    if msg.0.len() != len {
+        return None;
+    }
+
+    let legacy_version = msg.u16()?;
+    // random
+    msg.bytes(32)?;
+    // legacy_session_id
+    msg.u8_prefixed()?;
+    if dtls {
+        // cookie
+        msg.u8_prefixed()?;
+    }
+    let ciphers = msg.u16_prefixed()?.u16_list()?;
+    // legacy_compression_methods
+    msg.u8_prefixed()?;
+
+    let mut extensions = vec![];
+    if !msg.is_empty() {
+        let mut exts = msg.single(Reader::u16_prefixed)?;
+        while !exts.is_empty() {
+            let ext_type = exts.u16()?;
+            let body = exts.u16_prefixed()?;
+            extensions.push((ext_type, body.0));
+        }
+    }
+
+    Some(RawClientHello {
+        legacy_version,
+        ciphers,
+        extensions,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_server_name() {
+        let ext = b"\x00\x0d\x00\x00\x0afoobar.com";
+        assert_eq!(server_name(ext), Some("foobar.com"));
+        assert_eq!(server_name(&ext[..ext.len() - 1]), None);
+        assert_eq!(server_name(b"\x00\x00"), None);
+    }
+
+    #[test]
+    fn decode_alpn_protocols() {
+        let ext = b"\x00\x0c\x02h2\x08http/1.1";
+        assert_eq!(
+            alpn_protocols(ext),
+            Some(vec![&b"h2"[..], &b"http/1.1"[..]])
+        );
+        assert_eq!(alpn_protocols(b"\x00\x03\x05h2"), None);
+    }
+
+    #[test]
+    fn decode_version_lists() {
+        assert_eq!(
+            supported_versions(b"\x04\x03\x04\x03\x03"),
+            Some(vec![0x0304, 0x0303])
+        );
+        assert_eq!(supported_versions(b"\x03\x03\x04\x03"), None);
+        assert_eq!(
+            u16_list(b"\x00\x04\x00\x1d\x00\x17"),
+            Some(vec![0x001d, 0x0017])
+        );
+        assert_eq!(u16_list(b"\x00\x04\x00\x1d\x00\x17\x00"), None);
+    }
+
+    #[test]
+    fn decode_key_shares() {
+        let ext = b"\x00\x08\x00\x1d\x00\x04abcd";
+        assert_eq!(key_shares(ext), Some(vec![(0x001d, &b"abcd"[..])]));
+        assert_eq!(key_shares(b"\x00\x08\x00\x1d\x00\x05abcd"), None);
+    }
+
+    #[test]
+    fn decode_client_hello() {
+        let mut msg = b"\x01\x00\x00\x00\x03\x03".to_vec();
+        msg.extend_from_slice(&[0; 32]);
+        msg.extend_from_slice(b"\x00\x00\x04\x13\x01\x00\xff\x01\x00");
+        msg.extend_from_slice(b"\x00\x0a\x43\x21\x00\x00\x00\x0b\x00\x02\x01\x00");
+        let len = msg.len() - 4;
+        msg[3] = len as u8;
+
+        let hello = client_hello(&msg, false).unwrap();
+        assert_eq!(hello.legacy_version, 0x0303);
+        assert_eq!(hello.ciphers, vec![0x1301, 0x00ff]);
+        assert_eq!(
+            hello.extensions,
+            vec![(0x4321, &b""[..]), (0x000b, &b"\x01\x00"[..])]
+        );
+        assert_eq!(hello.ext(0x000b), Some(&b"\x01\x00"[..]));
+        assert!(client_hello(&msg, true).is_none());
+        assert!(client_hello(&msg[..msg.len() - 1], false).is_none());
+    }
+}
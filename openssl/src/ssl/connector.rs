@@ -1,14 +1,20 @@
 use cfg_if::cfg_if;
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
 
 use crate::dh::Dh;
 use crate::error::ErrorStack;
+use crate::sha::sha256;
+#[cfg(ossl110)]
+use crate::ssl::SslVersion;
 use crate::ssl::{
     HandshakeError, Ssl, SslContext, SslContextBuilder, SslContextRef, SslMethod, SslMode,
     SslOptions, SslRef, SslStream, SslVerifyMode,
 };
 use crate::version;
+use crate::x509::{X509StoreContextRef, X509VerifyResult};
 
 const FFDHE_2048: &str = "
 -----BEGIN DH PARAMETERS-----
@@ -75,6 +81,107 @@ impl SslConnector {
         Ok(SslConnectorBuilder(ctx))
     }
 
+    /// Creates a new builder for TLS connections to modern servers.
+    ///
+    /// The profile consists of:
+    ///
+    /// * TLS 1.3 only, with the `TLS_AES_128_GCM_SHA256`, `TLS_AES_256_GCM_SHA384`, and
+    ///   `TLS_CHACHA20_POLY1305_SHA256` cipher suites.
+    /// * The X25519, P-256, and P-384 groups.
+    /// * ECDSA, Ed25519, and RSA-PSS signatures with SHA-256 or stronger. RSA PKCS#1 v1.5
+    ///   signatures with SHA-256 or stronger are additionally accepted on certificates.
+    /// * Security level 2.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn modern(method: SslMethod) -> Result<SslConnectorBuilder, ErrorStack> {
+        let mut ctx = ctx(method)?;
+        ctx.set_default_verify_paths()?;
+        ctx.set_min_proto_version(Some(SslVersion::TLS1_3))?;
+        ctx.set_ciphersuites(
+            "TLS_AES_128_GCM_SHA256:TLS_AES_256_GCM_SHA384:TLS_CHACHA20_POLY1305_SHA256",
+        )?;
+        ctx.set_groups_list("X25519:P-256:P-384")?;
+        ctx.set_sigalgs_list(
+            "ECDSA+SHA256:ECDSA+SHA384:ECDSA+SHA512:ed25519:RSA-PSS+SHA256:RSA-PSS+SHA384:\
+             RSA-PSS+SHA512:RSA+SHA256:RSA+SHA384:RSA+SHA512",
+        )?;
+        ctx.set_security_level(2);
+        setup_verify(&mut ctx);
+
+        Ok(SslConnectorBuilder(ctx))
+    }
+
+    /// Creates a new builder for TLS connections to a wide range of servers, including legacy
+    /// ones.
+    ///
+    /// The profile consists of:
+    ///
+    /// * TLS 1.0 through TLS 1.3, with the same cipher list as `SslConnector::builder`.
+    /// * The X25519, P-256, P-384, and P-521 groups.
+    /// * ECDSA, Ed25519, RSA-PSS, and RSA PKCS#1 v1.5 signatures, including SHA-1 for TLS 1.0 and
+    ///   TLS 1.1 servers.
+    /// * Security level 1, or security level 0 on OpenSSL 3.0.0 and newer, where level 1 forbids
+    ///   TLS 1.0, TLS 1.1, and SHA-1 signatures.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn compat(method: SslMethod) -> Result<SslConnectorBuilder, ErrorStack> {
+        let mut ctx = ctx(method)?;
+        ctx.set_default_verify_paths()?;
+        ctx.set_min_proto_version(Some(SslVersion::TLS1))?;
+        ctx.set_cipher_list(
+            "DEFAULT:!aNULL:!eNULL:!MD5:!3DES:!DES:!RC4:!IDEA:!SEED:!aDSS:!SRP:!PSK",
+        )?;
+        ctx.set_groups_list("X25519:P-256:P-384:P-521")?;
+        ctx.set_sigalgs_list(
+            "ECDSA+SHA256:ECDSA+SHA384:ECDSA+SHA512:ed25519:RSA-PSS+SHA256:RSA-PSS+SHA384:\
+             RSA-PSS+SHA512:RSA+SHA256:RSA+SHA384:RSA+SHA512:ECDSA+SHA1:RSA+SHA1",
+        )?;
+        ctx.set_security_level(if cfg!(ossl300) { 0 } else { 1 });
+        setup_verify(&mut ctx);
+
+        Ok(SslConnectorBuilder(ctx))
+    }
+
+    /// Creates a new builder for TLS connections restricted to FIPS 140 approved algorithms.
+    ///
+    /// The profile consists of:
+    ///
+    /// * TLS 1.2 and TLS 1.3.
+    /// * The `ECDHE-ECDSA-AES128-GCM-SHA256`, `ECDHE-RSA-AES128-GCM-SHA256`,
+    ///   `ECDHE-ECDSA-AES256-GCM-SHA384`, and `ECDHE-RSA-AES256-GCM-SHA384` cipher suites for
+    ///   TLS 1.2, and the `TLS_AES_128_GCM_SHA256` and `TLS_AES_256_GCM_SHA384` cipher suites for
+    ///   TLS 1.3.
+    /// * The P-256, P-384, and P-521 groups.
+    /// * ECDSA, RSA-PSS, and RSA PKCS#1 v1.5 signatures with SHA-256 or stronger.
+    /// * Security level 2.
+    ///
+    /// This only restricts the algorithms negotiated by the connection. It does not put OpenSSL
+    /// into FIPS mode.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn fips(method: SslMethod) -> Result<SslConnectorBuilder, ErrorStack> {
+        let mut ctx = ctx(method)?;
+        ctx.set_default_verify_paths()?;
+        ctx.set_min_proto_version(Some(SslVersion::TLS1_2))?;
+        ctx.set_cipher_list(
+            "ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:\
+             ECDHE-ECDSA-AES256-GCM-SHA384:ECDHE-RSA-AES256-GCM-SHA384",
+        )?;
+        ctx.set_ciphersuites("TLS_AES_128_GCM_SHA256:TLS_AES_256_GCM_SHA384")?;
+        ctx.set_groups_list("P-256:P-384:P-521")?;
+        ctx.set_sigalgs_list(
+            "ECDSA+SHA256:ECDSA+SHA384:ECDSA+SHA512:RSA-PSS+SHA256:RSA-PSS+SHA384:RSA-PSS+SHA512:\
+             RSA+SHA256:RSA+SHA384:RSA+SHA512",
+        )?;
+        ctx.set_security_level(2);
+        setup_verify(&mut ctx);
+
+        Ok(SslConnectorBuilder(ctx))
+    }
+
     /// Initiates a client-side TLS session on a stream.
     ///
     /// The domain is used for SNI and hostname verification.
@@ -91,6 +198,8 @@ impl SslConnector {
             ssl,
             sni: true,
             verify_hostname: true,
+            spki_pins: vec![],
+            pin_report_only: false,
         })
     }
 
@@ -101,7 +210,7 @@ impl SslConnector {
 
     /// Returns a shared reference to the inner raw `SslContext`.
     pub fn context(&self) -> &SslContextRef {
-        &*self.0
+        &self.0
     }
 }
 
@@ -134,6 +243,8 @@ pub struct ConnectConfiguration {
     ssl: Ssl,
     sni: bool,
     verify_hostname: bool,
+    spki_pins: Vec<[u8; 32]>,
+    pin_report_only: bool,
 }
 
 impl ConnectConfiguration {
@@ -169,6 +280,45 @@ impl ConnectConfiguration {
         self.verify_hostname = verify_hostname;
     }
 
+    /// A builder-style version of `set_pin_spki_sha256`.
+    pub fn pin_spki_sha256(mut self, pins: &[[u8; 32]]) -> ConnectConfiguration {
+        self.set_pin_spki_sha256(pins);
+        self
+    }
+
+    /// Configures public key pinning when connecting.
+    ///
+    /// Each pin is the SHA-256 digest of a DER-encoded SubjectPublicKeyInfo structure, as returned
+    /// by `X509Ref::public_key_info_der`. Once the certificate chain has been verified, the
+    /// handshake fails unless the public key of at least one certificate in the chain matches one
+    /// of the pins.
+    ///
+    /// Pinning is performed in addition to, not instead of, the normal certificate verification.
+    /// Configuring pins therefore enables `SslVerifyMode::PEER` on the connection. A verify callback
+    /// already configured on the context or the `Ssl` is still invoked, and a chain it rejects is
+    /// rejected regardless of the pins. An empty list of pins disables pinning, which is the
+    /// default.
+    pub fn set_pin_spki_sha256(&mut self, pins: &[[u8; 32]]) {
+        self.spki_pins = pins.to_vec();
+    }
+
+    /// A builder-style version of `set_pin_report_only`.
+    pub fn pin_report_only(mut self, report_only: bool) -> ConnectConfiguration {
+        self.set_pin_report_only(report_only);
+        self
+    }
+
+    /// Configures public key pin mismatches to be reported rather than enforced.
+    ///
+    /// If enabled, a chain which does not match any of the pins configured with
+    /// `set_pin_spki_sha256` does not cause the handshake to fail. Instead, `SslRef::verify_result`
+    /// will return `X509VerifyResult::APPLICATION_VERIFICATION` once the handshake completes.
+    ///
+    /// Defaults to `false`.
+    pub fn set_pin_report_only(&mut self, report_only: bool) {
+        self.pin_report_only = report_only;
+    }
+
     /// Returns an `Ssl` configured to connect to the provided domain.
     ///
     /// The domain is used for SNI and hostname verification if enabled.
@@ -181,6 +331,10 @@ impl ConnectConfiguration {
             setup_verify_hostname(&mut self.ssl, domain)?;
         }
 
+        if !self.spki_pins.is_empty() {
+            setup_spki_pins(&mut self.ssl, self.spki_pins, self.pin_report_only);
+        }
+
         Ok(self.ssl)
     }
 
@@ -242,6 +396,57 @@ impl SslAcceptor {
         Ok(SslAcceptorBuilder(ctx))
     }
 
+    /// Creates a new builder configured to connect to legacy clients, such as those which only
+    /// support TLS 1.0. This should only be used when such clients must be supported.
+    ///
+    /// This corresponds to the old configuration of version 5 of Mozilla's server side TLS
+    /// recommendations. See its [documentation][docs] for more details on specifics.
+    ///
+    /// The profile consists of:
+    ///
+    /// * TLS 1.0 through TLS 1.3, with the server's cipher suite preference order.
+    /// * The Mozilla old cipher list for TLS 1.0 through TLS 1.2, ending with `DES-CBC3-SHA`, and
+    ///   the `TLS_AES_128_GCM_SHA256`, `TLS_AES_256_GCM_SHA384`, and
+    ///   `TLS_CHACHA20_POLY1305_SHA256` cipher suites for TLS 1.3.
+    /// * The X25519, P-256, and P-384 groups, and the 2048 bit `ffdhe2048` group for DHE.
+    /// * OpenSSL's default signature algorithms, including the MD5 and SHA-1 based signatures
+    ///   used by TLS 1.0 and TLS 1.1 clients.
+    /// * Security level 0.
+    ///
+    /// The groups and TLS 1.3 cipher suites require OpenSSL 1.1.1 or newer, and the security level
+    /// requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [docs]: https://wiki.mozilla.org/Security/Server_Side_TLS
+    pub fn mozilla_old_v5(method: SslMethod) -> Result<SslAcceptorBuilder, ErrorStack> {
+        let mut ctx = ctx(method)?;
+        ctx.set_options(SslOptions::CIPHER_SERVER_PREFERENCE);
+        #[cfg(ossl110)]
+        {
+            ctx.set_min_proto_version(Some(SslVersion::TLS1))?;
+            ctx.set_security_level(0);
+        }
+        let dh = Dh::params_from_pem(FFDHE_2048.as_bytes())?;
+        ctx.set_tmp_dh(&dh)?;
+        setup_curves(&mut ctx)?;
+        #[cfg(ossl111)]
+        ctx.set_groups_list("X25519:P-256:P-384")?;
+        ctx.set_cipher_list(
+            "ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:ECDHE-ECDSA-AES256-GCM-SHA384:\
+             ECDHE-RSA-AES256-GCM-SHA384:ECDHE-ECDSA-CHACHA20-POLY1305:ECDHE-RSA-CHACHA20-POLY1305:\
+             DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:DHE-RSA-CHACHA20-POLY1305:\
+             ECDHE-ECDSA-AES128-SHA256:ECDHE-RSA-AES128-SHA256:ECDHE-ECDSA-AES128-SHA:\
+             ECDHE-RSA-AES128-SHA:ECDHE-ECDSA-AES256-SHA384:ECDHE-RSA-AES256-SHA384:\
+             ECDHE-ECDSA-AES256-SHA:ECDHE-RSA-AES256-SHA:DHE-RSA-AES128-SHA256:DHE-RSA-AES256-SHA256:\
+             AES128-GCM-SHA256:AES256-GCM-SHA384:AES128-SHA256:AES256-SHA256:AES128-SHA:AES256-SHA:\
+             DES-CBC3-SHA",
+        )?;
+        #[cfg(ossl111)]
+        ctx.set_ciphersuites(
+            "TLS_AES_128_GCM_SHA256:TLS_AES_256_GCM_SHA384:TLS_CHACHA20_POLY1305_SHA256",
+        )?;
+        Ok(SslAcceptorBuilder(ctx))
+    }
+
     /// Creates a new builder configured to connect to modern clients.
     ///
     /// This corresponds to the modern configuration of version 5 of Mozilla's server side TLS recommendations.
@@ -328,7 +533,7 @@ impl SslAcceptor {
 
     /// Returns a shared reference to the inner raw `SslContext`.
     pub fn context(&self) -> &SslContextRef {
-        &*self.0
+        &self.0
     }
 }
 
@@ -377,12 +582,65 @@ cfg_if! {
     }
 }
 
+fn setup_spki_pins(ssl: &mut SslRef, pins: Vec<[u8; 32]>, report_only: bool) {
+    // The callback is only consulted for the peer's chain if it is actually verified.
+    let mode = ssl.verify_mode() | SslVerifyMode::PEER;
+    // Any callback configured on the context or the `Ssl` still decides whether the chain is
+    // trusted; pinning only further restricts the chains it accepts.
+    let previous = unsafe { ffi::SSL_get_verify_callback(ssl.as_ptr()) };
+    ssl.set_verify_callback(mode, move |preverify_ok, x509_ctx| {
+        let ok = match previous {
+            Some(previous) => previous(preverify_ok as c_int, x509_ctx.as_ptr()) != 0,
+            None => verify_peer(preverify_ok, x509_ctx),
+        };
+        if !ok || x509_ctx.error_depth() != 0 {
+            return ok;
+        }
+
+        match matches_spki_pins(&pins, x509_ctx) {
+            Ok(true) => true,
+            Ok(false) => {
+                x509_ctx.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+                report_only
+            }
+            // A chain we were unable to check is never accepted, even in report-only mode.
+            Err(e) => {
+                e.put();
+                x509_ctx.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+                false
+            }
+        }
+    });
+}
+
+fn matches_spki_pins(
+    pins: &[[u8; 32]],
+    x509_ctx: &X509StoreContextRef,
+) -> Result<bool, ErrorStack> {
+    let chain = match x509_ctx.chain() {
+        Some(chain) => chain,
+        None => return Ok(false),
+    };
+
+    for cert in chain {
+        if pins.contains(&sha256(&cert.public_key_info_der()?)) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
 cfg_if! {
     if #[cfg(any(ossl102, libressl261))] {
         fn setup_verify(ctx: &mut SslContextBuilder) {
             ctx.set_verify(SslVerifyMode::PEER);
         }
 
+        fn verify_peer(preverify_ok: bool, _: &mut X509StoreContextRef) -> bool {
+            preverify_ok
+        }
+
         fn setup_verify_hostname(ssl: &mut SslRef, domain: &str) -> Result<(), ErrorStack> {
             use crate::x509::verify::X509CheckFlags;
 
@@ -405,6 +663,10 @@ cfg_if! {
             Ok(())
         }
 
+        fn verify_peer(preverify_ok: bool, x509_ctx: &mut X509StoreContextRef) -> bool {
+            verify::verify_callback(preverify_ok, x509_ctx)
+        }
+
         mod verify {
             use std::net::IpAddr;
             use std::str;
@@ -0,0 +1,100 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::ssl::SslRef;
+
+/// The tolerance OpenSSL allows between a ticket's reported and actual age.
+const TICKET_AGE_ALLOWANCE: Duration = Duration::from_secs(10);
+
+/// An in-memory cache which rejects replayed early data.
+///
+/// When resuming a session from a ticket, OpenSSL only accepts early data if the ticket age
+/// reported by the client is within 10 seconds of the ticket's actual age. A captured client hello
+/// can therefore only be replayed for a limited window. This cache remembers the random of every
+/// client hello whose early data it allowed for that window, and rejects early data from any
+/// client hello it has already seen. It is meant to be consulted from the callback passed to
+/// [`SslContextBuilder::set_allow_early_data_callback`].
+///
+/// The cache only protects a single process. Early data sent with an external pre-shared key is
+/// not subject to the ticket age check, so the window must be chosen by the application.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+///
+/// [`SslContextBuilder::set_allow_early_data_callback`]: struct.SslContextBuilder.html#method.set_allow_early_data_callback
+#[derive(Debug)]
+pub struct EarlyDataAntiReplay {
+    window: Duration,
+    seen: Mutex<Seen>,
+}
+
+#[derive(Debug, Default)]
+struct Seen {
+    randoms: HashSet<[u8; 32]>,
+    expiry: VecDeque<(Instant, [u8; 32])>,
+}
+
+impl Default for EarlyDataAntiReplay {
+    /// Creates a cache whose window matches OpenSSL's ticket age tolerance.
+    fn default() -> EarlyDataAntiReplay {
+        EarlyDataAntiReplay::new(TICKET_AGE_ALLOWANCE)
+    }
+}
+
+impl EarlyDataAntiReplay {
+    /// Creates a cache which remembers client hello randoms for `window`.
+    pub fn new(window: Duration) -> EarlyDataAntiReplay {
+        EarlyDataAntiReplay {
+            window,
+            seen: Mutex::new(Seen::default()),
+        }
+    }
+
+    /// Returns `true` if the connection's client hello has not been seen before, and records it.
+    ///
+    /// Returns `false` if the client hello is a replay, in which case its early data should be
+    /// rejected.
+    pub fn check(&self, ssl: &SslRef) -> bool {
+        let mut random = [0; 32];
+        if ssl.client_random(&mut random) != random.len() {
+            return false;
+        }
+
+        self.check_random(random, Instant::now())
+    }
+
+    fn check_random(&self, random: [u8; 32], now: Instant) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+
+        while let Some(&(time, old)) = seen.expiry.front() {
+            if now.duration_since(time) < self.window {
+                break;
+            }
+            seen.expiry.pop_front();
+            seen.randoms.remove(&old);
+        }
+
+        if !seen.randoms.insert(random) {
+            return false;
+        }
+        seen.expiry.push_back((now, random));
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rejects_replays() {
+        let cache = EarlyDataAntiReplay::new(Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert!(cache.check_random([1; 32], start));
+        assert!(cache.check_random([2; 32], start));
+        assert!(!cache.check_random([1; 32], start + Duration::from_secs(5)));
+        assert!(cache.check_random([1; 32], start + Duration::from_secs(10)));
+        assert!(!cache.check_random([1; 32], start + Duration::from_secs(11)));
+    }
+}
@@ -0,0 +1,387 @@
+use foreign_types::ForeignTypeRef;
+use std::fmt::Write;
+
+use crate::error::ErrorStack;
+use crate::hash::{hash, MessageDigest};
+use crate::ssl::client_hello;
+use crate::ssl::{SslRef, TlsExtType};
+
+/// JA3 and JA4 fingerprints of a client's hello message.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientHelloFingerprint {
+    ja3: String,
+    ja3_md5: String,
+    ja4: String,
+}
+
+impl ClientHelloFingerprint {
+    /// Computes the fingerprints of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// The message is read from OpenSSL's decoded view of it, which omits unknown extensions.
+    pub fn from_client_hello(ssl: &SslRef) -> Option<ClientHelloFingerprint> {
+        ClientHello::from_ssl(ssl)?.fingerprint().ok()
+    }
+
+    /// Computes the fingerprints of a raw client hello handshake message.
+    pub(crate) fn from_raw_client_hello(msg: &[u8], dtls: bool) -> Option<ClientHelloFingerprint> {
+        ClientHello::from_raw(msg, dtls)?.fingerprint().ok()
+    }
+
+    /// Returns the JA3 string.
+    pub fn ja3(&self) -> &str {
+        &self.ja3
+    }
+
+    /// Returns the hex-encoded MD5 digest of the JA3 string, which is the usual form of a JA3
+    /// fingerprint.
+    pub fn ja3_md5(&self) -> &str {
+        &self.ja3_md5
+    }
+
+    /// Returns the JA4 fingerprint.
+    pub fn ja4(&self) -> &str {
+        &self.ja4
+    }
+}
+
+/// The parts of a client's hello message which contribute to its fingerprints.
+struct ClientHello {
+    dtls: bool,
+    legacy_version: u16,
+    ciphers: Vec<u16>,
+    extensions: Vec<u16>,
+    supported_versions: Vec<u16>,
+    groups: Vec<u16>,
+    point_formats: Vec<u8>,
+    signature_algorithms: Vec<u16>,
+    alpn: Option<Vec<u8>>,
+}
+
+impl ClientHello {
+    fn new<'a, F>(
+        dtls: bool,
+        legacy_version: u16,
+        ciphers: Vec<u16>,
+        extensions: Vec<u16>,
+        ext: F,
+    ) -> ClientHello
+    where
+        F: Fn(TlsExtType) -> Option<&'a [u8]>,
+    {
+        let point_formats = ext(TlsExtType::EC_POINT_FORMATS)
+            .and_then(|ext| ext.split_first())
+            .filter(|&(&len, formats)| usize::from(len) == formats.len())
+            .map_or_else(Vec::new, |(_, formats)| formats.to_vec());
+        let alpn = ext(TlsExtType::ALPN)
+            .and_then(client_hello::alpn_protocols)
+            .and_then(|protocols| protocols.first().map(|p| p.to_vec()));
+
+        ClientHello {
+            dtls,
+            legacy_version,
+            ciphers,
+            extensions,
+            supported_versions: ext(TlsExtType::SUPPORTED_VERSIONS)
+                .and_then(client_hello::supported_versions)
+                .unwrap_or_default(),
+            groups: ext(TlsExtType::SUPPORTED_GROUPS)
+                .and_then(client_hello::u16_list)
+                .unwrap_or_default(),
+            point_formats,
+            signature_algorithms: ext(TlsExtType::SIGNATURE_ALGORITHMS)
+                .and_then(client_hello::u16_list)
+                .unwrap_or_default(),
+            alpn,
+        }
+    }
+
+    fn from_ssl(ssl: &SslRef) -> Option<ClientHello> {
+        let legacy_version = ssl.client_hello_legacy_version()?.0 as u16;
+        let ciphers = ssl
+            .client_hello_ciphers()?
+            .chunks(2)
+            .filter(|c| c.len() == 2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        let extensions = ssl
+            .client_hello_ext_present()?
+            .iter()
+            .map(|e| e.as_raw())
+            .collect();
+
+        let dtls = unsafe { ffi::SSL_is_dtls(ssl.as_ptr()) != 0 };
+
+        Some(ClientHello::new(
+            dtls,
+            legacy_version,
+            ciphers,
+            extensions,
+            |ext_type| ssl.client_hello_ext(ext_type),
+        ))
+    }
+
+    fn from_raw(msg: &[u8], dtls: bool) -> Option<ClientHello> {
+        let hello = client_hello::client_hello(msg, dtls)?;
+        let extensions = hello.extensions.iter().map(|&(t, _)| t).collect();
+
+        Some(ClientHello::new(
+            dtls,
+            hello.legacy_version,
+            hello.ciphers.clone(),
+            extensions,
+            |ext_type| hello.ext(ext_type.as_raw()),
+        ))
+    }
+
+    fn fingerprint(&self) -> Result<ClientHelloFingerprint, ErrorStack> {
+        let ja3 = self.ja3();
+        let ja3_md5 = hex(&hash(MessageDigest::md5(), ja3.as_bytes())?);
+        let ja4 = self.ja4()?;
+
+        Ok(ClientHelloFingerprint { ja3, ja3_md5, ja4 })
+    }
+
+    fn ja3(&self) -> String {
+        fn join<T: ToString>(values: impl Iterator<Item = T>) -> String {
+            values.map(|v| v.to_string()).collect::<Vec<_>>().join("-")
+        }
+
+        format!(
+            "{},{},{},{},{}",
+            self.legacy_version,
+            join(not_grease(&self.ciphers)),
+            join(not_grease(&self.extensions)),
+            join(not_grease(&self.groups)),
+            join(self.point_formats.iter()),
+        )
+    }
+
+    fn ja4(&self) -> Result<String, ErrorStack> {
+        // DTLS version numbers count down.
+        let versions = not_grease(&self.supported_versions);
+        let version = if self.dtls {
+            versions.min()
+        } else {
+            versions.max()
+        };
+        let version = version.unwrap_or(self.legacy_version);
+        let version = match version {
+            0x0304 => "13",
+            0x0303 => "12",
+            0x0302 => "11",
+            0x0301 => "10",
+            0x0300 => "s3",
+            0xfefc => "d3",
+            0xfefd => "d2",
+            0xfeff => "d1",
+            _ => "00",
+        };
+        let transport = if self.dtls { 'd' } else { 't' };
+        let sni = if self.extensions.contains(&TlsExtType::SERVER_NAME.as_raw()) {
+            'd'
+        } else {
+            'i'
+        };
+        let alpn = match &self.alpn {
+            Some(alpn) if !alpn.is_empty() => {
+                let first = alpn[0];
+                let last = alpn[alpn.len() - 1];
+                if first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric() {
+                    format!("{}{}", first as char, last as char)
+                } else {
+                    let hex = hex(alpn);
+                    format!("{}{}", &hex[..1], &hex[hex.len() - 1..])
+                }
+            }
+            _ => "00".to_string(),
+        };
+
+        let mut ciphers = not_grease(&self.ciphers).collect::<Vec<_>>();
+        ciphers.sort_unstable();
+        let mut extensions = not_grease(&self.extensions).collect::<Vec<_>>();
+        let extension_count = extensions.len();
+        extensions
+            .retain(|&e| e != TlsExtType::SERVER_NAME.as_raw() && e != TlsExtType::ALPN.as_raw());
+        extensions.sort_unstable();
+
+        let ciphers_hash = truncated_sha256(&hex_list(ciphers.iter()))?;
+        let mut extensions_hash = hex_list(extensions.iter());
+        let signature_algorithms = not_grease(&self.signature_algorithms).collect::<Vec<_>>();
+        if !signature_algorithms.is_empty() {
+            extensions_hash.push('_');
+            extensions_hash.push_str(&hex_list(signature_algorithms.iter()));
+        }
+        let extensions_hash = truncated_sha256(&extensions_hash)?;
+
+        Ok(format!(
+            "{}{}{}{:02}{:02}{}_{}_{}",
+            transport,
+            version,
+            sni,
+            ciphers.len().min(99),
+            extension_count.min(99),
+            alpn,
+            ciphers_hash,
+            extensions_hash,
+        ))
+    }
+}
+
+/// Filters out the GREASE values reserved by RFC 8701.
+fn not_grease(values: &[u16]) -> impl Iterator<Item = u16> + '_ {
+    values
+        .iter()
+        .cloned()
+        .filter(|&v| v & 0x0f0f != 0x0a0a || v >> 8 != v & 0xff)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        write!(s, "{:02x}", b).unwrap();
+    }
+    s
+}
+
+fn hex_list<'a>(values: impl Iterator<Item = &'a u16>) -> String {
+    values
+        .map(|v| format!("{:04x}", v))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn truncated_sha256(s: &str) -> Result<String, ErrorStack> {
+    if s.is_empty() {
+        return Ok("000000000000".to_string());
+    }
+
+    let digest = hash(MessageDigest::sha256(), s.as_bytes())?;
+    Ok(hex(&digest[..6]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn client_hello() -> ClientHello {
+        ClientHello {
+            dtls: false,
+            legacy_version: 0x0303,
+            ciphers: vec![0x2a2a, 0x1301, 0x1302, 0xc02f, 0x00ff],
+            extensions: vec![
+                0x3a3a, 0x0000, 0x000b, 0x000a, 0x0010, 0x000d, 0x002b, 0x0033,
+            ],
+            supported_versions: vec![0x7a7a, 0x0304, 0x0303],
+            groups: vec![0x4a4a, 0x001d, 0x0017],
+            point_formats: vec![0],
+            signature_algorithms: vec![0x0804, 0x0403],
+            alpn: Some(b"h2".to_vec()),
+        }
+    }
+
+    #[test]
+    fn ja3() {
+        let fingerprint = client_hello().fingerprint().unwrap();
+        assert_eq!(
+            fingerprint.ja3(),
+            "771,4865-4866-49199-255,0-11-10-16-13-43-51,29-23,0"
+        );
+    }
+
+    // The example from the JA3 README.
+    #[test]
+    fn ja3_reference() {
+        let hello = ClientHello {
+            dtls: false,
+            legacy_version: 0x0301,
+            ciphers: vec![47, 53, 5, 10, 49161, 49162, 49171, 49172, 50, 56, 19, 4],
+            extensions: vec![0, 10, 11],
+            supported_versions: vec![],
+            groups: vec![23, 24, 25],
+            point_formats: vec![0],
+            signature_algorithms: vec![],
+            alpn: None,
+        };
+        let fingerprint = hello.fingerprint().unwrap();
+        assert_eq!(
+            fingerprint.ja3(),
+            "769,47-53-5-10-49161-49162-49171-49172-50-56-19-4,0-10-11,23-24-25,0"
+        );
+        assert_eq!(fingerprint.ja3_md5(), "ada70206e40642a3e4461f35503241d5");
+    }
+
+    // The Chrome client hello from the JA4 technical details, with GREASE values added.
+    fn chrome_client_hello() -> ClientHello {
+        ClientHello {
+            dtls: false,
+            legacy_version: 0x0303,
+            ciphers: vec![
+                0x1a1a, 0x1301, 0x1302, 0x1303, 0xc02b, 0xc02f, 0xc02c, 0xc030, 0xcca9, 0xcca8,
+                0xc013, 0xc014, 0x009c, 0x009d, 0x002f, 0x0035,
+            ],
+            extensions: vec![
+                0x0a0a, 0x0000, 0x0017, 0xff01, 0x000a, 0x000b, 0x0023, 0x0010, 0x0005, 0x000d,
+                0x0012, 0x0033, 0x002d, 0x002b, 0x001b, 0x4469, 0xbaba, 0x0015,
+            ],
+            supported_versions: vec![0x6a6a, 0x0304, 0x0303],
+            groups: vec![0x7a7a, 0x001d, 0x0017, 0x0018],
+            point_formats: vec![0],
+            signature_algorithms: vec![
+                0x0403, 0x0804, 0x0401, 0x0503, 0x0805, 0x0501, 0x0806, 0x0601,
+            ],
+            alpn: Some(b"h2".to_vec()),
+        }
+    }
+
+    #[test]
+    fn ja4_reference() {
+        let fingerprint = chrome_client_hello().fingerprint().unwrap();
+        assert_eq!(fingerprint.ja4(), "t13d1516h2_8daaf6152771_e5627efa2ab1");
+
+        let mut hello = chrome_client_hello();
+        hello.signature_algorithms.insert(0, 0x5a5a);
+        assert_eq!(hello.fingerprint().unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn ja4() {
+        let mut hello = client_hello();
+        hello.supported_versions.clear();
+        hello.extensions.retain(|&e| e != 0x0000);
+        hello.alpn = Some(b"\x01foo\xff".to_vec());
+        let fingerprint = hello.fingerprint().unwrap();
+        assert!(fingerprint.ja4().starts_with("t12i04060f_"));
+    }
+
+    #[test]
+    fn ja4_dtls() {
+        let mut hello = chrome_client_hello();
+        hello.dtls = true;
+        hello.legacy_version = 0xfefd;
+        hello.supported_versions = vec![0xfefc, 0xfefd];
+        assert!(hello
+            .fingerprint()
+            .unwrap()
+            .ja4()
+            .starts_with("dd3d1516h2_"));
+
+        hello.supported_versions.clear();
+        assert!(hello
+            .fingerprint()
+            .unwrap()
+            .ja4()
+            .starts_with("dd2d1516h2_"));
+    }
+
+    #[test]
+    fn grease() {
+        assert_eq!(
+            not_grease(&[0x0a0a, 0x1a1a, 0xfafa, 0x0a1a, 0x1301]).collect::<Vec<_>>(),
+            vec![0x0a1a, 0x1301]
+        );
+    }
+}
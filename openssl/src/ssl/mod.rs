@@ -65,8 +65,14 @@ use once_cell::sync::{Lazy, OnceCell};
 use std::any::TypeId;
 use std::cmp;
 use std::collections::HashMap;
+#[cfg(ossl111)]
+use std::env;
+#[cfg(ossl111)]
+use std::ffi::OsString;
 use std::ffi::{CStr, CString};
 use std::fmt;
+#[cfg(ossl111)]
+use std::fs::{File, OpenOptions};
 use std::io;
 use std::io::prelude::*;
 use std::marker::PhantomData;
@@ -74,11 +80,17 @@ use std::mem::{self, ManuallyDrop};
 use std::ops::{Deref, DerefMut};
 use std::panic::resume_unwind;
 use std::path::Path;
+#[cfg(ossl111)]
+use std::path::PathBuf;
 use std::ptr;
 use std::slice;
 use std::str;
+#[cfg(ossl111)]
+use std::sync::Weak;
 use std::sync::{Arc, Mutex};
 
+#[cfg(ossl102)]
+use crate::conf::ConfRef;
 use crate::dh::{Dh, DhRef};
 #[cfg(all(ossl101, not(ossl110)))]
 use crate::ec::EcKey;
@@ -87,14 +99,16 @@ use crate::error::ErrorStack;
 use crate::ex_data::Index;
 #[cfg(ossl111)]
 use crate::hash::MessageDigest;
-#[cfg(ossl110)]
+#[cfg(ossl102)]
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, PKeyRef, Params, Private};
+#[cfg(ossl111)]
+use crate::pkey::{PKey, Public};
 use crate::srtp::{SrtpProtectionProfile, SrtpProtectionProfileRef};
 use crate::ssl::bio::BioMethod;
 use crate::ssl::callbacks::*;
 use crate::ssl::error::InnerError;
-use crate::stack::{Stack, StackRef};
+use crate::stack::{Stack, StackRef, Stackable};
 use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
 use crate::x509::store::{X509Store, X509StoreBuilderRef, X509StoreRef};
 #[cfg(any(ossl102, libressl261))]
@@ -105,12 +119,24 @@ use crate::{cvt, cvt_n, cvt_p, init};
 pub use crate::ssl::connector::{
     ConnectConfiguration, SslAcceptor, SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
 };
+#[cfg(ossl111)]
+pub use crate::ssl::early_data::EarlyDataAntiReplay;
 pub use crate::ssl::error::{Error, ErrorCode, HandshakeError};
+#[cfg(ossl111)]
+pub use crate::ssl::fingerprint::ClientHelloFingerprint;
+pub use crate::ssl::stats::{SslSessionStats, SslStats};
 
 mod bio;
 mod callbacks;
+#[cfg(ossl111)]
+mod client_hello;
 mod connector;
+#[cfg(ossl111)]
+mod early_data;
 mod error;
+#[cfg(ossl111)]
+mod fingerprint;
+mod stats;
 #[cfg(test)]
 mod test;
 
@@ -514,6 +540,51 @@ impl NameType {
     }
 }
 
+/// The policy used to sync key log lines to disk.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeylogSync {
+    /// Lines are written to the file but never explicitly synced to disk.
+    ///
+    /// They are still visible to other readers of the file immediately, which is sufficient for
+    /// live debugging.
+    Never,
+    /// Every line is synced to disk after it is written, so that no keys are lost if the system
+    /// crashes.
+    EveryLine,
+}
+
+#[cfg(ossl111)]
+static KEYLOG_FILES: Lazy<Mutex<HashMap<PathBuf, Weak<Mutex<File>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Opens a key log file, sharing the handle with any other context already logging to it.
+#[cfg(ossl111)]
+fn keylog_file(path: &Path) -> io::Result<Arc<Mutex<File>>> {
+    let mut files = KEYLOG_FILES.lock().unwrap();
+
+    if let Ok(canonical) = path.canonicalize() {
+        if let Some(file) = files.get(&canonical).and_then(Weak::upgrade) {
+            return Ok(file);
+        }
+    }
+
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = Arc::new(Mutex::new(options.open(path)?));
+
+    files.retain(|_, file| file.upgrade().is_some());
+    files.insert(path.canonicalize()?, Arc::downgrade(&file));
+    Ok(file)
+}
+
 static INDEXES: Lazy<Mutex<HashMap<TypeId, c_int>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static SSL_INDEXES: Lazy<Mutex<HashMap<TypeId, c_int>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static SESSION_CTX_INDEX: OnceCell<Index<Ssl, SslContext>> = OnceCell::new();
@@ -572,7 +643,7 @@ impl AlpnError {
     /// Terminate the handshake with a fatal alert.
     ///
     /// Requires OpenSSL 1.1.0 or newer.
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     pub const ALERT_FATAL: AlpnError = AlpnError(ffi::SSL_TLSEXT_ERR_ALERT_FATAL);
 
     /// Do not select a protocol, but continue the handshake.
@@ -595,6 +666,129 @@ impl ClientHelloResponse {
     pub const RETRY: ClientHelloResponse = ClientHelloResponse(ffi::SSL_CLIENT_HELLO_RETRY);
 }
 
+/// A TLS extension type.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TlsExtType(u16);
+
+#[cfg(ossl111)]
+impl TlsExtType {
+    /// The `server_name` extension.
+    pub const SERVER_NAME: TlsExtType = TlsExtType(0);
+
+    /// The `status_request` extension.
+    pub const STATUS_REQUEST: TlsExtType = TlsExtType(5);
+
+    /// The `supported_groups` extension, formerly known as `elliptic_curves`.
+    pub const SUPPORTED_GROUPS: TlsExtType = TlsExtType(10);
+
+    /// The `ec_point_formats` extension.
+    pub const EC_POINT_FORMATS: TlsExtType = TlsExtType(11);
+
+    /// The `signature_algorithms` extension.
+    pub const SIGNATURE_ALGORITHMS: TlsExtType = TlsExtType(13);
+
+    /// The `application_layer_protocol_negotiation` extension.
+    pub const ALPN: TlsExtType = TlsExtType(16);
+
+    /// The `session_ticket` extension.
+    pub const SESSION_TICKET: TlsExtType = TlsExtType(35);
+
+    /// The `pre_shared_key` extension.
+    pub const PRE_SHARED_KEY: TlsExtType = TlsExtType(41);
+
+    /// The `early_data` extension.
+    pub const EARLY_DATA: TlsExtType = TlsExtType(42);
+
+    /// The `supported_versions` extension.
+    pub const SUPPORTED_VERSIONS: TlsExtType = TlsExtType(43);
+
+    /// The `psk_key_exchange_modes` extension.
+    pub const PSK_KEY_EXCHANGE_MODES: TlsExtType = TlsExtType(45);
+
+    /// The `key_share` extension.
+    pub const KEY_SHARE: TlsExtType = TlsExtType(51);
+
+    /// The `renegotiation_info` extension.
+    pub const RENEGOTIATE: TlsExtType = TlsExtType(0xff01);
+
+    /// Constructs a `TlsExtType` from a raw extension code point.
+    pub fn from_raw(raw: u16) -> TlsExtType {
+        TlsExtType(raw)
+    }
+
+    /// Returns the raw extension code point.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
+/// An entry of the `key_share` extension of a client's hello message.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyShareEntry<'a> {
+    /// The TLS code point of the group.
+    pub group: u16,
+
+    /// The encoded public key.
+    pub key_exchange: &'a [u8],
+}
+
+/// The status of early data on a connection.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EarlyDataStatus(c_int);
+
+#[cfg(ossl111)]
+impl EarlyDataStatus {
+    /// The client did not send early data.
+    pub const NOT_SENT: EarlyDataStatus = EarlyDataStatus(ffi::SSL_EARLY_DATA_NOT_SENT);
+
+    /// The client sent early data, but the server rejected it.
+    pub const REJECTED: EarlyDataStatus = EarlyDataStatus(ffi::SSL_EARLY_DATA_REJECTED);
+
+    /// The client sent early data, and the server accepted it.
+    pub const ACCEPTED: EarlyDataStatus = EarlyDataStatus(ffi::SSL_EARLY_DATA_ACCEPTED);
+}
+
+/// A certificate compression algorithm, as defined in RFC 8879.
+///
+/// Requires OpenSSL 3.2.0 or newer.
+#[cfg(ossl320)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CertCompressionAlgorithm(c_int);
+
+#[cfg(ossl320)]
+impl CertCompressionAlgorithm {
+    /// zlib compression.
+    pub const ZLIB: CertCompressionAlgorithm = CertCompressionAlgorithm(ffi::TLSEXT_comp_cert_zlib);
+
+    /// Brotli compression.
+    pub const BROTLI: CertCompressionAlgorithm =
+        CertCompressionAlgorithm(ffi::TLSEXT_comp_cert_brotli);
+
+    /// Zstandard compression.
+    pub const ZSTD: CertCompressionAlgorithm = CertCompressionAlgorithm(ffi::TLSEXT_comp_cert_zstd);
+
+    /// Constructs a `CertCompressionAlgorithm` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> CertCompressionAlgorithm {
+        CertCompressionAlgorithm(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
 /// An SSL/TLS protocol version.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SslVersion(c_int);
@@ -1459,6 +1653,64 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the callback providing a pre-shared key session for a TLS 1.3 client.
+    ///
+    /// The callback will be called with the SSL context and the digest of the cipher used for
+    /// the handshake if it is being called after a HelloRetryRequest. It returns the identity
+    /// of the pre-shared key along with a session carrying the key, the cipher, and the
+    /// protocol version, or `None` if no pre-shared key should be offered. Sessions can be
+    /// constructed with [`SslSessionBuilder`].
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_psk_use_session_callback`].
+    ///
+    /// [`SSL_CTX_set_psk_use_session_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_psk_use_session_callback.html
+    #[cfg(ossl111)]
+    pub fn set_psk_use_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(
+                &mut SslRef,
+                Option<MessageDigest>,
+            ) -> Result<Option<(Vec<u8>, SslSession)>, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_use_session_callback(
+                self.as_ptr(),
+                Some(raw_psk_use_session::<F>),
+            );
+        }
+    }
+
+    /// Sets the callback looking up a pre-shared key session for a TLS 1.3 server.
+    ///
+    /// The callback will be called with the SSL context and the identity offered by the client.
+    /// It returns a session carrying the matching key, cipher, and protocol version, or `None`
+    /// if the identity is not known.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_psk_find_session_callback`].
+    ///
+    /// [`SSL_CTX_set_psk_find_session_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_psk_find_session_callback.html
+    #[cfg(ossl111)]
+    pub fn set_psk_find_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &[u8]) -> Result<Option<SslSession>, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_find_session_callback(
+                self.as_ptr(),
+                Some(raw_psk_find_session::<F>),
+            );
+        }
+    }
+
     /// Sets the callback which is called when new sessions are negotiated.
     ///
     /// This can be used by clients to implement session caching. While in TLSv1.2 the session is
@@ -1549,6 +1801,65 @@ impl SslContextBuilder {
         }
     }
 
+    /// Appends TLS key material to a file in the NSS SSLKEYLOGFILE format.
+    ///
+    /// The file is created if it does not exist, readable only by its owner on Unix. Each line is
+    /// written with a single `write` call to a file opened in append mode, so lines from multiple
+    /// processes are not interleaved. Within a process, all contexts logging to the same file share
+    /// a single handle and lock. Lines are handed to the operating system as soon as they are
+    /// generated so that tools like Wireshark see them immediately; `sync` controls whether they
+    /// are additionally synced to disk.
+    ///
+    /// This is implemented with `set_keylog_callback`, and replaces any callback configured with
+    /// it.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn set_keylog_file<P: AsRef<Path>>(&mut self, path: P, sync: KeylogSync) -> io::Result<()> {
+        let file = keylog_file(path.as_ref())?;
+
+        self.set_keylog_callback(move |_, line| {
+            let mut buf = Vec::with_capacity(line.len() + 1);
+            buf.extend_from_slice(line.as_bytes());
+            buf.push(b'\n');
+            let file = file.lock().unwrap();
+            // there is nowhere to report errors to
+            let _ = (&*file).write_all(&buf);
+            if sync == KeylogSync::EveryLine {
+                let _ = file.sync_data();
+            }
+        });
+        Ok(())
+    }
+
+    /// Appends TLS key material to the file named by the `SSLKEYLOGFILE` environment variable, if
+    /// it is set.
+    ///
+    /// Returns `true` if key logging was enabled. This is an opt-in hook intended to be called on
+    /// an `SslConnectorBuilder` or `SslAcceptorBuilder` in debugging environments. See
+    /// `set_keylog_file` for details on how the file is written.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn set_keylog_file_from_env(&mut self, sync: KeylogSync) -> io::Result<bool> {
+        self.set_keylog_file_from_var(env::var_os("SSLKEYLOGFILE"), sync)
+    }
+
+    #[cfg(ossl111)]
+    fn set_keylog_file_from_var(
+        &mut self,
+        path: Option<OsString>,
+        sync: KeylogSync,
+    ) -> io::Result<bool> {
+        match path {
+            Some(ref path) if !path.is_empty() => {
+                self.set_keylog_file(path, sync)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
     /// Sets the session caching mode use for connections made with the context.
     ///
     /// Returns the previous session caching mode.
@@ -1735,6 +2046,50 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the maximum amount of early data that will actually be read on incoming connections.
+    ///
+    /// Unlike [`set_max_early_data`], which is the limit advertised in session tickets, this is
+    /// the limit enforced by the server. Defaults to 16384.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_recv_max_early_data`].
+    ///
+    /// [`set_max_early_data`]: #method.set_max_early_data
+    /// [`SSL_CTX_set_recv_max_early_data`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_recv_max_early_data.html
+    #[cfg(ossl111)]
+    pub fn set_recv_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_recv_max_early_data(self.as_ptr(), bytes)).map(|_| ()) }
+    }
+
+    /// Sets a callback which decides whether early data sent by a client will be accepted.
+    ///
+    /// The callback is invoked on the server once the client's early data has been otherwise
+    /// found acceptable, and returns `false` to reject it. The handshake then continues as a
+    /// normal 1-RTT handshake. This can be combined with an [`EarlyDataAntiReplay`] cache to
+    /// reject replayed early data.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_allow_early_data_cb`].
+    ///
+    /// [`EarlyDataAntiReplay`]: struct.EarlyDataAntiReplay.html
+    /// [`SSL_CTX_set_allow_early_data_cb`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_allow_early_data_cb.html
+    #[cfg(ossl111)]
+    pub fn set_allow_early_data_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_allow_early_data_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_allow_early_data::<F>),
+                ptr,
+            );
+        }
+    }
+
     /// Sets a callback which will be invoked just after the client's hello message is received.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
@@ -1760,6 +2115,143 @@ impl SslContextBuilder {
         }
     }
 
+    /// Enables JA3 and JA4 fingerprinting of client hello messages.
+    ///
+    /// The fingerprint of each connection is available from `SslRef::client_hello_fingerprint`
+    /// once the client's hello message has been received, including from a client hello
+    /// callback. If the server requests a new hello message with a HelloRetryRequest, the
+    /// fingerprint of the second message replaces that of the first.
+    ///
+    /// The raw message is fingerprinted, so extensions unknown to OpenSSL are included. This is
+    /// implemented with a message callback, which replaces any callback configured with
+    /// `SSL_CTX_set_msg_callback`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn enable_client_hello_fingerprint(&mut self) {
+        self.enable_msg_observers(MsgObservers::CLIENT_HELLO);
+    }
+
+    /// Adds observers to the context's internal message callback, installing it if necessary.
+    #[cfg(ossl110)]
+    fn enable_msg_observers(&mut self, observers: MsgObservers) {
+        unsafe {
+            let idx = SslContext::cached_ex_index::<MsgObservers>();
+            let ptr = ffi::SSL_CTX_get_ex_data(self.as_ptr(), idx.as_raw()) as *mut MsgObservers;
+            if ptr.is_null() {
+                self.set_ex_data(idx, observers);
+            } else {
+                *ptr |= observers;
+            }
+            ffi::SSL_CTX_set_msg_callback(self.as_ptr(), Some(raw_msg));
+        }
+    }
+
+    /// Applies configuration commands to the context.
+    ///
+    /// Each command is a name-value pair in the format used by configuration files, such as
+    /// `("MinProtocol", "TLSv1.2")` or `("Ciphersuites", "TLS_AES_256_GCM_SHA384")`. Commands for
+    /// both clients and servers are accepted, as are the `Certificate` and `PrivateKey` commands.
+    ///
+    /// All command names are checked before any command is applied, so an unknown command leaves
+    /// the context untouched. The commands are then applied in order, stopping at the first one
+    /// with an invalid value. The commands preceding it remain applied, so the context should be
+    /// discarded if an error is returned.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CONF_cmd`].
+    ///
+    /// [`SSL_CONF_cmd`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CONF_cmd.html
+    #[cfg(ossl102)]
+    pub fn apply_conf_cmds(&mut self, cmds: &[(&str, &str)]) -> Result<(), ErrorStack> {
+        unsafe {
+            let cctx = SslConfCtx::new(self)?;
+            let cmds = cmds
+                .iter()
+                .map(|&(cmd, value)| {
+                    let cmd = CString::new(cmd).unwrap();
+                    cctx.check(&cmd)?;
+                    Ok((cmd, CString::new(value).unwrap()))
+                })
+                .collect::<Result<Vec<_>, ErrorStack>>()?;
+            for (cmd, value) in &cmds {
+                cctx.cmd(cmd, value)?;
+            }
+            cctx.finish()
+        }
+    }
+
+    /// Applies the configuration commands in a section of a configuration file to the context.
+    ///
+    /// For example, the section
+    ///
+    /// ```text
+    /// [tls_server]
+    /// MinProtocol = TLSv1.2
+    /// Ciphersuites = TLS_AES_256_GCM_SHA384
+    /// ```
+    ///
+    /// is equivalent to the commands passed to `apply_conf_cmds` in its documentation.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CONF_cmd`].
+    ///
+    /// [`SSL_CONF_cmd`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CONF_cmd.html
+    #[cfg(ossl102)]
+    pub fn apply_conf_section(&mut self, conf: &ConfRef, section: &str) -> Result<(), ErrorStack> {
+        let cmds = conf.section(section)?;
+        self.apply_conf_cmds(&cmds)
+    }
+
+    /// Sets the security level of the context.
+    ///
+    /// The security level restricts the algorithms and key sizes which may be used. Level 1
+    /// requires 80 bits of security, level 2 112 bits, level 3 128 bits, level 4 192 bits, and
+    /// level 5 256 bits. Level 0 permits everything.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_security_level`].
+    ///
+    /// [`SSL_CTX_set_security_level`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_security_level.html
+    #[cfg(ossl110)]
+    pub fn set_security_level(&mut self, level: u32) {
+        unsafe { ffi::SSL_CTX_set_security_level(self.as_ptr(), level as c_int) }
+    }
+
+    /// Enables collection of per-connection statistics.
+    ///
+    /// Once enabled, `SslRef::stats` reports handshake timings as well as the number of records
+    /// and bytes exchanged with the peer.
+    ///
+    /// This is implemented with an info callback, which calls any info callback previously
+    /// configured on the context, and with a message callback, which replaces any callback
+    /// configured with `SSL_CTX_set_msg_callback`.
+    ///
+    /// Record and byte counts require OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_info_callback`] and [`SSL_CTX_set_msg_callback`].
+    ///
+    /// [`SSL_CTX_set_info_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_info_callback.html
+    /// [`SSL_CTX_set_msg_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_msg_callback.html
+    pub fn enable_connection_stats(&mut self) {
+        unsafe {
+            let previous = ffi::SSL_CTX_get_info_callback(self.as_ptr());
+            let raw = raw_stats_info as unsafe extern "C" fn(_, _, _);
+            if let Some(previous) = previous.filter(|&p| p as usize != raw as usize) {
+                self.set_ex_data(
+                    SslContext::cached_ex_index(),
+                    PreviousInfoCallback(previous),
+                );
+            }
+            ffi::SSL_CTX_set_info_callback(self.as_ptr(), Some(raw));
+        }
+        #[cfg(ossl110)]
+        self.enable_msg_observers(MsgObservers::STATS);
+    }
+
     /// Sets the context's session cache size limit, returning the previous limit.
     ///
     /// A value of 0 means that the cache size is unbounded.
@@ -1803,6 +2295,57 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the certificate compression algorithms the context is willing to use, in order of
+    /// preference.
+    ///
+    /// Algorithms not compiled into OpenSSL are ignored, and an error is returned if OpenSSL was
+    /// built without any of them. An empty list disables certificate compression.
+    ///
+    /// The algorithm the peer used to compress its certificates is reported by
+    /// `SslRef::peer_cert_comp`. This is implemented with a message callback, which replaces any
+    /// callback configured with `SSL_CTX_set_msg_callback`.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set1_cert_comp_preference`].
+    ///
+    /// [`SSL_CTX_set1_cert_comp_preference`]: https://www.openssl.org/docs/man3.2/man3/SSL_CTX_set1_cert_comp_preference.html
+    #[cfg(ossl320)]
+    pub fn set_cert_comp_preference(
+        &mut self,
+        algs: &[CertCompressionAlgorithm],
+    ) -> Result<(), ErrorStack> {
+        let mut algs = algs.iter().map(|a| a.0).collect::<Vec<_>>();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_cert_comp_preference(
+                self.as_ptr(),
+                algs.as_mut_ptr(),
+                algs.len(),
+            ))?;
+        }
+        self.enable_msg_observers(MsgObservers::CERT_COMP);
+        Ok(())
+    }
+
+    /// Pre-compresses the context's certificate chain.
+    ///
+    /// This avoids compressing the chain during every handshake. If `alg` is `None`, the chain is
+    /// compressed with every algorithm in the preference list.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_compress_certs`].
+    ///
+    /// [`SSL_CTX_compress_certs`]: https://www.openssl.org/docs/man3.2/man3/SSL_CTX_compress_certs.html
+    #[cfg(ossl320)]
+    pub fn compress_certs(
+        &mut self,
+        alg: Option<CertCompressionAlgorithm>,
+    ) -> Result<(), ErrorStack> {
+        let alg = alg.map_or(ffi::TLSEXT_comp_cert_none, |a| a.0);
+        unsafe { cvt(ffi::SSL_CTX_compress_certs(self.as_ptr(), alg)).map(|_| ()) }
+    }
+
     /// Consumes the builder, returning a new `SslContext`.
     pub fn build(self) -> SslContext {
         self.0
@@ -1969,6 +2512,18 @@ impl SslContextRef {
         unsafe { ffi::SSL_CTX_get_max_early_data(self.as_ptr()) }
     }
 
+    /// Gets the maximum amount of early data that will actually be read on incoming connections.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_get_recv_max_early_data`].
+    ///
+    /// [`SSL_CTX_get_recv_max_early_data`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_get_recv_max_early_data.html
+    #[cfg(ossl111)]
+    pub fn recv_max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_get_recv_max_early_data(self.as_ptr()) }
+    }
+
     /// Adds a session to the context's cache.
     ///
     /// Returns `true` if the session was successfully added to the cache, and `false` if it was already present.
@@ -2013,15 +2568,119 @@ impl SslContextRef {
         unsafe { ffi::SSL_CTX_sess_get_cache_size(self.as_ptr()).into() }
     }
 
-    /// Returns the verify mode that was set on this context from [`SslContextBuilder::set_verify`].
+    /// Returns the security level of the context.
     ///
-    /// This corresponds to [`SSL_CTX_get_verify_mode`].
+    /// Requires OpenSSL 1.1.0 or newer.
     ///
-    /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
-    /// [`SSL_CTX_get_verify_mode`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_get_verify_mode.html
-    pub fn verify_mode(&self) -> SslVerifyMode {
-        let mode = unsafe { ffi::SSL_CTX_get_verify_mode(self.as_ptr()) };
-        SslVerifyMode::from_bits(mode).expect("SSL_CTX_get_verify_mode returned invalid mode")
+    /// This corresponds to [`SSL_CTX_get_security_level`].
+    ///
+    /// [`SSL_CTX_get_security_level`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_get_security_level.html
+    #[cfg(ossl110)]
+    pub fn security_level(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_get_security_level(self.as_ptr()) as u32 }
+    }
+
+    /// Returns the ciphers enabled for the context, in order of preference.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_get_ciphers`].
+    ///
+    /// [`SSL_CTX_get_ciphers`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_get_ciphers.html
+    #[cfg(ossl110)]
+    pub fn ciphers(&self) -> Option<&StackRef<SslCipher>> {
+        unsafe {
+            let ptr = ffi::SSL_CTX_get_ciphers(self.as_ptr());
+            StackRef::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the context's session cache statistics.
+    ///
+    /// This corresponds to [`SSL_CTX_sess_number`] and related functions.
+    ///
+    /// [`SSL_CTX_sess_number`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_sess_number.html
+    pub fn session_stats(&self) -> SslSessionStats {
+        unsafe {
+            let ctx = self.as_ptr();
+            SslSessionStats {
+                number: ffi::SSL_CTX_sess_number(ctx) as u64,
+                connect: ffi::SSL_CTX_sess_connect(ctx) as u64,
+                connect_good: ffi::SSL_CTX_sess_connect_good(ctx) as u64,
+                connect_renegotiate: ffi::SSL_CTX_sess_connect_renegotiate(ctx) as u64,
+                accept: ffi::SSL_CTX_sess_accept(ctx) as u64,
+                accept_good: ffi::SSL_CTX_sess_accept_good(ctx) as u64,
+                accept_renegotiate: ffi::SSL_CTX_sess_accept_renegotiate(ctx) as u64,
+                hits: ffi::SSL_CTX_sess_hits(ctx) as u64,
+                cb_hits: ffi::SSL_CTX_sess_cb_hits(ctx) as u64,
+                misses: ffi::SSL_CTX_sess_misses(ctx) as u64,
+                timeouts: ffi::SSL_CTX_sess_timeouts(ctx) as u64,
+                cache_full: ffi::SSL_CTX_sess_cache_full(ctx) as u64,
+            }
+        }
+    }
+
+    /// Returns the verify mode that was set on this context from [`SslContextBuilder::set_verify`].
+    ///
+    /// This corresponds to [`SSL_CTX_get_verify_mode`].
+    ///
+    /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
+    /// [`SSL_CTX_get_verify_mode`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_get_verify_mode.html
+    pub fn verify_mode(&self) -> SslVerifyMode {
+        let mode = unsafe { ffi::SSL_CTX_get_verify_mode(self.as_ptr()) };
+        SslVerifyMode::from_bits(mode).expect("SSL_CTX_get_verify_mode returned invalid mode")
+    }
+}
+
+/// An `SSL_CONF_CTX` applying commands to an `SslContextBuilder`.
+#[cfg(ossl102)]
+struct SslConfCtx(*mut ffi::SSL_CONF_CTX);
+
+#[cfg(ossl102)]
+impl SslConfCtx {
+    unsafe fn new(ctx: &mut SslContextBuilder) -> Result<SslConfCtx, ErrorStack> {
+        let cctx = SslConfCtx(cvt_p(ffi::SSL_CONF_CTX_new())?);
+        ffi::SSL_CONF_CTX_set_flags(
+            cctx.0,
+            ffi::SSL_CONF_FLAG_FILE
+                | ffi::SSL_CONF_FLAG_CLIENT
+                | ffi::SSL_CONF_FLAG_SERVER
+                | ffi::SSL_CONF_FLAG_CERTIFICATE
+                | ffi::SSL_CONF_FLAG_SHOW_ERRORS,
+        );
+        ffi::SSL_CONF_CTX_set_ssl_ctx(cctx.0, ctx.as_ptr());
+        Ok(cctx)
+    }
+
+    unsafe fn check(&self, cmd: &CStr) -> Result<(), ErrorStack> {
+        if ffi::SSL_CONF_cmd_value_type(self.0, cmd.as_ptr()) == ffi::SSL_CONF_TYPE_UNKNOWN {
+            return Err(ErrorStack::internal(
+                ffi::ERR_LIB_SSL,
+                ffi::SSL_R_UNKNOWN_CMD_NAME,
+                Some(format!("cmd={}", cmd.to_string_lossy())),
+            ));
+        }
+        Ok(())
+    }
+
+    unsafe fn cmd(&self, cmd: &CStr, value: &CStr) -> Result<(), ErrorStack> {
+        // returns -2 for unknown commands and 0 or -3 for invalid values
+        if ffi::SSL_CONF_cmd(self.0, cmd.as_ptr(), value.as_ptr()) > 0 {
+            Ok(())
+        } else {
+            Err(ErrorStack::get())
+        }
+    }
+
+    unsafe fn finish(&self) -> Result<(), ErrorStack> {
+        cvt(ffi::SSL_CONF_CTX_finish(self.0)).map(|_| ())
+    }
+}
+
+#[cfg(ossl102)]
+impl Drop for SslConfCtx {
+    fn drop(&mut self) {
+        unsafe { ffi::SSL_CONF_CTX_free(self.0) }
     }
 }
 
@@ -2034,6 +2693,25 @@ pub struct CipherBits {
     pub algorithm: i32,
 }
 
+/// A signature algorithm supported by both peers of a connection.
+///
+/// Requires OpenSSL 1.0.2 or newer.
+#[cfg(ossl102)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SharedSigalg {
+    /// The signature algorithm, or `Nid::UNDEF` if it is not known to OpenSSL.
+    pub sign: Nid,
+
+    /// The digest algorithm, or `Nid::UNDEF` if it is not known to OpenSSL or not applicable.
+    pub hash: Nid,
+
+    /// The combined signature and digest algorithm, or `Nid::UNDEF` if there is none.
+    pub sign_and_hash: Nid,
+
+    /// The TLS `SignatureScheme` code point of the algorithm.
+    pub scheme: u16,
+}
+
 /// Information about a cipher.
 pub struct SslCipher(*mut ffi::SSL_CIPHER);
 
@@ -2066,6 +2744,10 @@ impl DerefMut for SslCipher {
     }
 }
 
+impl Stackable for SslCipher {
+    type StackType = ffi::stack_st_SSL_CIPHER;
+}
+
 /// Reference to an [`SslCipher`].
 ///
 /// [`SslCipher`]: struct.SslCipher.html
@@ -2178,7 +2860,7 @@ impl SslCipherRef {
     /// This corresponds to [`SSL_CIPHER_get_cipher_nid`].
     ///
     /// [`SSL_CIPHER_get_cipher_nid`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_CIPHER_get_cipher_nid.html
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     pub fn cipher_nid(&self) -> Option<Nid> {
         let n = unsafe { ffi::SSL_CIPHER_get_cipher_nid(self.as_ptr()) };
         if n == 0 {
@@ -2221,6 +2903,104 @@ impl SslSession {
         SslSession,
         ffi::d2i_SSL_SESSION
     }
+
+    /// Returns a builder for a new session.
+    ///
+    /// This is used to construct sessions for TLS 1.3 external pre-shared keys.
+    pub fn builder() -> Result<SslSessionBuilder, ErrorStack> {
+        SslSessionBuilder::new()
+    }
+}
+
+/// A builder for `SslSession`s.
+pub struct SslSessionBuilder(SslSession);
+
+impl SslSessionBuilder {
+    /// Creates a new, empty session.
+    ///
+    /// This corresponds to [`SSL_SESSION_new`].
+    ///
+    /// [`SSL_SESSION_new`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_new.html
+    pub fn new() -> Result<SslSessionBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::SSL_SESSION_new()).map(|p| SslSessionBuilder(SslSession::from_ptr(p)))
+        }
+    }
+
+    /// Sets the session's master key.
+    ///
+    /// For TLS 1.3 external pre-shared keys, this is the pre-shared key itself.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_set1_master_key`].
+    ///
+    /// [`SSL_SESSION_set1_master_key`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set1_master_key.html
+    #[cfg(ossl111)]
+    pub fn set_master_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set1_master_key(
+                self.0.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the session's cipher.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_set_cipher`].
+    ///
+    /// [`SSL_SESSION_set_cipher`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set_cipher.html
+    #[cfg(ossl111)]
+    pub fn set_cipher(&mut self, cipher: &SslCipherRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set_cipher(
+                self.0.as_ptr(),
+                cipher.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the session's TLS protocol version.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_set_protocol_version`].
+    ///
+    /// [`SSL_SESSION_set_protocol_version`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set_protocol_version.html
+    #[cfg(ossl111)]
+    pub fn set_protocol_version(&mut self, version: SslVersion) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set_protocol_version(
+                self.0.as_ptr(),
+                version.0,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the maximum amount of early data that can be sent on this session.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_set_max_early_data`].
+    ///
+    /// [`SSL_SESSION_set_max_early_data`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set_max_early_data.html
+    #[cfg(ossl111)]
+    pub fn set_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_SESSION_set_max_early_data(self.0.as_ptr(), bytes)).map(|_| ()) }
+    }
+
+    /// Consumes the builder, returning the session.
+    pub fn build(self) -> SslSession {
+        self.0
+    }
 }
 
 impl ToOwned for SslSessionRef {
@@ -2567,7 +3347,7 @@ impl SslRef {
     /// This corresponds to `SSL_set_tmp_ecdh_callback`.
     ///
     /// [`SslContextBuilder::set_tmp_ecdh_callback`]: struct.SslContextBuilder.html#method.set_tmp_ecdh_callback
-    #[cfg(any(all(ossl101, not(ossl110))))]
+    #[cfg(all(ossl101, not(ossl110)))]
     pub fn set_tmp_ecdh_callback<F>(&mut self, callback: F)
     where
         F: Fn(&mut SslRef, bool, u32) -> Result<EcKey<Params>, ErrorStack> + 'static + Sync + Send,
@@ -2631,6 +3411,302 @@ impl SslRef {
         }
     }
 
+    /// Looks up a cipher by its two byte IANA identifier.
+    ///
+    /// For example, `[0x13, 0x01]` identifies `TLS_AES_128_GCM_SHA256`. Returns `None` if the
+    /// cipher is not supported.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CIPHER_find`].
+    ///
+    /// [`SSL_CIPHER_find`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CIPHER_find.html
+    #[cfg(ossl102)]
+    pub fn find_cipher(&self, id: [u8; 2]) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_CIPHER_find(self.as_ptr(), id.as_ptr());
+            SslCipherRef::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the ciphers offered by the client.
+    ///
+    /// This is only available on the server side, once the client's hello message has been
+    /// processed. Otherwise, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_get_client_ciphers`].
+    ///
+    /// [`SSL_get_client_ciphers`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_client_ciphers.html
+    #[cfg(ossl110)]
+    pub fn client_ciphers(&self) -> Option<&StackRef<SslCipher>> {
+        unsafe {
+            let ptr = ffi::SSL_get_client_ciphers(self.as_ptr());
+            StackRef::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the enabled ciphers which can actually be negotiated by the connection.
+    ///
+    /// Unlike the configured cipher list, this excludes ciphers which are unusable with the
+    /// connection's protocol version range or, on the server side, its certificates. On the client
+    /// side, these are the ciphers sent in the hello message.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_get1_supported_ciphers`].
+    ///
+    /// [`SSL_get1_supported_ciphers`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get1_supported_ciphers.html
+    #[cfg(ossl110)]
+    pub fn supported_ciphers(&self) -> Option<Stack<SslCipher>> {
+        unsafe {
+            let ptr = ffi::SSL_get1_supported_ciphers(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Stack::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the names of the ciphers supported by both the client and the server, separated by
+    /// `:`.
+    ///
+    /// This is only available on the server side. Otherwise, `None` is returned.
+    ///
+    /// This corresponds to [`SSL_get_shared_ciphers`].
+    ///
+    /// [`SSL_get_shared_ciphers`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_shared_ciphers.html
+    pub fn shared_ciphers(&self) -> Option<String> {
+        unsafe {
+            // The list is silently truncated if the buffer is too small, so make sure it will fit
+            // every cipher suite OpenSSL knows about.
+            let mut buf = vec![0; 16 * 1024];
+            let ptr =
+                ffi::SSL_get_shared_ciphers(self.as_ptr(), buf.as_mut_ptr(), buf.len() as c_int);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    /// Returns the signature algorithms supported by both peers, in order of preference.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_get_shared_sigalgs`].
+    ///
+    /// [`SSL_get_shared_sigalgs`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_shared_sigalgs.html
+    #[cfg(ossl102)]
+    pub fn shared_sigalgs(&self) -> Vec<SharedSigalg> {
+        let mut sigalgs = vec![];
+
+        loop {
+            let mut sign = 0;
+            let mut hash = 0;
+            let mut sign_and_hash = 0;
+            let mut rsig = 0;
+            let mut rhash = 0;
+            let count = unsafe {
+                ffi::SSL_get_shared_sigalgs(
+                    self.as_ptr(),
+                    sigalgs.len() as c_int,
+                    &mut sign,
+                    &mut hash,
+                    &mut sign_and_hash,
+                    &mut rsig,
+                    &mut rhash,
+                )
+            };
+            if sigalgs.len() >= count as usize {
+                break;
+            }
+
+            sigalgs.push(SharedSigalg {
+                sign: Nid::from_raw(sign),
+                hash: Nid::from_raw(hash),
+                sign_and_hash: Nid::from_raw(sign_and_hash),
+                scheme: (u16::from(rhash) << 8) | u16::from(rsig),
+            });
+        }
+
+        sigalgs
+    }
+
+    /// Returns the digest used by the peer to sign the handshake.
+    ///
+    /// Returns `None` if the peer did not sign the handshake.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_get_peer_signature_nid`].
+    ///
+    /// [`SSL_get_peer_signature_nid`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_peer_signature_nid.html
+    #[cfg(ossl102)]
+    pub fn peer_signature_nid(&self) -> Option<Nid> {
+        unsafe {
+            let mut nid = 0;
+            if ffi::SSL_get_peer_signature_nid(self.as_ptr(), &mut nid) > 0 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns the signature type used by the peer to sign the handshake.
+    ///
+    /// Returns `None` if the peer did not sign the handshake.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_peer_signature_type_nid`].
+    ///
+    /// [`SSL_get_peer_signature_type_nid`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_peer_signature_nid.html
+    #[cfg(ossl111)]
+    pub fn peer_signature_type_nid(&self) -> Option<Nid> {
+        unsafe {
+            let mut nid = 0;
+            if ffi::SSL_get_peer_signature_type_nid(self.as_ptr(), &mut nid) > 0 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns the ephemeral key sent by the peer for key exchange.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_peer_tmp_key`].
+    ///
+    /// [`SSL_get_peer_tmp_key`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_peer_tmp_key.html
+    #[cfg(ossl111)]
+    pub fn peer_tmp_key(&self) -> Result<PKey<Public>, ErrorStack> {
+        unsafe {
+            let mut key = ptr::null_mut();
+            cvt(ffi::SSL_get_peer_tmp_key(self.as_ptr(), &mut key) as c_int)?;
+            Ok(PKey::from_ptr(key))
+        }
+    }
+
+    /// Returns the group used for key exchange.
+    ///
+    /// Groups not known to OpenSSL are reported as `TLSEXT_nid_unknown` (`0x1000000`) combined with
+    /// the group's TLS code point.
+    ///
+    /// On OpenSSL 1.1.1 only the server can determine the group, and this returns `None` on the
+    /// client side of a connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_negotiated_group`] on OpenSSL 3.0.0 and newer, and to
+    /// [`SSL_get_shared_group`] with an index of 0 otherwise.
+    ///
+    /// [`SSL_get_negotiated_group`]: https://www.openssl.org/docs/man3.0/man3/SSL_get_negotiated_group.html
+    /// [`SSL_get_shared_group`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_shared_group.html
+    #[cfg(ossl111)]
+    pub fn negotiated_group(&self) -> Option<Nid> {
+        unsafe {
+            #[cfg(ossl300)]
+            let nid = ffi::SSL_get_negotiated_group(self.as_ptr());
+            #[cfg(not(ossl300))]
+            let nid = ffi::SSL_get_shared_group(self.as_ptr(), 0);
+
+            if nid == 0 {
+                None
+            } else {
+                Some(Nid::from_raw(nid))
+            }
+        }
+    }
+
+    /// Sets the certificate compression algorithms the connection is willing to use, in order of
+    /// preference.
+    ///
+    /// The algorithm the peer used to compress its certificates is reported by `peer_cert_comp`.
+    /// This is implemented with a message callback, which replaces any callback configured with
+    /// `SSL_set_msg_callback`.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    ///
+    /// This corresponds to [`SSL_set1_cert_comp_preference`].
+    ///
+    /// [`SSL_set1_cert_comp_preference`]: https://www.openssl.org/docs/man3.2/man3/SSL_set1_cert_comp_preference.html
+    #[cfg(ossl320)]
+    pub fn set_cert_comp_preference(
+        &mut self,
+        algs: &[CertCompressionAlgorithm],
+    ) -> Result<(), ErrorStack> {
+        let mut algs = algs.iter().map(|a| a.0).collect::<Vec<_>>();
+        unsafe {
+            cvt(ffi::SSL_set1_cert_comp_preference(
+                self.as_ptr(),
+                algs.as_mut_ptr(),
+                algs.len(),
+            ))?;
+        }
+        self.enable_msg_observers(MsgObservers::CERT_COMP);
+        Ok(())
+    }
+
+    /// Pre-compresses the connection's certificate chain.
+    ///
+    /// If `alg` is `None`, the chain is compressed with every algorithm in the preference list.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    ///
+    /// This corresponds to [`SSL_compress_certs`].
+    ///
+    /// [`SSL_compress_certs`]: https://www.openssl.org/docs/man3.2/man3/SSL_compress_certs.html
+    #[cfg(ossl320)]
+    pub fn compress_certs(
+        &mut self,
+        alg: Option<CertCompressionAlgorithm>,
+    ) -> Result<(), ErrorStack> {
+        let alg = alg.map_or(ffi::TLSEXT_comp_cert_none, |a| a.0);
+        unsafe { cvt(ffi::SSL_compress_certs(self.as_ptr(), alg)).map(|_| ()) }
+    }
+
+    /// Returns the algorithm the peer used to compress its certificate chain, if it was
+    /// compressed.
+    ///
+    /// This is only recorded if certificate compression preferences were set on the context or
+    /// connection with `set_cert_comp_preference`, as OpenSSL itself does not report it.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[cfg(ossl320)]
+    pub fn peer_cert_comp(&self) -> Option<CertCompressionAlgorithm> {
+        self.ex_data(Ssl::cached_ex_index::<CertCompressionAlgorithm>())
+            .cloned()
+    }
+
+    /// Adds observers to the connection's internal message callback, installing it if necessary.
+    ///
+    /// The connection starts out with the observers enabled on its context.
+    #[cfg(ossl320)]
+    fn enable_msg_observers(&mut self, observers: MsgObservers) {
+        let idx = Ssl::cached_ex_index::<MsgObservers>();
+        match self.ex_data_mut(idx) {
+            Some(enabled) => *enabled |= observers,
+            None => {
+                let inherited = self
+                    .ssl_context()
+                    .ex_data(SslContext::cached_ex_index::<MsgObservers>())
+                    .cloned()
+                    .unwrap_or_else(MsgObservers::empty);
+                self.set_ex_data(idx, inherited | observers);
+            }
+        }
+        unsafe {
+            ffi::SSL_set_msg_callback(self.as_ptr(), Some(raw_msg));
+        }
+    }
+
     /// Returns a short string describing the state of the session.
     ///
     /// This corresponds to [`SSL_state_string`].
@@ -2974,7 +4050,7 @@ impl SslRef {
     /// This corresponds to [`SSL_get_client_random`].
     ///
     /// [`SSL_get_client_random`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_client_random.html
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     pub fn client_random(&self, buf: &mut [u8]) -> usize {
         unsafe {
             ffi::SSL_get_client_random(self.as_ptr(), buf.as_mut_ptr() as *mut c_uchar, buf.len())
@@ -2991,7 +4067,7 @@ impl SslRef {
     /// This corresponds to [`SSL_get_server_random`].
     ///
     /// [`SSL_get_server_random`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_client_random.html
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     pub fn server_random(&self, buf: &mut [u8]) -> usize {
         unsafe {
             ffi::SSL_get_server_random(self.as_ptr(), buf.as_mut_ptr() as *mut c_uchar, buf.len())
@@ -3226,6 +4302,44 @@ impl SslRef {
         unsafe { ffi::SSL_get_max_early_data(self.as_ptr()) }
     }
 
+    /// Sets the maximum amount of early data that will actually be read on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_set_recv_max_early_data`].
+    ///
+    /// [`SSL_set_recv_max_early_data`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_set_recv_max_early_data.html
+    #[cfg(ossl111)]
+    pub fn set_recv_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_recv_max_early_data(self.as_ptr(), bytes)).map(|_| ()) }
+    }
+
+    /// Gets the maximum amount of early data that will actually be read on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_recv_max_early_data`].
+    ///
+    /// [`SSL_get_recv_max_early_data`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_recv_max_early_data.html
+    #[cfg(ossl111)]
+    pub fn recv_max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_get_recv_max_early_data(self.as_ptr()) }
+    }
+
+    /// Returns whether early data was sent and accepted on this connection.
+    ///
+    /// This is only meaningful once the handshake has completed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_early_data_status`].
+    ///
+    /// [`SSL_get_early_data_status`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_early_data_status.html
+    #[cfg(ossl111)]
+    pub fn early_data_status(&self) -> EarlyDataStatus {
+        unsafe { EarlyDataStatus(ffi::SSL_get_early_data_status(self.as_ptr())) }
+    }
+
     /// Copies the contents of the last Finished message sent to the peer into the provided buffer.
     ///
     /// The total size of the message is returned, so this can be used to determine the size of the
@@ -3382,6 +4496,243 @@ impl SslRef {
         }
     }
 
+    /// Returns the types of the extensions present in the client's hello message, in the order in
+    /// which they were sent.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_client_hello_get1_extensions_present`].
+    ///
+    /// [`SSL_client_hello_get1_extensions_present`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_client_hello_cb.html
+    #[cfg(ossl111)]
+    pub fn client_hello_ext_present(&self) -> Option<Vec<TlsExtType>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get1_extensions_present(self.as_ptr(), &mut ptr, &mut len) != 1
+            {
+                return None;
+            }
+
+            let exts = if len == 0 {
+                vec![]
+            } else {
+                slice::from_raw_parts(ptr, len)
+                    .iter()
+                    .map(|&ext| TlsExtType(ext as u16))
+                    .collect()
+            };
+            ffi::CRYPTO_free(
+                ptr as *mut c_void,
+                concat!(file!(), "\0").as_ptr() as *const c_char,
+                line!() as c_int,
+            );
+            Some(exts)
+        }
+    }
+
+    /// Returns the body of an extension of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, or if the extension
+    /// is not present, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_client_hello_get0_ext`].
+    ///
+    /// [`SSL_client_hello_get0_ext`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_client_hello_cb.html
+    #[cfg(ossl111)]
+    pub fn client_hello_ext(&self, ext_type: TlsExtType) -> Option<&[u8]> {
+        unsafe {
+            let mut ptr = ptr::null();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get0_ext(
+                self.as_ptr(),
+                c_uint::from(ext_type.0),
+                &mut ptr,
+                &mut len,
+            ) != 1
+            {
+                return None;
+            }
+
+            if len == 0 {
+                Some(&[])
+            } else {
+                Some(slice::from_raw_parts(ptr, len))
+            }
+        }
+    }
+
+    /// Returns the host name sent in the `server_name` extension of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. `None` is returned if the
+    /// extension is not present or is malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_server_name(&self) -> Option<&str> {
+        self.client_hello_ext(TlsExtType::SERVER_NAME)
+            .and_then(client_hello::server_name)
+    }
+
+    /// Returns the protocols sent in the ALPN extension of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. `None` is returned if the
+    /// extension is not present or is malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_alpn_protocols(&self) -> Option<Vec<&[u8]>> {
+        self.client_hello_ext(TlsExtType::ALPN)
+            .and_then(client_hello::alpn_protocols)
+    }
+
+    /// Returns the raw protocol versions sent in the `supported_versions` extension of the client's
+    /// hello message.
+    ///
+    /// This can only be used inside of the client hello callback. `None` is returned if the
+    /// extension is not present or is malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_supported_versions(&self) -> Option<Vec<u16>> {
+        self.client_hello_ext(TlsExtType::SUPPORTED_VERSIONS)
+            .and_then(client_hello::supported_versions)
+    }
+
+    /// Returns the TLS code points of the groups sent in the `supported_groups` extension of the
+    /// client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. `None` is returned if the
+    /// extension is not present or is malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_supported_groups(&self) -> Option<Vec<u16>> {
+        self.client_hello_ext(TlsExtType::SUPPORTED_GROUPS)
+            .and_then(client_hello::u16_list)
+    }
+
+    /// Returns the TLS `SignatureScheme` code points sent in the `signature_algorithms` extension
+    /// of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. `None` is returned if the
+    /// extension is not present or is malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_signature_algorithms(&self) -> Option<Vec<u16>> {
+        self.client_hello_ext(TlsExtType::SIGNATURE_ALGORITHMS)
+            .and_then(client_hello::u16_list)
+    }
+
+    /// Returns the entries of the `key_share` extension of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. `None` is returned if the
+    /// extension is not present or is malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_key_shares(&self) -> Option<Vec<KeyShareEntry<'_>>> {
+        let shares = self
+            .client_hello_ext(TlsExtType::KEY_SHARE)
+            .and_then(client_hello::key_shares)?;
+        Some(
+            shares
+                .into_iter()
+                .map(|(group, key_exchange)| KeyShareEntry {
+                    group,
+                    key_exchange,
+                })
+                .collect(),
+        )
+    }
+
+    /// Computes the JA3 and JA4 fingerprints of the client's hello message and stores them in the
+    /// `Ssl`, returning them.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// Unlike `SslContextBuilder::enable_client_hello_fingerprint`, this uses OpenSSL's decoded
+    /// view of the message, which omits extensions unknown to OpenSSL.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn record_client_hello_fingerprint(&mut self) -> Option<&ClientHelloFingerprint> {
+        let fingerprint = ClientHelloFingerprint::from_client_hello(self)?;
+        self.set_client_hello_fingerprint(fingerprint);
+        self.client_hello_fingerprint()
+    }
+
+    #[cfg(ossl111)]
+    fn set_client_hello_fingerprint(&mut self, fingerprint: ClientHelloFingerprint) {
+        // a HelloRetryRequest results in a second client hello
+        let idx = Ssl::cached_ex_index::<ClientHelloFingerprint>();
+        match self.ex_data_mut(idx) {
+            Some(old) => *old = fingerprint,
+            None => self.set_ex_data(idx, fingerprint),
+        }
+    }
+
+    /// Returns the fingerprints of the client's hello message, if they have been recorded.
+    ///
+    /// See `SslContextBuilder::enable_client_hello_fingerprint`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_fingerprint(&self) -> Option<&ClientHelloFingerprint> {
+        self.ex_data(Ssl::cached_ex_index())
+    }
+
+    /// Sets the security level of the connection.
+    ///
+    /// See `SslContextBuilder::set_security_level` for details.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_set_security_level`].
+    ///
+    /// [`SSL_set_security_level`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_set_security_level.html
+    #[cfg(ossl110)]
+    pub fn set_security_level(&mut self, level: u32) {
+        unsafe { ffi::SSL_set_security_level(self.as_ptr(), level as c_int) }
+    }
+
+    /// Returns the security level of the connection.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_get_security_level`].
+    ///
+    /// [`SSL_get_security_level`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_security_level.html
+    #[cfg(ossl110)]
+    pub fn security_level(&self) -> u32 {
+        unsafe { ffi::SSL_get_security_level(self.as_ptr()) as u32 }
+    }
+
+    /// Returns statistics about the connection.
+    ///
+    /// Handshake timings and record counts are only available if the context was configured with
+    /// `SslContextBuilder::enable_connection_stats`.
+    ///
+    /// This corresponds to [`SSL_total_renegotiations`] and [`SSL_session_reused`], in addition
+    /// to the statistics collected by the context.
+    ///
+    /// [`SSL_total_renegotiations`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_total_renegotiations.html
+    /// [`SSL_session_reused`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_session_reused.html
+    pub fn stats(&self) -> SslStats {
+        let mut stats = self
+            .ex_data(Ssl::cached_ex_index::<Mutex<SslStats>>())
+            .map(|stats| stats.lock().unwrap_or_else(|e| e.into_inner()).clone())
+            .unwrap_or_default();
+        stats.renegotiations = unsafe { ffi::SSL_total_renegotiations(self.as_ptr()) as u64 };
+        stats.session_reused = self.session_reused();
+        stats
+    }
+
     /// Sets the MTU used for DTLS connections.
     ///
     /// This corresponds to `SSL_set_mtu`.
@@ -0,0 +1,180 @@
+use std::time::{Duration, Instant, SystemTime};
+
+/// Statistics about a single TLS connection.
+///
+/// Handshake timings and record counts are only collected if the context was configured with
+/// [`SslContextBuilder::enable_connection_stats`]. Otherwise, they are reported as `None` and 0
+/// respectively.
+///
+/// [`SslContextBuilder::enable_connection_stats`]: struct.SslContextBuilder.html#method.enable_connection_stats
+#[derive(Debug, Clone, Default)]
+pub struct SslStats {
+    pub(crate) handshake_start: Option<(SystemTime, Instant)>,
+    pub(crate) handshake_finish: Option<(SystemTime, Instant)>,
+    pub(crate) records_read: u64,
+    pub(crate) records_written: u64,
+    pub(crate) bytes_read: u64,
+    pub(crate) bytes_written: u64,
+    pub(crate) renegotiations: u64,
+    pub(crate) session_reused: bool,
+}
+
+impl SslStats {
+    /// Returns the time at which the initial handshake started.
+    pub fn handshake_start(&self) -> Option<SystemTime> {
+        self.handshake_start.map(|(time, _)| time)
+    }
+
+    /// Returns the time at which the initial handshake finished.
+    pub fn handshake_finish(&self) -> Option<SystemTime> {
+        self.handshake_finish.map(|(time, _)| time)
+    }
+
+    /// Returns how long the initial handshake took, if it has finished.
+    pub fn handshake_duration(&self) -> Option<Duration> {
+        match (self.handshake_start, self.handshake_finish) {
+            (Some((_, start)), Some((_, finish))) => Some(finish.duration_since(start)),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of TLS records read from the peer.
+    pub fn records_read(&self) -> u64 {
+        self.records_read
+    }
+
+    /// Returns the number of TLS records written to the peer.
+    pub fn records_written(&self) -> u64 {
+        self.records_written
+    }
+
+    /// Returns the number of bytes read from the peer, including record headers.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns the number of bytes written to the peer, including record headers.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Returns the number of renegotiations performed on the connection.
+    pub fn renegotiations(&self) -> u64 {
+        self.renegotiations
+    }
+
+    /// Returns `true` if the connection resumed a previous session.
+    pub fn session_reused(&self) -> bool {
+        self.session_reused
+    }
+
+    pub(crate) fn record_handshake_start(&mut self) {
+        if self.handshake_start.is_none() {
+            self.handshake_start = Some((SystemTime::now(), Instant::now()));
+        }
+    }
+
+    pub(crate) fn record_handshake_done(&mut self) {
+        if self.handshake_finish.is_none() {
+            self.handshake_finish = Some((SystemTime::now(), Instant::now()));
+        }
+    }
+
+    pub(crate) fn record_header(&mut self, write: bool, header: &[u8]) {
+        // the record length is the last field of both TLS and DTLS record headers
+        let len = match header.len() {
+            len if len >= 2 => u16::from_be_bytes([header[len - 2], header[len - 1]]),
+            _ => return,
+        };
+        let bytes = header.len() as u64 + u64::from(len);
+
+        if write {
+            self.records_written += 1;
+            self.bytes_written += bytes;
+        } else {
+            self.records_read += 1;
+            self.bytes_read += bytes;
+        }
+    }
+}
+
+/// Session cache statistics of an `SslContext`.
+///
+/// The counters cover every connection created from the context.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct SslSessionStats {
+    pub(crate) number: u64,
+    pub(crate) connect: u64,
+    pub(crate) connect_good: u64,
+    pub(crate) connect_renegotiate: u64,
+    pub(crate) accept: u64,
+    pub(crate) accept_good: u64,
+    pub(crate) accept_renegotiate: u64,
+    pub(crate) hits: u64,
+    pub(crate) cb_hits: u64,
+    pub(crate) misses: u64,
+    pub(crate) timeouts: u64,
+    pub(crate) cache_full: u64,
+}
+
+impl SslSessionStats {
+    /// Returns the number of sessions currently in the internal session cache.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the number of client handshakes started.
+    pub fn connect(&self) -> u64 {
+        self.connect
+    }
+
+    /// Returns the number of client handshakes which completed successfully.
+    pub fn connect_good(&self) -> u64 {
+        self.connect_good
+    }
+
+    /// Returns the number of client renegotiations started.
+    pub fn connect_renegotiate(&self) -> u64 {
+        self.connect_renegotiate
+    }
+
+    /// Returns the number of server handshakes started.
+    pub fn accept(&self) -> u64 {
+        self.accept
+    }
+
+    /// Returns the number of server handshakes which completed successfully.
+    pub fn accept_good(&self) -> u64 {
+        self.accept_good
+    }
+
+    /// Returns the number of server renegotiations started.
+    pub fn accept_renegotiate(&self) -> u64 {
+        self.accept_renegotiate
+    }
+
+    /// Returns the number of sessions successfully resumed.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of sessions retrieved from the external session cache by a server.
+    pub fn cb_hits(&self) -> u64 {
+        self.cb_hits
+    }
+
+    /// Returns the number of sessions proposed by clients which were not found in the cache.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Returns the number of sessions proposed by clients which had timed out.
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Returns the number of sessions removed because the cache was full.
+    pub fn cache_full(&self) -> u64 {
+        self.cache_full
+    }
+}
@@ -1,6 +1,5 @@
 #![allow(unused_imports)]
 
-use std::env;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::{self, BufReader};
@@ -11,6 +10,8 @@ use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::Path;
 use std::process::{Child, ChildStdin, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(ossl111)]
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use tempdir::TempDir;
@@ -18,19 +19,23 @@ use tempdir::TempDir;
 use crate::dh::Dh;
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+use crate::nid::Nid;
 use crate::ocsp::{OcspResponse, OcspResponseStatus};
-use crate::pkey::PKey;
+use crate::pkey::{Id, PKey};
+use crate::sha::sha256;
 use crate::srtp::SrtpProfileId;
 use crate::ssl;
 use crate::ssl::test::server::Server;
 #[cfg(any(ossl110, ossl111, libressl261))]
 use crate::ssl::SslVersion;
 #[cfg(ossl111)]
-use crate::ssl::{ClientHelloResponse, ExtensionContext};
+use crate::ssl::{
+    ClientHelloResponse, ExtensionContext, KeylogSync, SslRef, SslSession, TlsExtType,
+};
 use crate::ssl::{
     Error, HandshakeError, MidHandshakeSslStream, ShutdownResult, ShutdownState, Ssl, SslAcceptor,
-    SslAcceptorBuilder, SslConnector, SslContext, SslContextBuilder, SslFiletype, SslMethod,
-    SslOptions, SslSessionCacheMode, SslStream, SslVerifyMode, StatusType,
+    SslAcceptorBuilder, SslConnector, SslConnectorBuilder, SslContext, SslContextBuilder,
+    SslFiletype, SslMethod, SslOptions, SslSessionCacheMode, SslStream, SslVerifyMode, StatusType,
 };
 #[cfg(ossl102)]
 use crate::x509::store::X509StoreBuilder;
@@ -295,7 +300,11 @@ fn state() {
     let server = Server::builder().build();
 
     let s = server.client().connect();
-    assert_eq!(s.ssl().state_string(), "SSLOK ");
+    if cfg!(ossl300) {
+        assert_eq!(s.ssl().state_string(), "SSLOK");
+    } else {
+        assert_eq!(s.ssl().state_string(), "SSLOK ");
+    }
     assert_eq!(
         s.ssl().state_string_long(),
         "SSL negotiation finished successfully"
@@ -746,6 +755,149 @@ fn connector_no_hostname_can_disable_verify() {
     s.read_exact(&mut [0]).unwrap();
 }
 
+fn spki_pin(pem: &[u8]) -> [u8; 32] {
+    let cert = X509::from_pem(pem).unwrap();
+    sha256(&cert.public_key_info_der().unwrap())
+}
+
+#[test]
+fn connector_spki_pin_leaf() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let s = server.connect_tcp();
+    let mut s = connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[[0; 32], spki_pin(CERT)])
+        .connect("foobar.com", s)
+        .unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_spki_pin_root() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let s = server.connect_tcp();
+    let mut s = connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[spki_pin(ROOT_CERT)])
+        .connect("foobar.com", s)
+        .unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_spki_pin_mismatch() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let s = server.connect_tcp();
+    connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[[0; 32]])
+        .connect("foobar.com", s)
+        .unwrap_err();
+}
+
+#[test]
+fn connector_spki_pin_verify_none() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_verify(SslVerifyMode::NONE);
+
+    let s = server.connect_tcp();
+    connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[[0; 32]])
+        .connect("foobar.com", s)
+        .unwrap_err();
+}
+
+#[test]
+fn connector_spki_pin_report_only() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let s = server.connect_tcp();
+    let mut s = connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[[0; 32]])
+        .pin_report_only(true)
+        .connect("foobar.com", s)
+        .unwrap();
+    assert_eq!(
+        s.ssl().verify_result(),
+        X509VerifyResult::APPLICATION_VERIFICATION
+    );
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_spki_pin_keeps_verify_callback() {
+    let server = Server::builder().build();
+
+    // The context's callback trusts the server even though its root is unknown.
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+
+    let s = server.connect_tcp();
+    let mut s = connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[spki_pin(CERT)])
+        .connect("foobar.com", s)
+        .unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_spki_pin_report_only_verify_callback_rejects() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_verify_callback(SslVerifyMode::PEER, |_, _| false);
+
+    let s = server.connect_tcp();
+    connector
+        .build()
+        .configure()
+        .unwrap()
+        .pin_spki_sha256(&[spki_pin(CERT)])
+        .pin_report_only(true)
+        .connect("foobar.com", s)
+        .unwrap_err();
+}
+
 fn test_mozilla_server(new: fn(SslMethod) -> Result<SslAcceptorBuilder, ErrorStack>) {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let port = listener.local_addr().unwrap().port();
@@ -798,6 +950,217 @@ fn connector_client_server_mozilla_modern_v5() {
     test_mozilla_server(SslAcceptor::mozilla_modern_v5);
 }
 
+#[test]
+fn connector_client_server_mozilla_old_v5() {
+    test_mozilla_server(SslAcceptor::mozilla_old_v5);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn connector_client_server_mozilla_old_v5_tls1() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let t = thread::spawn(move || {
+        let key = PKey::private_key_from_pem(KEY).unwrap();
+        let cert = X509::from_pem(CERT).unwrap();
+        let mut acceptor = SslAcceptor::mozilla_old_v5(SslMethod::tls()).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        acceptor.set_certificate(&cert).unwrap();
+        let acceptor = acceptor.build();
+        let stream = listener.accept().unwrap().0;
+        let mut stream = acceptor.accept(stream).unwrap();
+
+        assert_eq!(stream.ssl().version2(), Some(SslVersion::TLS1));
+        stream.write_all(b"hello").unwrap();
+    });
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector
+        .set_max_proto_version(Some(SslVersion::TLS1))
+        .unwrap();
+    connector.set_security_level(0);
+    let connector = connector.build();
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let mut stream = connector.connect("foobar.com", stream).unwrap();
+
+    let mut buf = [0; 5];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(b"hello", &buf);
+
+    t.join().unwrap();
+}
+
+#[cfg(ossl111)]
+fn test_connector_profile(
+    new: fn(SslMethod) -> Result<SslConnectorBuilder, ErrorStack>,
+) -> SslStream<TcpStream> {
+    let server = Server::builder().build();
+
+    let mut connector = new(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    let connector = connector.build();
+
+    let s = server.connect_tcp();
+    let mut s = connector.connect("foobar.com", s).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+    s
+}
+
+#[test]
+#[cfg(ossl111)]
+fn connector_modern() {
+    let s = test_connector_profile(SslConnector::modern);
+    assert_eq!(s.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert_eq!(s.ssl().security_level(), 2);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn connector_compat() {
+    let s = test_connector_profile(SslConnector::compat);
+    assert_eq!(s.ssl().security_level(), if cfg!(ossl300) { 0 } else { 1 });
+}
+
+#[test]
+#[cfg(ossl111)]
+fn connector_compat_legacy_server() {
+    for &version in &[SslVersion::TLS1, SslVersion::TLS1_1] {
+        let mut server = Server::builder();
+        server.ctx().set_max_proto_version(Some(version)).unwrap();
+        server.ctx().set_security_level(0);
+        let server = server.build();
+
+        let mut connector = SslConnector::compat(SslMethod::tls()).unwrap();
+        connector.set_ca_file("test/root-ca.pem").unwrap();
+        let connector = connector.build();
+
+        let s = server.connect_tcp();
+        let mut s = connector.connect("foobar.com", s).unwrap();
+        s.read_exact(&mut [0]).unwrap();
+        assert_eq!(s.ssl().version2(), Some(version));
+    }
+}
+
+#[test]
+#[cfg(ossl111)]
+fn connector_fips() {
+    let s = test_connector_profile(SslConnector::fips);
+    let cipher = s.ssl().current_cipher().unwrap().name();
+    assert!(
+        cipher.contains("AES") && cipher.contains("GCM"),
+        "{}",
+        cipher
+    );
+}
+
+#[test]
+#[cfg(ossl110)]
+fn security_level() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_security_level(3);
+    let ctx = ctx.build();
+    assert_eq!(ctx.security_level(), 3);
+
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert_eq!(ssl.security_level(), 3);
+    ssl.set_security_level(0);
+    assert_eq!(ssl.security_level(), 0);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn ciphers() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_cipher_list("ECDHE-RSA-AES128-GCM-SHA256:AES256-SHA")
+        .unwrap();
+    ctx.set_ciphersuites("TLS_AES_128_GCM_SHA256").unwrap();
+    ctx.set_max_proto_version(Some(SslVersion::TLS1_2)).unwrap();
+    let ctx = ctx.build();
+
+    let names = ctx
+        .ciphers()
+        .unwrap()
+        .iter()
+        .map(|c| c.name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "TLS_AES_128_GCM_SHA256",
+            "ECDHE-RSA-AES128-GCM-SHA256",
+            "AES256-SHA"
+        ]
+    );
+
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_connect_state();
+    let names = ssl
+        .supported_ciphers()
+        .unwrap()
+        .iter()
+        .map(|c| c.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["ECDHE-RSA-AES128-GCM-SHA256", "AES256-SHA"]);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn apply_conf_cmds() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.apply_conf_cmds(&[
+        ("MinProtocol", "TLSv1.3"),
+        ("Ciphersuites", "TLS_AES_256_GCM_SHA384"),
+    ])
+    .unwrap();
+    assert_eq!(ctx.min_proto_version(), Some(SslVersion::TLS1_3));
+
+    let err = ctx
+        .apply_conf_cmds(&[("MaxProtocol", "TLSv1.3"), ("NotACommand", "foo")])
+        .unwrap_err();
+    assert_eq!(err.errors()[0].reason(), Some("unknown cmd name"));
+    assert_eq!(err.errors()[0].data(), Some("cmd=NotACommand"));
+    assert_eq!(ctx.max_proto_version(), None);
+    assert!(ctx.apply_conf_cmds(&[("MinProtocol", "TLSv9")]).is_err());
+
+    let server = Server::builder().build();
+    let mut client = server.client();
+    client
+        .ctx()
+        .apply_conf_cmds(&[("Ciphersuites", "TLS_AES_256_GCM_SHA384")])
+        .unwrap();
+    let s = client.connect();
+    assert_eq!(
+        s.ssl().current_cipher().unwrap().standard_name(),
+        Some("TLS_AES_256_GCM_SHA384")
+    );
+}
+
+#[test]
+#[cfg(ossl110g)]
+fn apply_conf_section() {
+    use crate::conf::Conf;
+
+    let dir = TempDir::new("conf").unwrap();
+    let path = dir.path().join("openssl.cnf");
+    File::create(&path)
+        .unwrap()
+        .write_all(b"[tls_server]\nMinProtocol = TLSv1.2\nMaxProtocol = TLSv1.2\n")
+        .unwrap();
+    let conf = Conf::load_file(&path).unwrap();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.apply_conf_section(&conf, "tls_server").unwrap();
+    assert_eq!(ctx.min_proto_version(), Some(SslVersion::TLS1_2));
+    assert_eq!(ctx.max_proto_version(), Some(SslVersion::TLS1_2));
+
+    let err = ctx.apply_conf_section(&conf, "missing").unwrap_err();
+    assert_eq!(err.errors()[0].reason(), Some("no section"));
+    assert_eq!(err.errors()[0].data(), Some("section=missing"));
+}
+
 #[test]
 fn shutdown() {
     let mut server = Server::builder();
@@ -1340,6 +1703,303 @@ fn psk_ciphers() {
     assert!(CLIENT_CALLED.load(Ordering::SeqCst) && SERVER_CALLED.load(Ordering::SeqCst));
 }
 
+#[test]
+#[cfg(ossl111)]
+fn psk_session_tls13() {
+    const PSK: &[u8] = b"thisisaverysecurekeythisisaverys";
+    const CLIENT_IDENT: &[u8] = b"thisisaclient";
+
+    fn psk_session(ssl: &SslRef) -> Result<SslSession, ErrorStack> {
+        // TLS_AES_128_GCM_SHA256
+        let cipher = ssl.find_cipher([0x13, 0x01]).unwrap();
+        let mut session = SslSession::builder()?;
+        session.set_master_key(PSK)?;
+        session.set_cipher(cipher)?;
+        session.set_protocol_version(SslVersion::TLS1_3)?;
+        Ok(session.build())
+    }
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    // the PSK is only used if its hash matches the negotiated cipher's
+    server
+        .ctx()
+        .set_ciphersuites("TLS_AES_128_GCM_SHA256")
+        .unwrap();
+    server.ctx().set_psk_find_session_callback(|ssl, identity| {
+        if identity == CLIENT_IDENT {
+            psk_session(ssl).map(Some)
+        } else {
+            Ok(None)
+        }
+    });
+    server.io_cb(|s| assert!(s.ssl().session_reused()));
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    client.ctx().set_psk_use_session_callback(|ssl, md| {
+        assert!(md.is_none());
+        Ok(Some((CLIENT_IDENT.to_vec(), psk_session(ssl)?)))
+    });
+
+    let s = client.connect();
+    assert!(s.ssl().session_reused());
+    assert!(s.ssl().peer_certificate().is_none());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn psk_session_hello_retry() {
+    use std::sync::atomic::AtomicUsize;
+
+    const PSK: &[u8] = b"thisisaverysecurekeythisisaverys";
+    const CLIENT_IDENT: &[u8] = b"thisisaclient";
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn psk_session(ssl: &SslRef) -> Result<SslSession, ErrorStack> {
+        // TLS_AES_128_GCM_SHA256
+        let cipher = ssl.find_cipher([0x13, 0x01]).unwrap();
+        let mut session = SslSession::builder()?;
+        session.set_master_key(PSK)?;
+        session.set_cipher(cipher)?;
+        session.set_protocol_version(SslVersion::TLS1_3)?;
+        Ok(session.build())
+    }
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_ciphersuites("TLS_AES_128_GCM_SHA256")
+        .unwrap();
+    // force a HelloRetryRequest, which runs the client's callback twice
+    server.ctx().set_groups_list("P-256").unwrap();
+    server.ctx().set_psk_find_session_callback(|ssl, identity| {
+        if identity == CLIENT_IDENT {
+            psk_session(ssl).map(Some)
+        } else {
+            Ok(None)
+        }
+    });
+    server.io_cb(|s| assert!(s.ssl().session_reused()));
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    client.ctx().set_psk_use_session_callback(|ssl, _| {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        Ok(Some((CLIENT_IDENT.to_vec(), psk_session(ssl)?)))
+    });
+
+    let s = client.connect();
+    assert!(s.ssl().session_reused());
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn early_data() {
+    use crate::ssl::{EarlyDataAntiReplay, EarlyDataStatus};
+
+    const PSK: &[u8] = b"thisisaverysecurekeythisisaverys";
+    static ALLOW: AtomicBool = AtomicBool::new(true);
+
+    fn psk_session(ssl: &SslRef) -> Result<SslSession, ErrorStack> {
+        let cipher = ssl.find_cipher([0x13, 0x01]).unwrap();
+        let mut session = SslSession::builder()?;
+        session.set_master_key(PSK)?;
+        session.set_cipher(cipher)?;
+        session.set_protocol_version(SslVersion::TLS1_3)?;
+        session.set_max_early_data(1024)?;
+        Ok(session.build())
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_chain_file("test/cert.pem").unwrap();
+    ctx.set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    ctx.set_ciphersuites("TLS_AES_128_GCM_SHA256").unwrap();
+    ctx.set_max_early_data(1024).unwrap();
+    ctx.set_recv_max_early_data(1024).unwrap();
+    ctx.set_psk_find_session_callback(|ssl, _| psk_session(ssl).map(Some));
+    let replay = EarlyDataAntiReplay::default();
+    ctx.set_allow_early_data_callback(move |ssl| ALLOW.load(Ordering::SeqCst) && replay.check(ssl));
+    let ctx = ctx.build();
+    assert_eq!(ctx.recv_max_early_data(), 1024);
+
+    let guard = thread::spawn(move || {
+        for _ in 0..3 {
+            let stream = listener.accept().unwrap().0;
+            let mut ssl = Ssl::new(&ctx).unwrap();
+            ssl.set_accept_state();
+            let mut stream = SslStream::new(ssl, stream).unwrap();
+
+            let mut buf = [0; 32];
+            let mut len = 0;
+            loop {
+                match stream.read_early_data(&mut buf[len..]).unwrap() {
+                    0 => break,
+                    n => len += n,
+                }
+            }
+            stream.accept().unwrap();
+            stream.write_all(&[len as u8]).unwrap();
+            stream.write_all(&buf[..len]).unwrap();
+        }
+    });
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_psk_use_session_callback(|ssl, _| Ok(Some((b"client".to_vec(), psk_session(ssl)?))));
+    let ctx = ctx.build();
+
+    let connect = |early_data: &[u8]| {
+        let mut ssl = Ssl::new(&ctx).unwrap();
+        ssl.set_connect_state();
+        let mut stream = SslStream::new(ssl, TcpStream::connect(addr).unwrap()).unwrap();
+        stream.write_early_data(early_data).unwrap();
+        stream.connect().unwrap();
+        let mut len = [0];
+        stream.read_exact(&mut len).unwrap();
+        let mut buf = vec![0; usize::from(len[0])];
+        stream.read_exact(&mut buf).unwrap();
+        (stream.ssl().early_data_status(), buf)
+    };
+
+    assert_eq!(
+        connect(b"hello"),
+        (EarlyDataStatus::ACCEPTED, b"hello".to_vec())
+    );
+    // the second client hello has a different random, so it is not a replay
+    assert_eq!(
+        connect(b"world"),
+        (EarlyDataStatus::ACCEPTED, b"world".to_vec())
+    );
+
+    ALLOW.store(false, Ordering::SeqCst);
+    assert_eq!(connect(b"hello"), (EarlyDataStatus::REJECTED, vec![]));
+
+    guard.join().unwrap();
+}
+
+#[test]
+fn connection_stats() {
+    let mut server = Server::builder();
+    server.ctx().enable_connection_stats();
+    server.io_cb(|s| {
+        let stats = s.ssl().stats();
+        assert!(stats.handshake_duration().is_some());
+        assert!(!stats.session_reused());
+        assert_eq!(stats.renegotiations(), 0);
+
+        let session_stats = s.ssl().ssl_context().session_stats();
+        assert_eq!(session_stats.accept(), 1);
+        assert_eq!(session_stats.accept_good(), 1);
+        assert_eq!(session_stats.hits(), 0);
+    });
+    let server = server.build();
+
+    static INFO_CALLED: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "C" fn info_callback(_: *const ffi::SSL, _: libc::c_int, _: libc::c_int) {
+        INFO_CALLED.store(true, Ordering::SeqCst);
+    }
+
+    let mut client = server.client();
+    unsafe {
+        ffi::SSL_CTX_set_info_callback(client.ctx().as_ptr(), Some(info_callback));
+    }
+    client.ctx().enable_connection_stats();
+    // enabling statistics twice must not chain the info callback to itself
+    client.ctx().enable_connection_stats();
+    let s = client.connect();
+    assert!(INFO_CALLED.load(Ordering::SeqCst));
+
+    let stats = s.ssl().stats();
+    assert!(stats.handshake_start().unwrap() <= stats.handshake_finish().unwrap());
+    #[cfg(ossl110)]
+    {
+        assert!(stats.records_read() > 0);
+        assert!(stats.records_written() > 0);
+        assert!(stats.bytes_read() > 5 * stats.records_read());
+        assert!(stats.bytes_written() > 5 * stats.records_written());
+    }
+}
+
+#[test]
+#[cfg(ossl111)]
+fn keylog_file() {
+    let dir = TempDir::new("keylog").unwrap();
+    let path = dir.path().join("keys.log");
+
+    let server = Server::builder().build();
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_keylog_file(&path, KeylogSync::Never)
+        .unwrap();
+    client.connect();
+
+    let mut contents = String::new();
+    File::open(&path)
+        .unwrap()
+        .read_to_string(&mut contents)
+        .unwrap();
+    let labels = contents
+        .lines()
+        .map(|l| l.split(' ').next().unwrap())
+        .collect::<Vec<_>>();
+    assert!(labels.contains(&"CLIENT_HANDSHAKE_TRAFFIC_SECRET"));
+    assert!(labels.contains(&"CLIENT_TRAFFIC_SECRET_0"));
+    assert!(contents.lines().all(|l| l.split(' ').count() == 3));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn keylog_file_shared() {
+    let dir = TempDir::new("keylog").unwrap();
+    let path = dir.path().join("keys.log");
+
+    let a = ssl::keylog_file(&path).unwrap();
+    let b = ssl::keylog_file(&dir.path().join(".").join("keys.log")).unwrap();
+    assert!(Arc::ptr_eq(&a, &b));
+
+    drop((a, b));
+    let c = ssl::keylog_file(&path).unwrap();
+    let d = ssl::keylog_file(&dir.path().join("other.log")).unwrap();
+    assert!(!Arc::ptr_eq(&c, &d));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn keylog_file_from_var() {
+    let dir = TempDir::new("keylog").unwrap();
+    let path = dir.path().join("keys.log");
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    assert!(!ctx
+        .set_keylog_file_from_var(None, KeylogSync::Never)
+        .unwrap());
+    assert!(!ctx
+        .set_keylog_file_from_var(Some("".into()), KeylogSync::Never)
+        .unwrap());
+    assert!(ctx
+        .set_keylog_file_from_var(Some(path.clone().into()), KeylogSync::Never)
+        .unwrap());
+    assert!(path.exists());
+}
+
 #[test]
 fn sni_callback_swapped_ctx() {
     static CALLED_BACK: AtomicBool = AtomicBool::new(false);
@@ -1386,6 +2046,184 @@ fn client_hello() {
     assert!(CALLED_BACK.load(Ordering::SeqCst));
 }
 
+#[test]
+#[cfg(ossl320)]
+fn cert_compression() {
+    use crate::ssl::CertCompressionAlgorithm;
+
+    let algs = [
+        CertCompressionAlgorithm::ZSTD,
+        CertCompressionAlgorithm::BROTLI,
+        CertCompressionAlgorithm::ZLIB,
+    ];
+
+    let mut server = Server::builder();
+    // algorithms are only available if OpenSSL was built with the corresponding library
+    if server.ctx().set_cert_comp_preference(&algs).is_err() {
+        return;
+    }
+    server.io_cb(|s| assert_eq!(s.ssl().peer_cert_comp(), None));
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_cert_comp_preference(&algs).unwrap();
+    let s = client.connect();
+    let alg = s.ssl().peer_cert_comp().unwrap();
+    assert!(algs.contains(&alg));
+}
+
+#[test]
+#[cfg(ossl320)]
+fn compressed_certificate_alg() {
+    use crate::ssl::callbacks::compressed_certificate_alg;
+    use crate::ssl::CertCompressionAlgorithm;
+
+    let msg = [25, 0, 0, 8, 0, 2, 0, 0, 5, 0, 0, 1];
+    assert_eq!(
+        compressed_certificate_alg(&msg),
+        Some(CertCompressionAlgorithm::BROTLI)
+    );
+    let msg = [11, 0, 0, 4, 0, 0, 0, 0];
+    assert_eq!(compressed_certificate_alg(&msg), None);
+    assert_eq!(compressed_certificate_alg(&[25, 0, 0, 2, 0]), None);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn negotiated_parameters() {
+    let mut server = Server::builder();
+    server.ctx().set_groups_list("X25519:P-256").unwrap();
+    server.io_cb(|s| {
+        let ssl = s.ssl();
+        assert_eq!(ssl.negotiated_group(), Some(Nid::from_raw(ffi::NID_X25519)));
+        assert!(!ssl.client_ciphers().unwrap().is_empty());
+        assert!(ssl
+            .shared_ciphers()
+            .unwrap()
+            .split(':')
+            .any(|c| c == "TLS_AES_256_GCM_SHA384"));
+        assert!(!ssl.shared_sigalgs().is_empty());
+        assert_eq!(ssl.peer_tmp_key().unwrap().id(), Id::X25519);
+        assert_eq!(ssl.peer_signature_nid(), None);
+    });
+    let server = server.build();
+
+    let s = server.client().connect();
+    let ssl = s.ssl();
+    assert_eq!(ssl.peer_tmp_key().unwrap().id(), Id::X25519);
+    assert_eq!(ssl.peer_signature_nid(), Some(Nid::SHA256));
+    assert_eq!(ssl.peer_signature_type_nid(), Some(Nid::RSASSAPSS));
+    if cfg!(ossl300) {
+        assert_eq!(ssl.negotiated_group(), Some(Nid::from_raw(ffi::NID_X25519)));
+    } else {
+        assert_eq!(ssl.negotiated_group(), None);
+    }
+    assert!(ssl.client_ciphers().is_none());
+    assert!(ssl.shared_ciphers().is_none());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn client_hello_extensions() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_client_hello_callback(|ssl, _| {
+        let exts = ssl.client_hello_ext_present().unwrap();
+        assert!(exts.contains(&TlsExtType::SERVER_NAME));
+        assert!(exts.contains(&TlsExtType::KEY_SHARE));
+        assert!(ssl.client_hello_ext(TlsExtType::SERVER_NAME).is_some());
+        assert!(ssl.client_hello_ext(TlsExtType::from_raw(0x1234)).is_none());
+
+        assert_eq!(ssl.client_hello_server_name(), Some("foobar.com"));
+        assert_eq!(
+            ssl.client_hello_alpn_protocols(),
+            Some(vec![&b"h2"[..], &b"http/1.1"[..]])
+        );
+        assert!(ssl
+            .client_hello_supported_versions()
+            .unwrap()
+            .contains(&0x0304));
+        assert!(ssl
+            .client_hello_supported_groups()
+            .unwrap()
+            .contains(&0x001d));
+        assert!(ssl
+            .client_hello_signature_algorithms()
+            .unwrap()
+            .contains(&0x0804));
+        let shares = ssl.client_hello_key_shares().unwrap();
+        assert!(shares
+            .iter()
+            .any(|share| share.group == 0x001d && share.key_exchange.len() == 32));
+
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        Ok(ClientHelloResponse::SUCCESS)
+    });
+
+    let server = server.build();
+    let mut client = server.client();
+    client.ctx().set_alpn_protos(b"\x02h2\x08http/1.1").unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn client_hello_fingerprint() {
+    let mut server = Server::builder();
+    server.ctx().enable_client_hello_fingerprint();
+    // force a HelloRetryRequest
+    server.ctx().set_groups_list("P-256").unwrap();
+    server.io_cb(|s| {
+        let fingerprint = s.ssl().client_hello_fingerprint().unwrap();
+        assert!(fingerprint.ja3().starts_with("771,"));
+        // extensions unknown to the server are included
+        let extensions = fingerprint.ja3().split(',').nth(2).unwrap();
+        assert!(extensions.split('-').any(|e| e == "12345"));
+        assert_eq!(fingerprint.ja3_md5().len(), 32);
+        assert!(fingerprint.ja4().starts_with("t13d"));
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .add_custom_ext(
+            12345,
+            ExtensionContext::CLIENT_HELLO,
+            |_, _, _| Ok(Some(b"hello")),
+            |_, _, _, _| unreachable!(),
+        )
+        .unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    let s = client.connect();
+    assert!(s.ssl().client_hello_fingerprint().is_none());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn record_client_hello_fingerprint() {
+    let mut server = Server::builder();
+    server.ctx().set_client_hello_callback(|ssl, _| {
+        ssl.record_client_hello_fingerprint();
+        Ok(ClientHelloResponse::SUCCESS)
+    });
+    // force a HelloRetryRequest, which runs the callback twice
+    server.ctx().set_groups_list("P-256").unwrap();
+    server.io_cb(|s| {
+        let fingerprint = s.ssl().client_hello_fingerprint().unwrap();
+        assert!(fingerprint.ja4().starts_with("t13i"));
+    });
+    let server = server.build();
+
+    server.client().connect();
+}
+
 #[test]
 #[cfg(ossl111)]
 fn openssl_cipher_name() {
@@ -53,11 +53,19 @@
 //! ```
 
 use cfg_if::cfg_if;
-use libc::c_int;
+#[cfg(ossl300)]
+use foreign_types::ForeignTypeRef;
+use libc::{c_char, c_int, c_void};
 use std::cmp;
+use std::ffi::CString;
+use std::io::{self, Read, Write};
+#[cfg(ossl300)]
+use std::marker::PhantomData;
 use std::ptr;
 
 use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 use crate::nid::Nid;
 use crate::{cvt, cvt_p};
 
@@ -90,6 +98,58 @@ impl Cipher {
         }
     }
 
+    /// Looks up the cipher for a certain name, such as `"aes-128-cbc"` or one of its aliases.
+    ///
+    /// This corresponds to [`EVP_get_cipherbyname`]
+    ///
+    /// [`EVP_get_cipherbyname`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_get_cipherbyname.html
+    pub fn from_name(name: &str) -> Option<Cipher> {
+        ffi::init();
+        let name = CString::new(name).ok()?;
+        let ptr = unsafe { ffi::EVP_get_cipherbyname(name.as_ptr()) };
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Cipher(ptr))
+        }
+    }
+
+    /// Fetches a cipher implementation by name from a provider.
+    ///
+    /// The implementation is looked up in `ctx`, or the default library context if `None`, and
+    /// must match the property query `properties`, for example `"provider=fips"`. This also gives
+    /// access to ciphers which have no dedicated constructor in this crate, such as the CTS modes
+    /// like `AES-128-CBC-CTS`.
+    ///
+    /// The fetched implementation is released when the returned value is dropped. Use
+    /// [`Crypter::with_cipher`] to encrypt or decrypt with it.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// This corresponds to [`EVP_CIPHER_fetch`].
+    ///
+    /// [`Crypter::with_cipher`]: struct.Crypter.html#method.with_cipher
+    /// [`EVP_CIPHER_fetch`]: https://www.openssl.org/docs/man3.0/man3/EVP_CIPHER_fetch.html
+    #[cfg(ossl300)]
+    pub fn fetch<'a>(
+        ctx: Option<&'a LibCtxRef>,
+        algorithm: &str,
+        properties: Option<&str>,
+    ) -> Result<FetchedCipher<'a>, ErrorStack> {
+        ffi::init();
+
+        let algorithm = CString::new(algorithm).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        unsafe {
+            cvt_p(ffi::EVP_CIPHER_fetch(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                algorithm.as_ptr(),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            ))
+            .map(|cipher| FetchedCipher(cipher, PhantomData))
+        }
+    }
+
     pub fn aes_128_ecb() -> Cipher {
         unsafe { Cipher(ffi::EVP_aes_128_ecb()) }
     }
@@ -224,6 +284,72 @@ impl Cipher {
         unsafe { Cipher(ffi::EVP_aes_256_ocb()) }
     }
 
+    /// AES-128 key wrap, as described in RFC 3394.
+    ///
+    /// The entire input must be passed to a single call to `Crypter::update`. If no IV is
+    /// provided, the default IV is used.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub fn aes_128_wrap() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aes_128_wrap()) }
+    }
+
+    /// AES-128 key wrap with padding, as described in RFC 5649.
+    ///
+    /// The entire input must be passed to a single call to `Crypter::update`. If no IV is
+    /// provided, the default IV is used.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub fn aes_128_wrap_pad() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aes_128_wrap_pad()) }
+    }
+
+    /// AES-192 key wrap, as described in RFC 3394.
+    ///
+    /// The entire input must be passed to a single call to `Crypter::update`. If no IV is
+    /// provided, the default IV is used.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub fn aes_192_wrap() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aes_192_wrap()) }
+    }
+
+    /// AES-192 key wrap with padding, as described in RFC 5649.
+    ///
+    /// The entire input must be passed to a single call to `Crypter::update`. If no IV is
+    /// provided, the default IV is used.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub fn aes_192_wrap_pad() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aes_192_wrap_pad()) }
+    }
+
+    /// AES-256 key wrap, as described in RFC 3394.
+    ///
+    /// The entire input must be passed to a single call to `Crypter::update`. If no IV is
+    /// provided, the default IV is used.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub fn aes_256_wrap() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aes_256_wrap()) }
+    }
+
+    /// AES-256 key wrap with padding, as described in RFC 5649.
+    ///
+    /// The entire input must be passed to a single call to `Crypter::update`. If no IV is
+    /// provided, the default IV is used.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub fn aes_256_wrap_pad() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aes_256_wrap_pad()) }
+    }
+
     #[cfg(not(osslconf = "OPENSSL_NO_BF"))]
     pub fn bf_cbc() -> Cipher {
         unsafe { Cipher(ffi::EVP_bf_cbc()) }
@@ -269,17 +395,357 @@ impl Cipher {
     }
 
     /// Requires OpenSSL 1.1.0 or newer.
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     pub fn chacha20() -> Cipher {
         unsafe { Cipher(ffi::EVP_chacha20()) }
     }
 
     /// Requires OpenSSL 1.1.0 or newer.
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     pub fn chacha20_poly1305() -> Cipher {
         unsafe { Cipher(ffi::EVP_chacha20_poly1305()) }
     }
 
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_128_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_128_ecb()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_128_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_128_cbc()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_128_cfb1() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_128_cfb1()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_128_cfb8() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_128_cfb8()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_128_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_128_cfb128()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_128_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_128_ofb()) }
+    }
+
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CAMELLIA")))]
+    pub fn camellia_128_ctr() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_128_ctr()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_192_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_192_ecb()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_192_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_192_cbc()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_192_cfb1() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_192_cfb1()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_192_cfb8() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_192_cfb8()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_192_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_192_cfb128()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_192_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_192_ofb()) }
+    }
+
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CAMELLIA")))]
+    pub fn camellia_192_ctr() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_192_ctr()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_256_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_256_ecb()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_256_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_256_cbc()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_256_cfb1() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_256_cfb1()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_256_cfb8() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_256_cfb8()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_256_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_256_cfb128()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    pub fn camellia_256_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_256_ofb()) }
+    }
+
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CAMELLIA")))]
+    pub fn camellia_256_ctr() -> Cipher {
+        unsafe { Cipher(ffi::EVP_camellia_256_ctr()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_ecb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_cbc()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_cfb1() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_cfb1()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_cfb8() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_cfb8()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_cfb128()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_ctr() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_ctr()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_ofb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_gcm() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_gcm()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_128_ccm() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_128_ccm()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_ecb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_cbc()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_cfb1() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_cfb1()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_cfb8() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_cfb8()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_cfb128()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_ctr() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_ctr()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_ofb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_gcm() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_gcm()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_192_ccm() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_192_ccm()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_ecb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_cbc()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_cfb1() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_cfb1()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_cfb8() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_cfb8()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_cfb128()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_ctr() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_ctr()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_ofb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_gcm() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_gcm()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    pub fn aria_256_ccm() -> Cipher {
+        unsafe { Cipher(ffi::EVP_aria_256_ccm()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn sm4_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_sm4_ecb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn sm4_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_sm4_cbc()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn sm4_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_sm4_cfb128()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn sm4_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_sm4_ofb()) }
+    }
+
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    pub fn sm4_ctr() -> Cipher {
+        unsafe { Cipher(ffi::EVP_sm4_ctr()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn seed_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_seed_ecb()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn seed_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_seed_cbc()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn seed_cfb128() -> Cipher {
+        unsafe { Cipher(ffi::EVP_seed_cfb128()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    pub fn seed_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_seed_ofb()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn cast5_ecb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_cast5_ecb()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn cast5_cbc() -> Cipher {
+        unsafe { Cipher(ffi::EVP_cast5_cbc()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn cast5_cfb64() -> Cipher {
+        unsafe { Cipher(ffi::EVP_cast5_cfb64()) }
+    }
+
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    pub fn cast5_ofb() -> Cipher {
+        unsafe { Cipher(ffi::EVP_cast5_ofb()) }
+    }
+
     /// Creates a `Cipher` from a raw pointer to its OpenSSL type.
     ///
     /// # Safety
@@ -294,6 +760,46 @@ impl Cipher {
         self.0
     }
 
+    /// Returns every cipher known to the library, sorted by name.
+    ///
+    /// Each cipher is returned once, even if it is known by several names.
+    ///
+    /// This corresponds to [`EVP_CIPHER_do_all_sorted`].
+    ///
+    /// [`EVP_CIPHER_do_all_sorted`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_do_all_sorted.html
+    pub fn all() -> Vec<Cipher> {
+        ffi::init();
+
+        unsafe extern "C" fn callback(
+            cipher: *const ffi::EVP_CIPHER,
+            _: *const c_char,
+            _: *const c_char,
+            arg: *mut c_void,
+        ) {
+            let ciphers = &mut *(arg as *mut Vec<Cipher>);
+            // aliases are reported without a cipher
+            if !cipher.is_null() && !ciphers.iter().any(|c| c.0 == cipher) {
+                ciphers.push(Cipher(cipher));
+            }
+        }
+
+        let mut ciphers = vec![];
+        unsafe {
+            ffi::EVP_CIPHER_do_all_sorted(Some(callback), &mut ciphers as *mut _ as *mut c_void);
+        }
+        ciphers
+    }
+
+    /// Returns the `Nid` of the cipher.
+    ///
+    /// This corresponds to [`EVP_CIPHER_nid`].
+    ///
+    /// [`EVP_CIPHER_nid`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_nid.html
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn nid(&self) -> Nid {
+        Nid::from_raw(unsafe { ffi::EVP_CIPHER_nid(self.0) })
+    }
+
     /// Returns the length of keys used with this cipher.
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn key_len(&self) -> usize {
@@ -327,6 +833,15 @@ impl Cipher {
     /// Determines whether the cipher is using CCM mode
     fn is_ccm(self) -> bool {
         // NOTE: OpenSSL returns pointers to static structs, which makes this work as expected
+        #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+        {
+            if self == Cipher::aria_128_ccm()
+                || self == Cipher::aria_192_ccm()
+                || self == Cipher::aria_256_ccm()
+            {
+                return true;
+            }
+        }
         self == Cipher::aes_128_ccm() || self == Cipher::aes_256_ccm()
     }
 
@@ -342,11 +857,126 @@ impl Cipher {
     const fn is_ocb(self) -> bool {
         false
     }
+
+    /// Determines whether the cipher is an AES key wrap cipher
+    #[cfg(ossl102)]
+    fn is_wrap(self) -> bool {
+        self == Cipher::aes_128_wrap()
+            || self == Cipher::aes_128_wrap_pad()
+            || self == Cipher::aes_192_wrap()
+            || self == Cipher::aes_192_wrap_pad()
+            || self == Cipher::aes_256_wrap()
+            || self == Cipher::aes_256_wrap_pad()
+    }
+
+    #[cfg(not(ossl102))]
+    const fn is_wrap(self) -> bool {
+        false
+    }
+
+    /// Returns the length of the tag appended to streams by `CryptoReader` and `CryptoWriter`,
+    /// or 0 if the cipher is not an AEAD cipher.
+    fn stream_tag_len(self) -> usize {
+        #[cfg(ossl110)]
+        {
+            if self == Cipher::chacha20_poly1305() {
+                return 16;
+            }
+        }
+        #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+        {
+            if self == Cipher::aria_128_gcm()
+                || self == Cipher::aria_192_gcm()
+                || self == Cipher::aria_256_gcm()
+            {
+                return 16;
+            }
+        }
+        if self == Cipher::aes_128_gcm()
+            || self == Cipher::aes_192_gcm()
+            || self == Cipher::aes_256_gcm()
+            || self.is_ocb()
+        {
+            16
+        } else {
+            0
+        }
+    }
 }
 
 unsafe impl Sync for Cipher {}
 unsafe impl Send for Cipher {}
 
+/// A cipher implementation fetched from a provider with [`Cipher::fetch`].
+///
+/// The implementation is released when this value is dropped, and it cannot outlive the library
+/// context it was fetched from. Cloning it takes another reference to the same implementation.
+///
+/// Requires OpenSSL 3.0.0 or newer.
+///
+/// [`Cipher::fetch`]: struct.Cipher.html#method.fetch
+#[cfg(ossl300)]
+pub struct FetchedCipher<'a>(*mut ffi::EVP_CIPHER, PhantomData<&'a LibCtxRef>);
+
+#[cfg(ossl300)]
+unsafe impl<'a> Sync for FetchedCipher<'a> {}
+#[cfg(ossl300)]
+unsafe impl<'a> Send for FetchedCipher<'a> {}
+
+#[cfg(ossl300)]
+impl<'a> Drop for FetchedCipher<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_CIPHER_free(self.0) }
+    }
+}
+
+#[cfg(ossl300)]
+impl<'a> Clone for FetchedCipher<'a> {
+    fn clone(&self) -> FetchedCipher<'a> {
+        unsafe {
+            ffi::EVP_CIPHER_up_ref(self.0);
+        }
+        FetchedCipher(self.0, PhantomData)
+    }
+}
+
+#[cfg(ossl300)]
+impl<'a> FetchedCipher<'a> {
+    // The returned `Cipher` must not outlive `self`, so it is never handed out.
+    fn cipher(&self) -> Cipher {
+        Cipher(self.0)
+    }
+
+    pub fn as_ptr(&self) -> *mut ffi::EVP_CIPHER {
+        self.0
+    }
+
+    /// Returns the `Nid` of the cipher, or `Nid::UNDEF` if it has none.
+    pub fn nid(&self) -> Nid {
+        self.cipher().nid()
+    }
+
+    /// Returns the length of keys used with this cipher.
+    pub fn key_len(&self) -> usize {
+        self.cipher().key_len()
+    }
+
+    /// Returns the length of the IV used with this cipher, or `None` if the cipher does not use
+    /// an IV.
+    pub fn iv_len(&self) -> Option<usize> {
+        self.cipher().iv_len()
+    }
+
+    /// Returns the block size of the cipher.
+    ///
+    /// # Note
+    ///
+    /// Stream ciphers such as RC4 have a block size of 1.
+    pub fn block_size(&self) -> usize {
+        self.cipher().block_size()
+    }
+}
+
 /// Represents a symmetric cipher context.
 ///
 /// Padding is enabled by default.
@@ -410,6 +1040,12 @@ unsafe impl Send for Cipher {}
 pub struct Crypter {
     ctx: *mut ffi::EVP_CIPHER_CTX,
     block_size: usize,
+    wrap: bool,
+    aead: bool,
+    iv_len: usize,
+    // passing a null IV to EVP_CipherInit_ex does not restore the IV in every mode, so the
+    // current one is kept to restart the cipher
+    iv: Option<Vec<u8>>,
 }
 
 unsafe impl Sync for Crypter {}
@@ -433,9 +1069,17 @@ impl Crypter {
 
         unsafe {
             let ctx = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
-            let crypter = Crypter {
+            let mut crypter = Crypter {
                 ctx,
                 block_size: t.block_size(),
+                wrap: t.is_wrap(),
+                aead: ffi::EVP_CIPHER_flags(t.as_ptr()) & ffi::EVP_CIPH_FLAG_AEAD_CIPHER != 0,
+                iv_len: match (iv, t.iv_len()) {
+                    (Some(iv), Some(_)) => iv.len(),
+                    (None, Some(len)) => len,
+                    (_, None) => 0,
+                },
+                iv: None,
             };
 
             let mode = match mode {
@@ -443,6 +1087,10 @@ impl Crypter {
                 Mode::Decrypt => 0,
             };
 
+            if crypter.wrap {
+                ffi::EVP_CIPHER_CTX_set_flags(crypter.ctx, ffi::EVP_CIPHER_CTX_FLAG_WRAP_ALLOW);
+            }
+
             cvt(ffi::EVP_CipherInit_ex(
                 crypter.ctx,
                 t.as_ptr(),
@@ -470,9 +1118,12 @@ impl Crypter {
                             ptr::null_mut(),
                         ))?;
                     }
+                    crypter.iv = Some(iv.to_vec());
                     iv.as_ptr() as *mut _
                 }
                 (Some(_), None) | (None, None) => ptr::null_mut(),
+                // the key wrap ciphers fall back to their default IV
+                (None, Some(_)) if crypter.wrap => ptr::null_mut(),
                 (None, Some(_)) => panic!("an IV is required for this cipher"),
             };
             cvt(ffi::EVP_CipherInit_ex(
@@ -488,6 +1139,127 @@ impl Crypter {
         }
     }
 
+    /// Creates a new `Crypter` using a cipher fetched from a provider.
+    ///
+    /// The cipher context keeps its own reference to the cipher.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Crypter::new`].
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`Crypter::new`]: #method.new
+    #[cfg(ossl300)]
+    pub fn with_cipher(
+        cipher: &FetchedCipher<'_>,
+        mode: Mode,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<Crypter, ErrorStack> {
+        Crypter::new(cipher.cipher(), mode, key, iv)
+    }
+
+    /// Restarts the cipher with a new IV, keeping the key schedule.
+    ///
+    /// Any partially processed data is discarded. This avoids allocating and keying a new
+    /// `Crypter` for every message, for example when each packet is encrypted with its own IV.
+    ///
+    /// This corresponds to [`EVP_CipherInit_ex`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `iv` does not have the same length as the IV the `Crypter` was created with.
+    ///
+    /// [`EVP_CipherInit_ex`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CipherInit_ex.html
+    pub fn reinit(&mut self, iv: &[u8]) -> Result<(), ErrorStack> {
+        assert_eq!(iv.len(), self.iv_len, "IV has the wrong length");
+        if self.iv_len > 0 {
+            self.iv = Some(iv.to_vec());
+        }
+        self.restart(ptr::null())
+    }
+
+    /// Restarts the cipher with its current key and IV.
+    ///
+    /// Any partially processed data is discarded.
+    ///
+    /// This corresponds to [`EVP_CipherInit_ex`].
+    ///
+    /// Returns an error if the cipher is an AEAD cipher such as AES GCM, as reusing an IV would
+    /// break its security. AEAD ciphers must be restarted with a new IV using [`reinit`] instead.
+    ///
+    /// [`reinit`]: #method.reinit
+    /// [`EVP_CipherInit_ex`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CipherInit_ex.html
+    pub fn reset(&mut self) -> Result<(), ErrorStack> {
+        if self.aead {
+            return Err(ErrorStack::internal(
+                ffi::ERR_LIB_EVP,
+                ffi::ERR_R_PASSED_INVALID_ARGUMENT,
+                Some("AEAD ciphers must be restarted with reinit".to_string()),
+            ));
+        }
+        self.restart(ptr::null())
+    }
+
+    // Restarts the cipher with the current IV, and `key` if it is not null.
+    fn restart(&mut self, key: *const u8) -> Result<(), ErrorStack> {
+        let iv = self.iv.as_ref().map_or(ptr::null(), |iv| iv.as_ptr());
+        unsafe {
+            cvt(ffi::EVP_CipherInit_ex(
+                self.ctx,
+                ptr::null(),
+                ptr::null_mut(),
+                key,
+                iv,
+                -1,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Replaces the key and restarts the cipher with its current IV.
+    ///
+    /// Any partially processed data is discarded.
+    ///
+    /// This corresponds to [`EVP_CipherInit_ex`].
+    ///
+    /// [`EVP_CipherInit_ex`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CipherInit_ex.html
+    pub fn set_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(key.len() <= c_int::max_value() as usize);
+            cvt(ffi::EVP_CIPHER_CTX_set_key_length(
+                self.ctx,
+                key.len() as c_int,
+            ))?;
+        }
+        self.restart(key.as_ptr())
+    }
+
+    /// Returns a copy of the `Crypter`, including its key schedule and any partially processed
+    /// data.
+    ///
+    /// A keyed `Crypter` can be copied once per thread rather than each thread setting up its
+    /// own.
+    ///
+    /// This corresponds to [`EVP_CIPHER_CTX_copy`].
+    ///
+    /// [`EVP_CIPHER_CTX_copy`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_CTX_copy.html
+    pub fn copy(&self) -> Result<Crypter, ErrorStack> {
+        unsafe {
+            let crypter = Crypter {
+                ctx: cvt_p(ffi::EVP_CIPHER_CTX_new())?,
+                block_size: self.block_size,
+                wrap: self.wrap,
+                aead: self.aead,
+                iv_len: self.iv_len,
+                iv: self.iv.clone(),
+            };
+            cvt(ffi::EVP_CIPHER_CTX_copy(crypter.ctx, self.ctx))?;
+            Ok(crypter)
+        }
+    }
+
     /// Enables or disables padding.
     ///
     /// If padding is disabled, total amount of data encrypted/decrypted must
@@ -585,15 +1357,12 @@ impl Crypter {
     /// Panics for block ciphers if `output.len() < input.len() + block_size`,
     /// where `block_size` is the block size of the cipher (see `Cipher::block_size`).
     ///
+    /// Panics for the AES key wrap ciphers if `output.len() < input.len() + 16`.
+    ///
     /// Panics if `output.len() > c_int::max_value()`.
     pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, ErrorStack> {
         unsafe {
-            let block_size = if self.block_size > 1 {
-                self.block_size
-            } else {
-                0
-            };
-            assert!(output.len() >= input.len() + block_size);
+            assert!(output.len() >= input.len() + self.update_overhead());
             assert!(output.len() <= c_int::max_value() as usize);
             let mut outl = output.len() as c_int;
             let inl = input.len() as c_int;
@@ -623,7 +1392,8 @@ impl Crypter {
     /// where `block_size` is the block size of the cipher (see `Cipher::block_size`).
     pub fn finalize(&mut self, output: &mut [u8]) -> Result<usize, ErrorStack> {
         unsafe {
-            if self.block_size > 1 {
+            // the key wrap ciphers process all of their input in `update`
+            if self.block_size > 1 && !self.wrap {
                 assert!(output.len() >= self.block_size);
             }
             let mut outl = cmp::min(output.len(), c_int::max_value() as usize) as c_int;
@@ -638,6 +1408,18 @@ impl Crypter {
         }
     }
 
+    // The number of bytes by which the output of `update` can exceed its input.
+    fn update_overhead(&self) -> usize {
+        if self.wrap {
+            // wrapping with padding rounds up to a multiple of 8 bytes and adds 8 more
+            16
+        } else if self.block_size > 1 {
+            self.block_size
+        } else {
+            0
+        }
+    }
+
     /// Retrieves the authentication tag used to authenticate ciphertext in AEAD ciphers such
     /// as AES GCM.
     ///
@@ -754,7 +1536,7 @@ fn cipher(
     data: &[u8],
 ) -> Result<Vec<u8>, ErrorStack> {
     let mut c = Crypter::new(t, mode, key, iv)?;
-    let mut out = vec![0; data.len() + t.block_size()];
+    let mut out = vec![0; data.len() + c.update_overhead()];
     let count = c.update(data, &mut out)?;
     let rest = c.finalize(&mut out[count..])?;
     out.truncate(count + rest);
@@ -780,57 +1562,399 @@ pub fn encrypt_aead(
     let mut c = Crypter::new(t, Mode::Encrypt, key, iv)?;
     let mut out = vec![0; data.len() + t.block_size()];
 
-    let is_ccm = t.is_ccm();
-    if is_ccm || t.is_ocb() {
-        c.set_tag_len(tag.len())?;
-        if is_ccm {
-            c.set_data_len(data.len())?;
-        }
+    let is_ccm = t.is_ccm();
+    if is_ccm || t.is_ocb() {
+        c.set_tag_len(tag.len())?;
+        if is_ccm {
+            c.set_data_len(data.len())?;
+        }
+    }
+
+    c.aad_update(aad)?;
+    let count = c.update(data, &mut out)?;
+    let rest = c.finalize(&mut out[count..])?;
+    c.get_tag(tag)?;
+    out.truncate(count + rest);
+    Ok(out)
+}
+
+/// Like `decrypt`, but for AEAD ciphers such as AES GCM.
+///
+/// Additional Authenticated Data can be provided in the `aad` field, and the authentication tag
+/// should be provided in the `tag` field.
+pub fn decrypt_aead(
+    t: Cipher,
+    key: &[u8],
+    iv: Option<&[u8]>,
+    aad: &[u8],
+    data: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut c = Crypter::new(t, Mode::Decrypt, key, iv)?;
+    let mut out = vec![0; data.len() + t.block_size()];
+
+    let is_ccm = t.is_ccm();
+    if is_ccm || t.is_ocb() {
+        c.set_tag(tag)?;
+        if is_ccm {
+            c.set_data_len(data.len())?;
+        }
+    }
+
+    c.aad_update(aad)?;
+    let count = c.update(data, &mut out)?;
+
+    let rest = if t.is_ccm() {
+        0
+    } else {
+        c.set_tag(tag)?;
+        c.finalize(&mut out[count..])?
+    };
+
+    out.truncate(count + rest);
+    Ok(out)
+}
+
+// The amount of input processed at a time by `CryptoReader` and `CryptoWriter`.
+const STREAM_CHUNK: usize = 8 * 1024;
+
+// The state shared by `CryptoReader` and `CryptoWriter`.
+struct StreamCrypter {
+    crypter: Crypter,
+    mode: Mode,
+    tag_len: usize,
+    // when decrypting with an AEAD cipher, the last `tag_len` bytes seen, which may be the tag
+    held: Vec<u8>,
+    out: Vec<u8>,
+}
+
+impl StreamCrypter {
+    fn new(
+        cipher: Cipher,
+        mode: Mode,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<StreamCrypter, ErrorStack> {
+        assert!(!cipher.is_ccm(), "CCM mode cannot be used with streams");
+        assert!(
+            !cipher.is_wrap(),
+            "key wrap ciphers cannot be used with streams"
+        );
+
+        let tag_len = cipher.stream_tag_len();
+        Ok(StreamCrypter {
+            crypter: Crypter::new(cipher, mode, key, iv)?,
+            mode,
+            tag_len,
+            held: vec![],
+            out: vec![0; STREAM_CHUNK + cipher.block_size() + tag_len],
+        })
+    }
+
+    fn holds_tag(&self) -> bool {
+        match self.mode {
+            Mode::Decrypt => self.tag_len > 0,
+            Mode::Encrypt => false,
+        }
+    }
+
+    // `input` must be no longer than `STREAM_CHUNK`.
+    fn update(&mut self, input: &[u8]) -> Result<&[u8], ErrorStack> {
+        let count = if self.holds_tag() {
+            self.held.extend_from_slice(input);
+            let len = self.held.len().saturating_sub(self.tag_len);
+            let count = self.crypter.update(&self.held[..len], &mut self.out)?;
+            self.held.drain(..len);
+            count
+        } else {
+            self.crypter.update(input, &mut self.out)?
+        };
+        Ok(&self.out[..count])
+    }
+
+    fn finalize(&mut self) -> io::Result<&[u8]> {
+        if self.holds_tag() {
+            if self.held.len() < self.tag_len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream is too short to contain a tag",
+                ));
+            }
+            self.crypter.set_tag(&self.held)?;
+        }
+
+        let mut count = self.crypter.finalize(&mut self.out)?;
+        if let Mode::Encrypt = self.mode {
+            if self.tag_len > 0 {
+                self.crypter
+                    .get_tag(&mut self.out[count..count + self.tag_len])?;
+                count += self.tag_len;
+            }
+        }
+        Ok(&self.out[..count])
+    }
+}
+
+/// A reader which encrypts or decrypts the data read from another reader.
+///
+/// Padding is enabled by default, and the final block is produced once the inner reader reaches
+/// EOF. With AEAD ciphers other than CCM, such as AES GCM, the 16 byte tag is appended to the
+/// ciphertext when encrypting, and is expected at the end of the ciphertext when decrypting. CCM
+/// mode is not supported.
+///
+/// # Warning
+///
+/// When decrypting with an AEAD cipher, plaintext is returned before the tag has been verified. A
+/// failed verification is reported as an error by the final read, and all plaintext read from the
+/// stream must then be discarded.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::symm::{Cipher, CryptoReader, Mode};
+/// use std::io::Read;
+///
+/// let cipher = Cipher::aes_128_gcm();
+/// let key = [0; 16];
+/// let iv = [0; 12];
+///
+/// let plaintext = b"a very long stream of data";
+/// let mut reader =
+///     CryptoReader::new(&plaintext[..], cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+/// let mut ciphertext = vec![];
+/// reader.read_to_end(&mut ciphertext).unwrap();
+/// assert_eq!(ciphertext.len(), plaintext.len() + 16);
+///
+/// let mut reader =
+///     CryptoReader::new(&ciphertext[..], cipher, Mode::Decrypt, &key, Some(&iv)).unwrap();
+/// let mut decrypted = vec![];
+/// reader.read_to_end(&mut decrypted).unwrap();
+/// assert_eq!(decrypted, plaintext);
+/// ```
+pub struct CryptoReader<R> {
+    inner: R,
+    stream: StreamCrypter,
+    input: Vec<u8>,
+    // the unread output of the last call to the stream, in `stream.out`
+    pos: usize,
+    len: usize,
+    done: bool,
+    // errors from the cipher are returned again by later reads
+    error: Option<(io::ErrorKind, String)>,
+}
+
+impl<R: Read> CryptoReader<R> {
+    /// Creates a new `CryptoReader` wrapping `inner`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher uses CCM mode.
+    pub fn new(
+        inner: R,
+        cipher: Cipher,
+        mode: Mode,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<CryptoReader<R>, ErrorStack> {
+        Ok(CryptoReader {
+            inner,
+            stream: StreamCrypter::new(cipher, mode, key, iv)?,
+            input: vec![0; STREAM_CHUNK],
+            pos: 0,
+            len: 0,
+            done: false,
+            error: None,
+        })
+    }
+
+    /// Enables or disables padding.
+    ///
+    /// This must be called before any data is read.
+    pub fn pad(&mut self, padding: bool) {
+        self.stream.crypter.pad(padding);
+    }
+
+    /// Feeds Additional Authenticated Data (AAD) through an AEAD cipher.
+    ///
+    /// This must be called before any data is read.
+    pub fn aad_update(&mut self, aad: &[u8]) -> Result<(), ErrorStack> {
+        self.stream.crypter.aad_update(aad)
+    }
+
+    /// Returns a shared reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    ///
+    /// Reading from the inner reader directly will corrupt the stream.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `CryptoReader`, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CryptoReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.len {
+                let len = cmp::min(buf.len(), self.len - self.pos);
+                buf[..len].copy_from_slice(&self.stream.out[self.pos..self.pos + len]);
+                self.pos += len;
+                return Ok(len);
+            }
+            if let Some((kind, ref msg)) = self.error {
+                return Err(io::Error::new(kind, msg.clone()));
+            }
+            if self.done {
+                return Ok(0);
+            }
+
+            let n = self.inner.read(&mut self.input)?;
+            let r = if n == 0 {
+                self.stream.finalize()
+            } else {
+                self.stream
+                    .update(&self.input[..n])
+                    .map_err(io::Error::from)
+            };
+            match r {
+                Ok(out) => self.len = out.len(),
+                Err(e) => {
+                    self.error = Some((e.kind(), e.to_string()));
+                    return Err(e);
+                }
+            }
+            self.done = n == 0;
+            self.pos = 0;
+        }
+    }
+}
+
+/// A writer which encrypts or decrypts data before writing it to another writer.
+///
+/// Padding is enabled by default. The final block is written by [`finish`], or when the
+/// `CryptoWriter` is dropped, in which case any error is ignored. With AEAD ciphers other than
+/// CCM, such as AES GCM, the 16 byte tag is appended to the ciphertext when encrypting, and is
+/// expected at the end of the ciphertext when decrypting. CCM mode is not supported.
+///
+/// # Warning
+///
+/// When decrypting with an AEAD cipher, plaintext is written before the tag has been verified. A
+/// failed verification is reported as an error by [`finish`], and all plaintext written to the
+/// inner writer must then be discarded.
+///
+/// [`finish`]: #method.finish
+///
+/// # Examples
+///
+/// ```
+/// use openssl::symm::{decrypt, Cipher, CryptoWriter, Mode};
+/// use std::io::Write;
+///
+/// let cipher = Cipher::aes_128_cbc();
+/// let key = [0; 16];
+/// let iv = [0; 16];
+///
+/// let mut writer = CryptoWriter::new(vec![], cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+/// writer.write_all(b"a very long ").unwrap();
+/// writer.write_all(b"stream of data").unwrap();
+/// let ciphertext = writer.finish().unwrap();
+///
+/// let plaintext = decrypt(cipher, &key, Some(&iv), &ciphertext).unwrap();
+/// assert_eq!(plaintext, b"a very long stream of data");
+/// ```
+pub struct CryptoWriter<W: Write> {
+    inner: Option<W>,
+    stream: StreamCrypter,
+}
+
+impl<W: Write> CryptoWriter<W> {
+    /// Creates a new `CryptoWriter` wrapping `inner`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher uses CCM mode.
+    pub fn new(
+        inner: W,
+        cipher: Cipher,
+        mode: Mode,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<CryptoWriter<W>, ErrorStack> {
+        Ok(CryptoWriter {
+            inner: Some(inner),
+            stream: StreamCrypter::new(cipher, mode, key, iv)?,
+        })
+    }
+
+    /// Enables or disables padding.
+    ///
+    /// This must be called before any data is written.
+    pub fn pad(&mut self, padding: bool) {
+        self.stream.crypter.pad(padding);
     }
 
-    c.aad_update(aad)?;
-    let count = c.update(data, &mut out)?;
-    let rest = c.finalize(&mut out[count..])?;
-    c.get_tag(tag)?;
-    out.truncate(count + rest);
-    Ok(out)
-}
+    /// Feeds Additional Authenticated Data (AAD) through an AEAD cipher.
+    ///
+    /// This must be called before any data is written.
+    pub fn aad_update(&mut self, aad: &[u8]) -> Result<(), ErrorStack> {
+        self.stream.crypter.aad_update(aad)
+    }
 
-/// Like `decrypt`, but for AEAD ciphers such as AES GCM.
-///
-/// Additional Authenticated Data can be provided in the `aad` field, and the authentication tag
-/// should be provided in the `tag` field.
-pub fn decrypt_aead(
-    t: Cipher,
-    key: &[u8],
-    iv: Option<&[u8]>,
-    aad: &[u8],
-    data: &[u8],
-    tag: &[u8],
-) -> Result<Vec<u8>, ErrorStack> {
-    let mut c = Crypter::new(t, Mode::Decrypt, key, iv)?;
-    let mut out = vec![0; data.len() + t.block_size()];
+    /// Returns a shared reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
 
-    let is_ccm = t.is_ccm();
-    if is_ccm || t.is_ocb() {
-        c.set_tag(tag)?;
-        if is_ccm {
-            c.set_data_len(data.len())?;
-        }
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Writing to the inner writer directly will corrupt the stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().unwrap()
     }
 
-    c.aad_update(aad)?;
-    let count = c.update(data, &mut out)?;
+    /// Writes the final block, and the tag when encrypting with an AEAD cipher, then flushes and
+    /// returns the inner writer.
+    ///
+    /// When decrypting, this verifies the padding or tag.
+    pub fn finish(mut self) -> io::Result<W> {
+        let r = self.do_finish();
+        let inner = self.inner.take().unwrap();
+        r.map(|_| inner)
+    }
 
-    let rest = if t.is_ccm() {
-        0
-    } else {
-        c.set_tag(tag)?;
-        c.finalize(&mut out[count..])?
-    };
+    fn do_finish(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().unwrap();
+        inner.write_all(self.stream.finalize()?)?;
+        inner.flush()
+    }
+}
 
-    out.truncate(count + rest);
-    Ok(out)
+impl<W: Write> Write for CryptoWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let buf = &buf[..cmp::min(buf.len(), STREAM_CHUNK)];
+        let output = self.stream.update(buf)?;
+        self.inner.as_mut().unwrap().write_all(output)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for CryptoWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.do_finish();
+        }
+    }
 }
 
 cfg_if! {
@@ -1011,6 +2135,9 @@ mod tests {
 
     #[test]
     fn test_rc4() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         let pt = "0000000000000000000000000000000000000000000000000000000000000000000000000000";
         let ct = "A68686B04D686AA107BD8D4CAB191A3EEC0A6294BC78B60F65C25CB47BD7BB3A48EFC4D26BE4";
         let key = "97CD440324DA5FD1F7955C1C13B6B466";
@@ -1194,6 +2321,9 @@ mod tests {
 
     #[test]
     fn test_bf_cbc() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         // https://www.schneier.com/code/vectors.txt
 
         let pt = "37363534333231204E6F77206973207468652074696D6520666F722000000000";
@@ -1206,6 +2336,9 @@ mod tests {
 
     #[test]
     fn test_bf_ecb() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         let pt = "5CD54CA83DEF57DA";
         let ct = "B1B8CC0B250F09A0";
         let key = "0131D9619DC1376E";
@@ -1216,6 +2349,9 @@ mod tests {
 
     #[test]
     fn test_bf_cfb64() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         let pt = "37363534333231204E6F77206973207468652074696D6520666F722000";
         let ct = "E73214A2822139CAF26ECF6D2EB9E76E3DA3DE04D1517200519D57A6C3";
         let key = "0123456789ABCDEFF0E1D2C3B4A59687";
@@ -1226,6 +2362,9 @@ mod tests {
 
     #[test]
     fn test_bf_ofb() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         let pt = "37363534333231204E6F77206973207468652074696D6520666F722000";
         let ct = "E73214A2822139CA62B343CC5B65587310DD908D0C241B2263C2CF80DA";
         let key = "0123456789ABCDEFF0E1D2C3B4A59687";
@@ -1236,6 +2375,9 @@ mod tests {
 
     #[test]
     fn test_des_cbc() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         let pt = "54686973206973206120746573742e";
         let ct = "6f2867cfefda048a4046ef7e556c7132";
         let key = "7cb66337f3d3c0fe";
@@ -1246,6 +2388,9 @@ mod tests {
 
     #[test]
     fn test_des_ecb() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
         let pt = "54686973206973206120746573742e";
         let ct = "0050ab8aecec758843fe157b4dde938c";
         let key = "7cb66337f3d3c0fe";
@@ -1495,7 +2640,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     fn test_chacha20() {
         let key = "0000000000000000000000000000000000000000000000000000000000000000";
         let iv = "00000000000000000000000000000000";
@@ -1510,7 +2655,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(any(ossl110))]
+    #[cfg(ossl110)]
     fn test_chacha20_poly1305() {
         let key = "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f";
         let iv = "070000004041424344454647";
@@ -1549,4 +2694,692 @@ mod tests {
         .unwrap();
         assert_eq!(pt, hex::encode(out));
     }
+
+    #[test]
+    fn from_name() {
+        assert!(Cipher::from_name("aes-128-cbc") == Some(Cipher::aes_128_cbc()));
+        assert!(Cipher::from_name("AES256") == Some(Cipher::aes_256_cbc()));
+        assert!(Cipher::from_name("not-a-cipher").is_none());
+    }
+
+    #[test]
+    fn all() {
+        let ciphers = Cipher::all();
+        assert!(ciphers.contains(&Cipher::aes_128_cbc()));
+        assert!(ciphers.contains(&Cipher::aes_256_gcm()));
+        for (i, cipher) in ciphers.iter().enumerate() {
+            assert!(!ciphers[..i].contains(cipher));
+        }
+        assert_eq!(Cipher::aes_128_cbc().nid(), Nid::AES_128_CBC);
+    }
+
+    fn stream_data() -> Vec<u8> {
+        (0..3 * STREAM_CHUNK + 5).map(|i| i as u8).collect()
+    }
+
+    fn write_stream(cipher: Cipher, mode: Mode, key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut writer = CryptoWriter::new(vec![], cipher, mode, key, Some(iv)).unwrap();
+        // uneven chunks exercise buffering across writes
+        for chunk in data.chunks(1000) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn read_stream(
+        cipher: Cipher,
+        mode: Mode,
+        key: &[u8],
+        iv: &[u8],
+        data: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        let mut reader = CryptoReader::new(data, cipher, mode, key, Some(iv)).unwrap();
+        let mut out = vec![];
+        let mut buf = [0; 777];
+        loop {
+            match reader.read(&mut buf)? {
+                0 => return Ok(out),
+                n => out.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    #[test]
+    fn crypto_stream_cbc() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [1; 16];
+        let iv = [2; 16];
+        let data = stream_data();
+
+        let ciphertext = write_stream(cipher, Mode::Encrypt, &key, &iv, &data);
+        assert_eq!(ciphertext, encrypt(cipher, &key, Some(&iv), &data).unwrap());
+        assert_eq!(
+            read_stream(cipher, Mode::Encrypt, &key, &iv, &data).unwrap(),
+            ciphertext
+        );
+
+        assert_eq!(
+            write_stream(cipher, Mode::Decrypt, &key, &iv, &ciphertext),
+            data
+        );
+        assert_eq!(
+            read_stream(cipher, Mode::Decrypt, &key, &iv, &ciphertext).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn crypto_stream_gcm() {
+        let cipher = Cipher::aes_256_gcm();
+        let key = [1; 32];
+        let iv = [2; 12];
+        let data = stream_data();
+
+        let mut tag = [0; 16];
+        let mut expected = encrypt_aead(cipher, &key, Some(&iv), &[], &data, &mut tag).unwrap();
+        expected.extend_from_slice(&tag);
+
+        let ciphertext = write_stream(cipher, Mode::Encrypt, &key, &iv, &data);
+        assert_eq!(ciphertext, expected);
+        assert_eq!(
+            read_stream(cipher, Mode::Encrypt, &key, &iv, &data).unwrap(),
+            ciphertext
+        );
+
+        assert_eq!(
+            write_stream(cipher, Mode::Decrypt, &key, &iv, &ciphertext),
+            data
+        );
+        assert_eq!(
+            read_stream(cipher, Mode::Decrypt, &key, &iv, &ciphertext).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn crypto_stream_aad() {
+        let cipher = Cipher::aes_128_gcm();
+        let key = [1; 16];
+        let iv = [2; 12];
+
+        let mut writer = CryptoWriter::new(vec![], cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+        writer.aad_update(b"header").unwrap();
+        writer.write_all(b"body").unwrap();
+        let ciphertext = writer.finish().unwrap();
+
+        let mut tag = [0; 16];
+        let expected = encrypt_aead(cipher, &key, Some(&iv), b"header", b"body", &mut tag).unwrap();
+        assert_eq!(&ciphertext[..4], &expected[..]);
+        assert_eq!(&ciphertext[4..], &tag[..]);
+
+        let mut reader =
+            CryptoReader::new(&ciphertext[..], cipher, Mode::Decrypt, &key, Some(&iv)).unwrap();
+        reader.aad_update(b"header").unwrap();
+        let mut plaintext = vec![];
+        reader.read_to_end(&mut plaintext).unwrap();
+        assert_eq!(plaintext, b"body");
+    }
+
+    #[test]
+    fn crypto_stream_bad_tag() {
+        let cipher = Cipher::aes_128_gcm();
+        let key = [1; 16];
+        let iv = [2; 12];
+        let data = stream_data();
+
+        let mut ciphertext = write_stream(cipher, Mode::Encrypt, &key, &iv, &data);
+        *ciphertext.last_mut().unwrap() ^= 1;
+        assert!(read_stream(cipher, Mode::Decrypt, &key, &iv, &ciphertext).is_err());
+
+        let mut writer = CryptoWriter::new(vec![], cipher, Mode::Decrypt, &key, Some(&iv)).unwrap();
+        writer.write_all(&ciphertext).unwrap();
+        assert!(writer.finish().is_err());
+
+        let err = read_stream(cipher, Mode::Decrypt, &key, &iv, &[0; 15]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn crypto_reader_error_is_sticky() {
+        let cipher = Cipher::aes_128_gcm();
+        let key = [1; 16];
+        let iv = [2; 12];
+
+        let mut ciphertext = write_stream(cipher, Mode::Encrypt, &key, &iv, b"body");
+        *ciphertext.last_mut().unwrap() ^= 1;
+
+        let mut reader =
+            CryptoReader::new(&ciphertext[..], cipher, Mode::Decrypt, &key, Some(&iv)).unwrap();
+        let mut plaintext = vec![];
+        assert!(reader.read_to_end(&mut plaintext).is_err());
+        // later reads must not report a clean EOF
+        assert!(reader.read(&mut [0; 16]).is_err());
+        assert!(reader.read_to_end(&mut plaintext).is_err());
+
+        let mut reader =
+            CryptoReader::new(&[0; 15][..], cipher, Mode::Decrypt, &key, Some(&iv)).unwrap();
+        assert_eq!(
+            reader.read(&mut [0; 16]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            reader.read(&mut [0; 16]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn crypto_writer_finishes_on_drop() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [1; 16];
+        let iv = [2; 16];
+
+        let mut ciphertext = vec![];
+        {
+            let mut writer =
+                CryptoWriter::new(&mut ciphertext, cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+            writer.write_all(b"hello").unwrap();
+        }
+        assert_eq!(
+            ciphertext,
+            encrypt(cipher, &key, Some(&iv), b"hello").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn crypto_stream_chacha20_poly1305() {
+        let cipher = Cipher::chacha20_poly1305();
+        let key = [1; 32];
+        let iv = [2; 12];
+        let data = stream_data();
+
+        let ciphertext = write_stream(cipher, Mode::Encrypt, &key, &iv, &data);
+        assert_eq!(ciphertext.len(), data.len() + 16);
+        assert_eq!(
+            read_stream(cipher, Mode::Decrypt, &key, &iv, &ciphertext).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    #[cfg(ossl102)]
+    fn test_aes_128_wrap() {
+        // RFC 3394 section 4.1
+        let kek = Vec::from_hex("000102030405060708090A0B0C0D0E0F").unwrap();
+        let key = Vec::from_hex("00112233445566778899AABBCCDDEEFF").unwrap();
+        let wrapped = "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5";
+
+        let out = encrypt(Cipher::aes_128_wrap(), &kek, None, &key).unwrap();
+        assert_eq!(hex::encode(&out), wrapped);
+        let out = decrypt(Cipher::aes_128_wrap(), &kek, None, &out).unwrap();
+        assert_eq!(out, key);
+    }
+
+    #[test]
+    #[cfg(ossl102)]
+    fn test_aes_192_wrap_pad() {
+        // RFC 5649 section 6
+        let kek = Vec::from_hex("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8").unwrap();
+
+        let key = Vec::from_hex("c37b7e6492584340bed12207808941155068f738").unwrap();
+        let wrapped = "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a";
+        let out = encrypt(Cipher::aes_192_wrap_pad(), &kek, None, &key).unwrap();
+        assert_eq!(hex::encode(&out), wrapped);
+        let out = decrypt(Cipher::aes_192_wrap_pad(), &kek, None, &out).unwrap();
+        assert_eq!(out, key);
+
+        let key = Vec::from_hex("466f7250617369").unwrap();
+        let wrapped = "afbeb0f07dfbf5419200f2ccb50bb24f";
+        let out = encrypt(Cipher::aes_192_wrap_pad(), &kek, None, &key).unwrap();
+        assert_eq!(hex::encode(&out), wrapped);
+        let out = decrypt(Cipher::aes_192_wrap_pad(), &kek, None, &out).unwrap();
+        assert_eq!(out, key);
+
+        let mut tampered = Vec::from_hex(wrapped).unwrap();
+        tampered[0] ^= 1;
+        assert!(decrypt(Cipher::aes_192_wrap_pad(), &kek, None, &tampered).is_err());
+    }
+
+    #[test]
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    fn test_camellia_ecb() {
+        // RFC 3713 appendix A
+        let pt = "0123456789abcdeffedcba9876543210";
+
+        cipher_test_nopad(
+            Cipher::camellia_128_ecb(),
+            pt,
+            "67673138549669730857065648eabe43",
+            "0123456789abcdeffedcba9876543210",
+            "",
+        );
+        cipher_test_nopad(
+            Cipher::camellia_192_ecb(),
+            pt,
+            "b4993401b3e996f84ee5cee7d79b09b9",
+            "0123456789abcdeffedcba98765432100011223344556677",
+            "",
+        );
+        cipher_test_nopad(
+            Cipher::camellia_256_ecb(),
+            pt,
+            "9acc237dff16d76c20ef7c919e3a7509",
+            "0123456789abcdeffedcba987654321000112233445566778899aabbccddeeff",
+            "",
+        );
+    }
+
+    #[test]
+    #[cfg(not(osslconf = "OPENSSL_NO_CAMELLIA"))]
+    fn test_camellia_128_cbc() {
+        let pt = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+        let ct = "ca7fc7990a0ae400ef01987191a37d9adec8d01b8a79d023c19e4edf9f4833ee";
+        let key = "2b7e151628aed2a6abf7158809cf4f3c";
+        let iv = "000102030405060708090a0b0c0d0e0f";
+
+        cipher_test_nopad(Cipher::camellia_128_cbc(), pt, ct, key, iv);
+    }
+
+    #[test]
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    fn test_aria_ecb() {
+        // RFC 5794 appendix A
+        let pt = "00112233445566778899aabbccddeeff";
+
+        cipher_test_nopad(
+            Cipher::aria_128_ecb(),
+            pt,
+            "d718fbd6ab644c739da95f3be6451778",
+            "000102030405060708090a0b0c0d0e0f",
+            "",
+        );
+        cipher_test_nopad(
+            Cipher::aria_256_ecb(),
+            pt,
+            "f92bd7c79fb72e2f2b8f80c1972d24fc",
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "",
+        );
+    }
+
+    #[test]
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    fn test_aria_256_ctr() {
+        // The keystream is the encryption of successive counter blocks, so with the RFC 5794
+        // appendix A.3 plaintext as the initial counter, the first block of keystream is the
+        // ciphertext given there. The second block encrypts the incremented counter.
+        let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+        let iv = "00112233445566778899aabbccddeeff";
+        let pt = "0000000000000000000000000000000000000000000000000000000000000000";
+
+        let counter = Vec::from_hex("00112233445566778899aabbccddef00").unwrap();
+        let mut crypter = Crypter::new(
+            Cipher::aria_256_ecb(),
+            Mode::Encrypt,
+            &Vec::from_hex(key).unwrap(),
+            None,
+        )
+        .unwrap();
+        crypter.pad(false);
+        let mut block = vec![0; 32];
+        let count = crypter.update(&counter, &mut block).unwrap();
+        let ct = format!(
+            "f92bd7c79fb72e2f2b8f80c1972d24fc{}",
+            hex::encode(&block[..count])
+        );
+
+        cipher_test(Cipher::aria_256_ctr(), pt, &ct, key, iv);
+    }
+
+    #[test]
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_ARIA")))]
+    fn test_aria_aead() {
+        let key = [7; 16];
+        let nonce = [9; 12];
+        let aad = b"header";
+        let pt = b"some plaintext which is longer than a block";
+
+        for &cipher in &[Cipher::aria_128_gcm(), Cipher::aria_128_ccm()] {
+            let mut tag = [0; 12];
+            let ct = encrypt_aead(cipher, &key, Some(&nonce), aad, pt, &mut tag).unwrap();
+            assert_ne!(&ct[..], &pt[..]);
+            let out = decrypt_aead(cipher, &key, Some(&nonce), aad, &ct, &tag).unwrap();
+            assert_eq!(&out[..], &pt[..]);
+
+            tag[0] ^= 1;
+            assert!(decrypt_aead(cipher, &key, Some(&nonce), aad, &ct, &tag).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(all(ossl111, not(osslconf = "OPENSSL_NO_SM4")))]
+    fn test_sm4() {
+        // GB/T 32907-2016 appendix A
+        cipher_test_nopad(
+            Cipher::sm4_ecb(),
+            "0123456789abcdeffedcba9876543210",
+            "681edf34d206965e86b3e94f536e4246",
+            "0123456789abcdeffedcba9876543210",
+            "",
+        );
+
+        // draft-ribose-cfrg-sm4 appendix A.2.2
+        let pt = "aaaaaaaabbbbbbbbccccccccddddddddeeeeeeeeffffffffaaaaaaaabbbbbbbb";
+        let iv = "000102030405060708090a0b0c0d0e0f";
+        cipher_test_nopad(
+            Cipher::sm4_cbc(),
+            pt,
+            "78ebb11cc40b0a48312aaeb2040244cb4cb7016951909226979b0d15dc6a8f6d",
+            "0123456789abcdeffedcba9876543210",
+            iv,
+        );
+        cipher_test_nopad(
+            Cipher::sm4_cbc(),
+            pt,
+            "0d3a6ddc2d21c698857215587b7bb59a91f2c147911a4144665e1fa1d40bae38",
+            "fedcba98765432100123456789abcdef",
+            iv,
+        );
+    }
+
+    #[test]
+    #[cfg(not(osslconf = "OPENSSL_NO_SEED"))]
+    fn test_seed_ecb() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
+        // RFC 4269 appendix B
+        let pt = "000102030405060708090a0b0c0d0e0f";
+        let ct = "5ebac6e0054e166819aff1cc6d346cdb";
+        let key = "00000000000000000000000000000000";
+
+        cipher_test_nopad(Cipher::seed_ecb(), pt, ct, key, "");
+    }
+
+    #[test]
+    #[cfg(not(osslconf = "OPENSSL_NO_CAST"))]
+    fn test_cast5_ecb() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
+        // RFC 2144 appendix B.1
+        let pt = "0123456789abcdef";
+        let ct = "238b4fe5847e44b2";
+        let key = "0123456712345678234567893456789a";
+
+        cipher_test_nopad(Cipher::cast5_ecb(), pt, ct, key, "");
+    }
+
+    fn crypt(crypter: &mut Crypter, data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0; data.len() + 16];
+        let count = crypter.update(data, &mut out).unwrap();
+        let rest = crypter.finalize(&mut out[count..]).unwrap();
+        out.truncate(count + rest);
+        out
+    }
+
+    #[test]
+    fn crypter_reinit() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [1; 16];
+        let data = b"some data which spans several blocks";
+
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &key, Some(&[2; 16])).unwrap();
+        crypter.update(data, &mut [0; 64]).unwrap();
+        for &iv in &[[3; 16], [4; 16]] {
+            crypter.reinit(&iv).unwrap();
+            assert_eq!(
+                crypt(&mut crypter, data),
+                encrypt(cipher, &key, Some(&iv), data).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn crypter_reinit_gcm() {
+        let cipher = Cipher::aes_128_gcm();
+        let key = [1; 16];
+        let data = b"packet";
+
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &key, Some(&[0; 12])).unwrap();
+        for i in 0..3 {
+            let iv = [i; 12];
+            crypter.reinit(&iv).unwrap();
+            crypter.aad_update(b"aad").unwrap();
+            let out = crypt(&mut crypter, data);
+            let mut tag = [0; 16];
+            crypter.get_tag(&mut tag).unwrap();
+
+            let mut expected_tag = [0; 16];
+            let expected =
+                encrypt_aead(cipher, &key, Some(&iv), b"aad", data, &mut expected_tag).unwrap();
+            assert_eq!(out, expected);
+            assert_eq!(tag, expected_tag);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "IV has the wrong length")]
+    fn crypter_reinit_wrong_iv_len() {
+        let mut crypter = Crypter::new(
+            Cipher::aes_128_cbc(),
+            Mode::Encrypt,
+            &[1; 16],
+            Some(&[2; 16]),
+        )
+        .unwrap();
+        let _ = crypter.reinit(&[3; 8]);
+    }
+
+    #[test]
+    fn crypter_reset() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [1; 16];
+        let iv = [2; 16];
+        let data = b"some data which spans several blocks";
+
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+        crypter.update(&data[..20], &mut [0; 64]).unwrap();
+        crypter.reset().unwrap();
+        assert_eq!(
+            crypt(&mut crypter, data),
+            encrypt(cipher, &key, Some(&iv), data).unwrap()
+        );
+    }
+
+    #[test]
+    fn crypter_reset_ctr() {
+        let cipher = Cipher::aes_128_ctr();
+        let key = [1; 16];
+        let iv = [2; 16];
+        let data = b"some data which spans several blocks";
+
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+        crypter.update(&data[..20], &mut [0; 64]).unwrap();
+        crypter.reset().unwrap();
+        assert_eq!(
+            crypt(&mut crypter, data),
+            encrypt(cipher, &key, Some(&iv), data).unwrap()
+        );
+
+        // the IV passed to reinit is the one restored
+        let iv = [3; 16];
+        crypter.reinit(&iv).unwrap();
+        crypter.update(&data[..20], &mut [0; 64]).unwrap();
+        crypter.reset().unwrap();
+        assert_eq!(
+            crypt(&mut crypter, data),
+            encrypt(cipher, &key, Some(&iv), data).unwrap()
+        );
+    }
+
+    #[test]
+    fn crypter_reset_aead() {
+        let mut crypter = Crypter::new(
+            Cipher::aes_128_gcm(),
+            Mode::Encrypt,
+            &[1; 16],
+            Some(&[2; 12]),
+        )
+        .unwrap();
+        assert!(crypter.reset().is_err());
+        crypter.reinit(&[3; 12]).unwrap();
+
+        #[cfg(ossl110)]
+        {
+            let mut crypter = Crypter::new(
+                Cipher::chacha20_poly1305(),
+                Mode::Encrypt,
+                &[1; 32],
+                Some(&[2; 12]),
+            )
+            .unwrap();
+            assert!(crypter.reset().is_err());
+        }
+
+        // fetched AEAD ciphers are recognized as well
+        #[cfg(ossl300)]
+        {
+            let cipher = Cipher::fetch(None, "AES-256-GCM", None).unwrap();
+            let mut crypter =
+                Crypter::with_cipher(&cipher, Mode::Encrypt, &[1; 32], Some(&[2; 12])).unwrap();
+            assert!(crypter.reset().is_err());
+        }
+    }
+
+    #[test]
+    fn crypter_set_key() {
+        let cipher = Cipher::aes_256_ctr();
+        let iv = [2; 16];
+        let data = b"some data";
+
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &[1; 32], Some(&iv)).unwrap();
+        crypter.update(data, &mut [0; 64]).unwrap();
+        crypter.set_key(&[3; 32]).unwrap();
+        assert_eq!(
+            crypt(&mut crypter, data),
+            encrypt(cipher, &[3; 32], Some(&iv), data).unwrap()
+        );
+    }
+
+    #[test]
+    fn crypter_set_key_gcm() {
+        let cipher = Cipher::aes_128_gcm();
+        let iv = [2; 12];
+        let data = b"some data";
+
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &[1; 16], Some(&iv)).unwrap();
+        crypter.update(data, &mut [0; 64]).unwrap();
+        crypter.set_key(&[3; 16]).unwrap();
+        let out = crypt(&mut crypter, data);
+        let mut tag = [0; 16];
+        crypter.get_tag(&mut tag).unwrap();
+
+        let mut expected_tag = [0; 16];
+        let expected =
+            encrypt_aead(cipher, &[3; 16], Some(&iv), &[], data, &mut expected_tag).unwrap();
+        assert_eq!(out, expected);
+        assert_eq!(tag, expected_tag);
+    }
+
+    #[test]
+    fn crypter_copy() {
+        let cipher = Cipher::aes_128_cbc();
+        let key = [1; 16];
+        let iv = [2; 16];
+        let data = b"some data which spans several blocks";
+
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+        let mut out = vec![0; 64];
+        let count = crypter.update(&data[..20], &mut out).unwrap();
+        out.truncate(count);
+
+        let mut copy = crypter.copy().unwrap();
+        let expected = encrypt(cipher, &key, Some(&iv), data).unwrap();
+        for crypter in &mut [crypter, copy.copy().unwrap()] {
+            let mut out = out.clone();
+            out.extend(crypt(crypter, &data[20..]));
+            assert_eq!(out, expected);
+        }
+
+        copy.reinit(&[3; 16]).unwrap();
+        assert_eq!(
+            crypt(&mut copy, data),
+            encrypt(cipher, &key, Some(&[3; 16]), data).unwrap()
+        );
+    }
+
+    #[cfg(ossl300)]
+    fn crypt_fetched(
+        cipher: &FetchedCipher<'_>,
+        mode: Mode,
+        key: &[u8],
+        iv: &[u8],
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut crypter = Crypter::with_cipher(cipher, mode, key, Some(iv)).unwrap();
+        let mut out = vec![0; data.len() + cipher.block_size()];
+        let count = crypter.update(data, &mut out).unwrap();
+        let rest = crypter.finalize(&mut out[count..]).unwrap();
+        out.truncate(count + rest);
+        out
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetch() {
+        let cipher = Cipher::fetch(None, "AES-128-CBC", None).unwrap();
+        assert_eq!(cipher.nid(), Nid::AES_128_CBC);
+        assert_eq!(cipher.key_len(), 16);
+        assert_eq!(cipher.iv_len(), Some(16));
+
+        let key = [1; 16];
+        let iv = [2; 16];
+        let data = b"fetched ciphers match the built-in tables";
+        assert_eq!(
+            crypt_fetched(&cipher.clone(), Mode::Encrypt, &key, &iv, data),
+            encrypt(Cipher::aes_128_cbc(), &key, Some(&iv), data).unwrap()
+        );
+
+        // CTS modes have no dedicated constructor
+        let cts = Cipher::fetch(None, "AES-128-CBC-CTS", Some("provider=default")).unwrap();
+        drop(cipher);
+        let ciphertext = crypt_fetched(&cts, Mode::Encrypt, &key, &iv, data);
+        assert_eq!(ciphertext.len(), data.len());
+        assert_eq!(
+            crypt_fetched(&cts, Mode::Decrypt, &key, &iv, &ciphertext),
+            &data[..]
+        );
+
+        // without a partial block, the default CS1 variant matches CBC
+        assert_eq!(
+            crypt_fetched(&cts, Mode::Encrypt, &key, &iv, &data[..32]),
+            encrypt(Cipher::aes_128_cbc(), &key, Some(&iv), &data[..32]).unwrap()[..32]
+        );
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetch_lib_ctx() {
+        use crate::lib_ctx::LibCtx;
+        use crate::provider::Provider;
+
+        let ctx = LibCtx::new().unwrap();
+        let _default = Provider::load(Some(&ctx), "default").unwrap();
+        let cipher = Cipher::fetch(Some(&ctx), "AES-128-CBC", None).unwrap();
+        let mut crypter =
+            Crypter::with_cipher(&cipher, Mode::Encrypt, &[1; 16], Some(&[2; 16])).unwrap();
+        drop(cipher);
+
+        let mut out = [0; 32];
+        assert_eq!(crypter.update(&[3; 16], &mut out).unwrap(), 16);
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetch_missing() {
+        assert!(Cipher::fetch(None, "NOT-A-CIPHER", None).is_err());
+        assert!(Cipher::fetch(None, "AES-128-CBC", Some("provider=missing")).is_err());
+    }
 }
@@ -474,6 +474,27 @@ impl X509Ref {
         }
     }
 
+    /// Returns the DER-encoded SubjectPublicKeyInfo structure of the certificate.
+    ///
+    /// This is the input hashed to produce HTTP Public Key Pinning style SPKI pins.
+    ///
+    /// Unlike `public_key`, the structure is encoded exactly as it appears in the certificate, so
+    /// keys which OpenSSL cannot decode are supported as well.
+    ///
+    /// This corresponds to [`X509_get_X509_PUBKEY`] followed by [`i2d_X509_PUBKEY`].
+    ///
+    /// [`X509_get_X509_PUBKEY`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_get_X509_PUBKEY.html
+    /// [`i2d_X509_PUBKEY`]: https://www.openssl.org/docs/man1.1.0/crypto/i2d_X509_PUBKEY.html
+    pub fn public_key_info_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let key = cvt_p(X509_get_X509_PUBKEY(self.as_ptr()))?;
+            let len = cvt(ffi::i2d_X509_PUBKEY(key, ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2d_X509_PUBKEY(key, &mut buf.as_mut_ptr()))?;
+            Ok(buf)
+        }
+    }
+
     /// Returns a digest of the DER representation of the certificate.
     ///
     /// This corresponds to [`X509_digest`].
@@ -1507,8 +1528,16 @@ impl Stackable for X509Object {
 
 cfg_if! {
     if #[cfg(any(ossl110, libressl273))] {
-        use ffi::{X509_getm_notAfter, X509_getm_notBefore, X509_up_ref, X509_get0_signature};
+        use ffi::{
+            X509_getm_notAfter, X509_getm_notBefore, X509_up_ref, X509_get0_signature,
+            X509_get_X509_PUBKEY,
+        };
     } else {
+        #[allow(bad_style)]
+        unsafe fn X509_get_X509_PUBKEY(x: *const ffi::X509) -> *mut ffi::X509_PUBKEY {
+            (*(*x).cert_info).key
+        }
+
         #[allow(bad_style)]
         unsafe fn X509_getm_notAfter(x: *mut ffi::X509) -> *mut ffi::ASN1_TIME {
             (*(*(*x).cert_info).validity).notAfter
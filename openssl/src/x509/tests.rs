@@ -4,6 +4,7 @@ use crate::hash::MessageDigest;
 use crate::nid::Nid;
 use crate::pkey::{PKey, Private};
 use crate::rsa::Rsa;
+use crate::sha::sha256;
 use crate::stack::Stack;
 use crate::x509::extension::{
     AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
@@ -65,6 +66,22 @@ fn test_save_der() {
     assert!(!der.is_empty());
 }
 
+#[test]
+fn test_public_key_info_der() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+
+    let spki = cert.public_key_info_der().unwrap();
+    assert_eq!(
+        spki,
+        cert.public_key().unwrap().public_key_to_der().unwrap()
+    );
+    assert_eq!(
+        hex::encode(sha256(&spki)),
+        "5474006cd97aee79a464934449e4cabd3c5be5b42677599a5a730a1bfb637006"
+    );
+}
+
 #[test]
 fn test_subject_read_cn() {
     let cert = include_bytes!("../../test/cert.pem");
@@ -278,7 +295,7 @@ fn x509_req_builder() {
     let name = name.build();
 
     let mut builder = X509Req::builder().unwrap();
-    builder.set_version(2).unwrap();
+    builder.set_version(0).unwrap();
     builder.set_subject_name(&name).unwrap();
     builder.set_pubkey(&pkey).unwrap();
 
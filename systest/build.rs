@@ -53,6 +53,7 @@ fn main() {
         .header("openssl/x509v3.h")
         .header("openssl/safestack.h")
         .header("openssl/hmac.h")
+        .header("openssl/cmac.h")
         .header("openssl/ssl.h")
         .header("openssl/err.h")
         .header("openssl/rand.h")
@@ -61,10 +62,17 @@ fn main() {
         .header("openssl/aes.h")
         .header("openssl/ocsp.h")
         .header("openssl/evp.h")
+        .header("openssl/kdf.h")
         .header("openssl/x509_vfy.h");
 
-    if openssl_version.is_some() {
+    if let Some(version) = openssl_version {
         cfg.header("openssl/cms.h");
+        if version >= 0x3_00_00_00_0 {
+            cfg.header("openssl/provider.h");
+            // OpenSSL 3.0 constified the arguments of many functions which are still declared with
+            // mutable pointers here.
+            cfg.flag("-Wno-incompatible-pointer-types");
+        }
     }
 
     #[allow(clippy::if_same_then_else)]
@@ -102,6 +110,8 @@ fn main() {
     });
     cfg.skip_fn(move |s| {
         s == "CRYPTO_memcmp" ||                 // uses volatile
+        s == "SSL_CTX_get_info_callback" ||     // returns a function pointer
+        s == "SSL_get_verify_callback" ||       // returns a function pointer
 
         // Skip some functions with function pointers on windows, not entirely
         // sure how to get them to work out...